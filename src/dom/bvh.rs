@@ -0,0 +1,627 @@
+//! Bounding volume hierarchy.
+
+use core::ops::Range;
+use nalgebra::Point3;
+
+use crate::{
+    dom::{BvhBuilder, Surface},
+    geom::{Collide, Cube, Shape, Trace},
+    rt::{Hit, Ray},
+    util::ProgressBar,
+};
+
+/// Default number of bins used to evaluate splits along each axis.
+const DEFAULT_BINS: usize = 16;
+/// Default depth down to which nodes are built in parallel.
+const DEFAULT_PAR_DEPTH: u32 = 6;
+/// Cost of traversing a branch, relative to the cost of a Ray-Shape intersection test.
+const TRAVERSAL_COST: f64 = 1.0;
+
+/// Hierarchy node enumeration.
+#[allow(clippy::exhaustive_enums)]
+pub enum BvhNode {
+    /// Branching node.
+    Branch {
+        /// Boundary.
+        boundary: Cube,
+        /// Indices of the child nodes.
+        children: [usize; 2],
+    },
+    /// Terminal node.
+    Leaf {
+        /// Boundary.
+        boundary: Cube,
+        /// Range of the contained shape indices.
+        shapes: Range<usize>,
+    },
+}
+
+impl BvhNode {
+    /// Reference the node's boundary.
+    #[allow(clippy::missing_const_for_fn)]
+    #[inline]
+    #[must_use]
+    pub fn boundary(&self) -> &Cube {
+        match *self {
+            Self::Branch { ref boundary, .. } | Self::Leaf { ref boundary, .. } => boundary,
+        }
+    }
+}
+
+/// Binary hierarchy of bounding volumes, partitioned using the surface area heuristic.
+/// Each bounded shape is referenced by exactly one leaf.
+/// Unbounded shapes, such as planes, are held separately and tested against every Ray.
+/// Shapes of any type may be used, which must implement `Collide` to be sorted and `Trace` to be scanned.
+pub struct Bvh<'a, T, S = Shape> {
+    /// Nodes, starting with the root.
+    nodes: Vec<BvhNode>,
+    /// Bounded shapes and their corresponding attributes, ordered by leaf.
+    shapes: Vec<(&'a S, &'a T)>,
+    /// Unbounded shapes and their corresponding attributes.
+    unbounded: Vec<(&'a S, &'a T)>,
+}
+
+/// Shape being sorted into the hierarchy.
+struct Item<'a, T, S> {
+    /// Shape.
+    shape: &'a S,
+    /// Attribute.
+    attr: &'a T,
+    /// Bounding box.
+    bounds: Cube,
+    /// Bounding box centre.
+    centre: Point3<f64>,
+}
+
+/// Candidate partition of a node's triangles.
+struct Split {
+    /// Axis index.
+    axis: usize,
+    /// Minimum centroid coordinate along the axis.
+    min: f64,
+    /// Centroid extent along the axis.
+    extent: f64,
+    /// Number of bins.
+    bins: usize,
+    /// First bin placed in the right child.
+    bin: usize,
+}
+
+impl Split {
+    /// Determine the bin of a given position.
+    #[inline]
+    #[must_use]
+    fn bin_of(&self, pos: &Point3<f64>) -> usize {
+        ((((pos[self.axis] - self.min) / self.extent) * self.bins as f64) as usize)
+            .min(self.bins - 1)
+    }
+
+    /// Determine if a given position falls into the left child.
+    #[inline]
+    #[must_use]
+    fn is_left(&self, pos: &Point3<f64>) -> bool {
+        self.bin_of(pos) < self.bin
+    }
+}
+
+impl<'a, T, S> Bvh<'a, T, S> {
+    /// Construct a new instance.
+    /// Nodes are built in parallel down to the parallel depth of the settings.
+    /// If none of the shapes are bounded, the root is an empty leaf with a point boundary at the origin,
+    /// and only the unbounded shapes are tested.
+    #[inline]
+    #[must_use]
+    pub fn new(sett: &BvhBuilder, surfs: &'a [Surface<T, S>]) -> Self
+    where
+        T: Sync,
+        S: Collide + Sync,
+    {
+        let mut items = Vec::new();
+        let mut unbounded = Vec::new();
+        for surf in surfs {
+            for shape in &surf.shapes {
+                match shape.bounds() {
+                    Some(bounds) => {
+                        let centre = bounds.centre();
+                        items.push(Item {
+                            shape,
+                            attr: surf.attr,
+                            bounds,
+                            centre,
+                        });
+                    }
+                    None => unbounded.push((shape, surf.attr)),
+                }
+            }
+        }
+        if items.is_empty() {
+            let origin = Point3::origin();
+            return Self {
+                nodes: vec![BvhNode::Leaf {
+                    boundary: Cube {
+                        mins: origin,
+                        maxs: origin,
+                    },
+                    shapes: 0..0,
+                }],
+                shapes: Vec::new(),
+                unbounded,
+            };
+        }
+
+        let pb = ProgressBar::new("Building hierarchy", items.len());
+        let mut nodes = Vec::new();
+        Self::init_node(&pb, sett, &mut nodes, &mut items, 0, 0);
+        pb.finish_with_message("Hierarchy built");
+
+        let shapes = items
+            .into_iter()
+            .map(|item| (item.shape, item.attr))
+            .collect();
+
+        Self {
+            nodes,
+            shapes,
+            unbounded,
+        }
+    }
+
+    /// Initialise a node, and its descendants, returning its index.
+    #[inline]
+    fn init_node(
+        pb: &ProgressBar,
+        sett: &BvhBuilder,
+        nodes: &mut Vec<BvhNode>,
+        items: &mut [Item<'a, T, S>],
+        offset: usize,
+        depth: u32,
+    ) -> usize
+    where
+        T: Sync,
+        S: Sync,
+    {
+        debug_assert!(!items.is_empty());
+
+        let boundary = items
+            .iter()
+            .skip(1)
+            .fold(items[0].bounds.clone(), |acc, item| acc.union(&item.bounds));
+        let index = nodes.len();
+        nodes.push(BvhNode::Leaf {
+            boundary,
+            shapes: offset..(offset + items.len()),
+        });
+
+        let mid = Self::partition(sett, nodes[index].boundary(), items, depth);
+        if mid == 0 || mid == items.len() {
+            pb.block(items.len());
+            return index;
+        }
+
+        let (left, right) = items.split_at_mut(mid);
+        let (left_index, right_index) = if depth < sett.par_depth.unwrap_or(DEFAULT_PAR_DEPTH) {
+            let mut right_nodes = Vec::new();
+            let (left_index, _) = rayon::join(
+                || Self::init_node(pb, sett, nodes, left, offset, depth + 1),
+                || Self::init_node(pb, sett, &mut right_nodes, right, offset + mid, depth + 1),
+            );
+
+            // Relocate the separately built right nodes.
+            let base = nodes.len();
+            nodes.extend(right_nodes.into_iter().map(|node| match node {
+                BvhNode::Branch { boundary, children } => BvhNode::Branch {
+                    boundary,
+                    children: children.map(|child| child + base),
+                },
+                leaf @ BvhNode::Leaf { .. } => leaf,
+            }));
+
+            (left_index, base)
+        } else {
+            let left_index = Self::init_node(pb, sett, nodes, left, offset, depth + 1);
+            let right_index = Self::init_node(pb, sett, nodes, right, offset + mid, depth + 1);
+            (left_index, right_index)
+        };
+
+        let boundary = nodes[index].boundary().clone();
+        nodes[index] = BvhNode::Branch {
+            boundary,
+            children: [left_index, right_index],
+        };
+
+        index
+    }
+
+    /// Partition the triangles of a node, returning the number placed in the left child.
+    /// Zero is returned if the node should remain a leaf.
+    #[inline]
+    fn partition(
+        sett: &BvhBuilder,
+        boundary: &Cube,
+        items: &mut [Item<'a, T, S>],
+        depth: u32,
+    ) -> usize {
+        if items.len() <= sett.tar_tris || depth >= sett.max_depth {
+            return 0;
+        }
+
+        let split = match Self::find_split(sett, boundary, items) {
+            Some(split) => split,
+            None => return 0,
+        };
+
+        let mut mid = 0;
+        for i in 0..items.len() {
+            if split.is_left(&items[i].centre) {
+                items.swap(i, mid);
+                mid += 1;
+            }
+        }
+
+        mid
+    }
+
+    /// Find the split with the lowest surface area heuristic cost.
+    /// None is returned if no split is cheaper than keeping the triangles in a single leaf.
+    #[inline]
+    #[must_use]
+    fn find_split(sett: &BvhBuilder, boundary: &Cube, items: &[Item<'a, T, S>]) -> Option<Split> {
+        let parent_area = boundary.area();
+        if parent_area <= 0.0 {
+            return None;
+        }
+
+        let bins = sett.bins.unwrap_or(DEFAULT_BINS).max(2);
+
+        let mut mins = items[0].centre;
+        let mut maxs = mins;
+        for item in items {
+            for axis in 0..3 {
+                mins[axis] = mins[axis].min(item.centre[axis]);
+                maxs[axis] = maxs[axis].max(item.centre[axis]);
+            }
+        }
+
+        let mut best: Option<(f64, Split)> = None;
+        for axis in 0..3 {
+            let extent = maxs[axis] - mins[axis];
+            if extent <= 0.0 {
+                continue;
+            }
+
+            let split = Split {
+                axis,
+                min: mins[axis],
+                extent,
+                bins,
+                bin: 0,
+            };
+
+            let mut counts = vec![0_usize; bins];
+            let mut bounds: Vec<Option<Cube>> = vec![None; bins];
+            for item in items {
+                let b = split.bin_of(&item.centre);
+                counts[b] += 1;
+                bounds[b] = Some(
+                    bounds[b]
+                        .take()
+                        .map_or_else(|| item.bounds.clone(), |cube| cube.union(&item.bounds)),
+                );
+            }
+
+            // Sweep from the right, recording the area and count of everything at or above each bin.
+            let mut right_areas = vec![0.0; bins];
+            let mut right_counts = vec![0; bins];
+            let mut acc: Option<Cube> = None;
+            let mut count = 0;
+            for b in (1..bins).rev() {
+                acc = merge(acc, bounds[b].as_ref());
+                count += counts[b];
+                right_areas[b] = acc.as_ref().map_or(0.0, Cube::area);
+                right_counts[b] = count;
+            }
+
+            // Sweep from the left, evaluating the cost of splitting below each bin.
+            acc = None;
+            count = 0;
+            for b in 1..bins {
+                acc = merge(acc, bounds[b - 1].as_ref());
+                count += counts[b - 1];
+                if count == 0 || right_counts[b] == 0 {
+                    continue;
+                }
+
+                let left_area = acc.as_ref().map_or(0.0, Cube::area);
+                let cost = TRAVERSAL_COST
+                    + (left_area * count as f64 + right_areas[b] * right_counts[b] as f64)
+                        / parent_area;
+
+                if best
+                    .as_ref()
+                    .map_or(true, |&(best_cost, _)| cost < best_cost)
+                {
+                    best = Some((cost, Split { bin: b, ..split }));
+                }
+            }
+        }
+
+        best.filter(|&(cost, _)| cost < items.len() as f64)
+            .map(|(_, split)| split)
+    }
+
+    /// Reference the hierarchy's boundary.
+    #[inline]
+    #[must_use]
+    pub fn boundary(&self) -> &Cube {
+        self.nodes[0].boundary()
+    }
+
+    /// Reference the hierarchy nodes, starting with the root.
+    #[inline]
+    #[must_use]
+    pub fn nodes(&self) -> &[BvhNode] {
+        &self.nodes
+    }
+
+    /// Reference the bounded shapes and their attributes, ordered by leaf.
+    #[inline]
+    #[must_use]
+    pub fn shapes(&self) -> &[(&'a S, &'a T)] {
+        &self.shapes
+    }
+
+    /// Reference the unbounded shapes and their attributes.
+    #[inline]
+    #[must_use]
+    pub fn unbounded(&self) -> &[(&'a S, &'a T)] {
+        &self.unbounded
+    }
+
+    /// Determine what a given Ray would observe.
+    /// As for `Tree::scan`, the Ray is first advanced by the bump distance, so that surfaces within it are ignored,
+    /// and hit distances are measured from the original Ray position.
+    /// Nodes are visited nearest first, and only hits within the maximum distance are returned.
+    #[inline]
+    #[must_use]
+    pub fn scan(&self, mut ray: Ray, bump_dist: f64, max_dist: f64) -> Option<Hit<'a, T>>
+    where
+        S: Trace,
+    {
+        debug_assert!(bump_dist > 0.0);
+        debug_assert!(max_dist > 0.0);
+
+        if max_dist <= bump_dist {
+            return None;
+        }
+        ray.travel(bump_dist);
+
+        let mut nearest: Option<Hit<'a, T>> = None;
+        let mut limit = max_dist - bump_dist;
+
+        for &(shape, attr) in &self.unbounded {
+            if let Some((dist, side)) = shape.dist_side(&ray) {
+                if dist < limit {
+                    limit = dist;
+                    nearest = Some(Hit::new(attr, dist, side));
+                }
+            }
+        }
+
+        let mut stack = Vec::with_capacity(64);
+        if !self.shapes.is_empty() {
+            if let Some((entry, _)) = self.boundary().dist_range(&ray) {
+                stack.push((0, entry));
+            }
+        }
+
+        while let Some((index, entry)) = stack.pop() {
+            if entry > limit {
+                continue;
+            }
+
+            match self.nodes[index] {
+                BvhNode::Leaf { ref shapes, .. } => {
+                    for &(shape, attr) in &self.shapes[shapes.clone()] {
+                        if let Some((dist, side)) = shape.dist_side(&ray) {
+                            if dist < limit {
+                                limit = dist;
+                                nearest = Some(Hit::new(attr, dist, side));
+                            }
+                        }
+                    }
+                }
+                BvhNode::Branch { children, .. } => {
+                    let entries = children.map(|child| {
+                        self.nodes[child]
+                            .boundary()
+                            .dist_range(&ray)
+                            .map(|(child_entry, _)| child_entry)
+                            .filter(|&child_entry| child_entry <= limit)
+                    });
+
+                    // Push the further child first, so that the nearer is visited first.
+                    let order = match entries {
+                        [Some(a), Some(b)] if b < a => [0, 1],
+                        _ => [1, 0],
+                    };
+                    for i in order {
+                        if let Some(child_entry) = entries[i] {
+                            stack.push((children[i], child_entry));
+                        }
+                    }
+                }
+            }
+        }
+
+        nearest.map(|mut hit| {
+            hit.dist += bump_dist;
+            hit
+        })
+    }
+}
+
+/// Grow an optional accumulated bounding box by an optional box.
+#[inline]
+#[must_use]
+fn merge(acc: Option<Cube>, cube: Option<&Cube>) -> Option<Cube> {
+    match (acc, cube) {
+        (Some(acc), Some(cube)) => Some(acc.union(cube)),
+        (None, Some(cube)) => Some(cube.clone()),
+        (acc, None) => acc,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geom::{Plane, Sphere, Triangle};
+    use nalgebra::{Unit, Vector3};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// Generate a random point within a cube of the given half-width.
+    fn random_point(rng: &mut StdRng, half_width: f64) -> Point3<f64> {
+        Point3::new(
+            rng.gen_range(-half_width..half_width),
+            rng.gen_range(-half_width..half_width),
+            rng.gen_range(-half_width..half_width),
+        )
+    }
+
+    /// Generate a random direction, including axis-aligned directions.
+    fn random_dir(rng: &mut StdRng, n: usize) -> Unit<Vector3<f64>> {
+        match n % 8 {
+            0 => Vector3::x_axis(),
+            1 => -Vector3::z_axis(),
+            _ => Unit::new_normalize(random_point(rng, 1.0).coords + Vector3::new(0.0, 0.0, 1e-3)),
+        }
+    }
+
+    /// Construct a triangle from its vertices, with its plane normal at each vertex.
+    fn triangle(verts: [Point3<f64>; 3]) -> Shape {
+        let norm = Unit::new_normalize((verts[1] - verts[0]).cross(&(verts[2] - verts[0])));
+        Shape::Triangle(Triangle::new(verts, [norm; 3]))
+    }
+
+    /// Generate a scene of random triangles and spheres.
+    fn random_shapes(rng: &mut StdRng) -> Vec<Shape> {
+        let mut shapes = Vec::new();
+        for _ in 0..300 {
+            let a = random_point(rng, 10.0);
+            shapes.push(triangle([
+                a,
+                a + random_point(rng, 1.0).coords,
+                a + random_point(rng, 1.0).coords,
+            ]));
+        }
+        for _ in 0..100 {
+            let centre = random_point(rng, 10.0);
+            shapes.push(Shape::Sphere(Sphere::new(centre, rng.gen_range(0.05..0.5))));
+        }
+        shapes
+    }
+
+    /// Generate triangles and spheres whose bounding boxes all share the same centre.
+    fn coincident_shapes(rng: &mut StdRng) -> Vec<Shape> {
+        let centre = Point3::origin();
+        let mut shapes = Vec::new();
+        for _ in 0..50 {
+            // The third vertex lies within the box spanned by the first two.
+            let a = Vector3::from_fn(|_, _| rng.gen_range(1.0..2.0));
+            let b = random_point(rng, 1.0).coords;
+            shapes.push(triangle([centre + a, centre - a, centre + b]));
+        }
+        for n in 1..=50 {
+            shapes.push(Shape::Sphere(Sphere::new(centre, 0.05 * f64::from(n))));
+        }
+        shapes
+    }
+
+    /// Check that the hierarchy reports the nearest hit of every shape.
+    /// Returns the number of hits found.
+    fn check_scan(
+        rng: &mut StdRng,
+        surfs: &[Surface<u8>],
+        bvh: &Bvh<u8>,
+        half_width: f64,
+    ) -> usize {
+        let mut num_hits = 0;
+        for n in 0..2000 {
+            let ray = Ray::new(random_point(rng, half_width), random_dir(rng, n));
+            let max_dist = 1.0e9;
+
+            let nearest = surfs
+                .iter()
+                .flat_map(|surf| surf.shapes.iter().map(move |shape| (shape, surf.attr)))
+                .filter_map(|(shape, attr)| shape.dist(&ray).map(|dist| (dist, *attr)))
+                .min_by(|a, b| a.0.total_cmp(&b.0));
+            let hit = bvh
+                .scan(ray, 1.0e-9, max_dist)
+                .map(|hit| (hit.dist, *hit.tag));
+
+            match (nearest, hit) {
+                (None, None) => {}
+                (Some(a), Some(b)) => {
+                    num_hits += 1;
+                    assert!((a.0 - b.0).abs() < 1.0e-9);
+                }
+                _ => panic!("Mismatch: {nearest:?} {hit:?}"),
+            }
+        }
+        num_hits
+    }
+
+    /// Check that each bounded shape is referenced by exactly one leaf.
+    fn check_leaves(bvh: &Bvh<u8>) {
+        let mut ranges: Vec<_> = bvh
+            .nodes()
+            .iter()
+            .filter_map(|node| match *node {
+                BvhNode::Leaf { ref shapes, .. } => Some(shapes.clone()),
+                BvhNode::Branch { .. } => None,
+            })
+            .collect();
+        ranges.sort_by_key(|range| range.start);
+
+        let mut end = 0;
+        for range in ranges {
+            assert_eq!(range.start, end);
+            end = range.end;
+        }
+        assert_eq!(end, bvh.shapes().len());
+    }
+
+    #[test]
+    fn scan_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let attrs = [0, 1];
+        let surfs = vec![
+            Surface::from_shapes(random_shapes(&mut rng), &attrs[0]),
+            Surface::from_shapes(
+                vec![Shape::Plane(Plane::new(
+                    Point3::new(0.0, 0.0, -8.0),
+                    Vector3::z_axis(),
+                ))],
+                &attrs[1],
+            ),
+        ];
+        let bvh = BvhBuilder::default().build(&surfs);
+
+        assert_eq!(bvh.shapes().len(), 400);
+        assert_eq!(bvh.unbounded().len(), 1);
+        assert!(bvh.nodes().len() > 1);
+        check_leaves(&bvh);
+        assert!(check_scan(&mut rng, &surfs, &bvh, 10.0) > 500);
+    }
+
+    #[test]
+    fn coincident_centroids_form_a_single_leaf() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let attr = 0;
+        let surfs = vec![Surface::from_shapes(coincident_shapes(&mut rng), &attr)];
+        let bvh = BvhBuilder::default().build(&surfs);
+
+        assert_eq!(bvh.nodes().len(), 1);
+        check_leaves(&bvh);
+        assert!(check_scan(&mut rng, &surfs, &bvh, 4.0) > 100);
+    }
+}
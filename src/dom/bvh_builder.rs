@@ -0,0 +1,46 @@
+//! Bounding volume hierarchy construction settings.
+
+use serde::Deserialize;
+
+use crate::{
+    dom::{Bvh, Surface},
+    geom::Collide,
+};
+
+/// Bounding volume hierarchy construction settings.
+#[derive(Deserialize)]
+pub struct BvhBuilder {
+    /// Target maximum number of shapes per leaf.
+    /// Larger leaves are split if the surface area heuristic finds it worthwhile.
+    pub tar_tris: usize,
+    /// Maximum hierarchy depth.
+    pub max_depth: u32,
+    /// Optional number of bins used to evaluate splits along each axis.
+    pub bins: Option<usize>,
+    /// Optional depth down to which nodes are built in parallel.
+    pub par_depth: Option<u32>,
+}
+
+impl Default for BvhBuilder {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            tar_tris: 4,
+            max_depth: 32,
+            bins: None,
+            par_depth: None,
+        }
+    }
+}
+
+impl BvhBuilder {
+    /// Build a Bvh instance.
+    #[inline]
+    #[must_use]
+    pub fn build<'a, T: Sync, S: Collide + Sync>(
+        &self,
+        surfs: &'a [Surface<T, S>],
+    ) -> Bvh<'a, T, S> {
+        Bvh::new(self, surfs)
+    }
+}
@@ -0,0 +1,130 @@
+//! Mesh instance.
+
+use nalgebra::{Affine3, Matrix3, Point3, Unit, Vector3};
+
+use crate::{
+    dom::Bvh,
+    geom::{Cube, Shape, Trace},
+    rt::{Hit, Ray, Side},
+    Error,
+};
+
+/// Shared mesh hierarchy placed in the scene with its own transformation and attribute.
+pub struct Instance<'a, T, S = Shape> {
+    /// Object-space hierarchy.
+    object: &'a Bvh<'a, (), S>,
+    /// Attribute data object.
+    attr: &'a T,
+    /// World-to-object transformation.
+    to_object: Affine3<f64>,
+    /// Object-to-world transformation of normals.
+    norm_to_world: Matrix3<f64>,
+    /// World-space boundary.
+    boundary: Cube,
+}
+
+impl<'a, T, S> Instance<'a, T, S> {
+    /// Construct a new instance placed by the given object-to-world transformation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transformation is not invertible.
+    #[inline]
+    pub fn new(
+        object: &'a Bvh<'a, (), S>,
+        attr: &'a T,
+        to_world: &Affine3<f64>,
+    ) -> Result<Self, Error> {
+        let to_object = to_world.try_inverse().ok_or_else(|| {
+            Error::InvalidParameter("Instance transformation is not invertible.".to_owned())
+        })?;
+        let norm_to_world = to_object.matrix().fixed_view::<3, 3>(0, 0).transpose();
+
+        let bounds = object.boundary();
+        let corners = (0..8).map(|index: usize| {
+            to_world.transform_point(&Point3::new(
+                if index & 1 == 0 {
+                    bounds.mins.x
+                } else {
+                    bounds.maxs.x
+                },
+                if index & 2 == 0 {
+                    bounds.mins.y
+                } else {
+                    bounds.maxs.y
+                },
+                if index & 4 == 0 {
+                    bounds.mins.z
+                } else {
+                    bounds.maxs.z
+                },
+            ))
+        });
+        let first = to_world.transform_point(&bounds.mins);
+        let boundary = corners.fold(
+            Cube {
+                mins: first,
+                maxs: first,
+            },
+            |acc, corner| Cube {
+                mins: acc.mins.inf(&corner),
+                maxs: acc.maxs.sup(&corner),
+            },
+        );
+
+        Ok(Self {
+            object,
+            attr,
+            to_object,
+            norm_to_world,
+            boundary,
+        })
+    }
+
+    /// Reference the world-space boundary.
+    #[inline]
+    #[must_use]
+    pub const fn boundary(&self) -> &Cube {
+        &self.boundary
+    }
+
+    /// Reference the attribute data object.
+    #[inline]
+    #[must_use]
+    pub const fn attr(&self) -> &'a T {
+        self.attr
+    }
+
+    /// Determine what a given world-space Ray would observe.
+    /// The Ray is transformed into object space, and any hit is returned in world space.
+    #[inline]
+    #[must_use]
+    pub fn scan(&self, ray: &Ray, bump_dist: f64, max_dist: f64) -> Option<Hit<'a, T>>
+    where
+        S: Trace,
+    {
+        let pos = self.to_object.transform_point(&ray.pos);
+        let dir = self.to_object.transform_vector(&ray.dir);
+
+        // Object-space distance travelled per unit world-space distance.
+        let stretch = dir.norm();
+        let object_ray = Ray::new(pos, Unit::new_unchecked(dir / stretch));
+
+        self.object
+            .scan(object_ray, bump_dist * stretch, max_dist * stretch)
+            .map(|hit| {
+                let side = match hit.side {
+                    Side::Inside(norm) => Side::Inside(self.transform_norm(&norm)),
+                    Side::Outside(norm) => Side::Outside(self.transform_norm(&norm)),
+                };
+                Hit::new(self.attr, hit.dist / stretch, side)
+            })
+    }
+
+    /// Transform an object-space normal into world space.
+    #[inline]
+    #[must_use]
+    fn transform_norm(&self, norm: &Unit<Vector3<f64>>) -> Unit<Vector3<f64>> {
+        Unit::new_normalize(self.norm_to_world * norm.as_ref())
+    }
+}
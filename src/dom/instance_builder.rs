@@ -0,0 +1,46 @@
+//! Mesh instance loader.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::{
+    dom::{Bvh, Instance},
+    geom::TransformBuilder,
+    Error,
+};
+
+/// Mesh instance parameterisation.
+#[derive(Clone, Deserialize)]
+pub struct InstanceBuilder {
+    /// Mesh name.
+    pub mesh: String,
+    /// Attribute name.
+    pub attr: String,
+    /// Placement of the mesh.
+    #[serde(flatten)]
+    pub transform: TransformBuilder,
+}
+
+impl InstanceBuilder {
+    /// Construct a new instance.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the object hierarchy or attribute is not present in the given dictionaries,
+    /// or if the transformation is invalid.
+    #[inline]
+    pub fn build<'a, T, S>(
+        &self,
+        objects: &'a HashMap<String, Bvh<'a, (), S>>,
+        attributes: &'a HashMap<String, T>,
+    ) -> Result<Instance<'a, T, S>, Error> {
+        let object = objects
+            .get(&self.mesh)
+            .ok_or_else(|| Error::MissingKey("mesh", self.mesh.clone()))?;
+        let attr = attributes
+            .get(&self.attr)
+            .ok_or_else(|| Error::MissingKey("attribute", self.attr.clone()))?;
+
+        Instance::new(object, attr, &self.transform.build()?)
+    }
+}
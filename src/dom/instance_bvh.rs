@@ -0,0 +1,315 @@
+//! Top-level hierarchy of mesh instances.
+
+use core::ops::Range;
+
+use crate::{
+    dom::Instance,
+    geom::{Cube, Shape, Trace},
+    rt::{Hit, Ray},
+};
+
+/// Maximum number of instances held by a leaf node.
+const MAX_LEAF_INSTANCES: usize = 2;
+
+/// Hierarchy node enumeration.
+enum Node {
+    /// Branching node.
+    Branch {
+        /// Boundary.
+        boundary: Cube,
+        /// Indices of the child nodes.
+        children: [usize; 2],
+    },
+    /// Terminal node.
+    Leaf {
+        /// Boundary.
+        boundary: Cube,
+        /// Range of the contained instance indices.
+        instances: Range<usize>,
+    },
+}
+
+impl Node {
+    /// Reference the node's boundary.
+    #[inline]
+    #[must_use]
+    const fn boundary(&self) -> &Cube {
+        match *self {
+            Self::Branch { ref boundary, .. } | Self::Leaf { ref boundary, .. } => boundary,
+        }
+    }
+}
+
+/// Binary hierarchy of instance bounding volumes, split at the median along the widest axis.
+/// Rays reaching an instance are transformed into its object space, and scanned against its own hierarchy.
+pub struct InstanceBvh<'a, T, S = Shape> {
+    /// Nodes, starting with the root.
+    nodes: Vec<Node>,
+    /// Instances, ordered by leaf.
+    instances: Vec<Instance<'a, T, S>>,
+}
+
+impl<'a, T, S> InstanceBvh<'a, T, S> {
+    /// Construct a new instance.
+    #[inline]
+    #[must_use]
+    pub fn new(mut instances: Vec<Instance<'a, T, S>>) -> Self {
+        debug_assert!(!instances.is_empty());
+
+        let mut nodes = Vec::new();
+        Self::init_node(&mut nodes, &mut instances, 0);
+
+        Self { nodes, instances }
+    }
+
+    /// Initialise a node, and its descendants, returning its index.
+    #[inline]
+    fn init_node(
+        nodes: &mut Vec<Node>,
+        instances: &mut [Instance<'a, T, S>],
+        offset: usize,
+    ) -> usize {
+        let boundary = instances
+            .iter()
+            .skip(1)
+            .fold(instances[0].boundary().clone(), |acc, inst| {
+                acc.union(inst.boundary())
+            });
+
+        let index = nodes.len();
+        if instances.len() <= MAX_LEAF_INSTANCES {
+            nodes.push(Node::Leaf {
+                boundary,
+                instances: offset..(offset + instances.len()),
+            });
+            return index;
+        }
+
+        // Split at the median centre along the widest axis.
+        let widths = boundary.widths();
+        let axis = (0..3)
+            .max_by(|&a, &b| widths[a].total_cmp(&widths[b]))
+            .unwrap_or(0);
+        let mid = instances.len() / 2;
+        instances.select_nth_unstable_by(mid, |a, b| {
+            a.boundary().centre()[axis].total_cmp(&b.boundary().centre()[axis])
+        });
+
+        nodes.push(Node::Leaf {
+            boundary: boundary.clone(),
+            instances: offset..(offset + instances.len()),
+        });
+        let (left, right) = instances.split_at_mut(mid);
+        let left_index = Self::init_node(nodes, left, offset);
+        let right_index = Self::init_node(nodes, right, offset + mid);
+        nodes[index] = Node::Branch {
+            boundary,
+            children: [left_index, right_index],
+        };
+
+        index
+    }
+
+    /// Reference the hierarchy's boundary.
+    #[inline]
+    #[must_use]
+    pub fn boundary(&self) -> &Cube {
+        self.nodes[0].boundary()
+    }
+
+    /// Reference the instances, ordered by leaf.
+    #[inline]
+    #[must_use]
+    pub fn instances(&self) -> &[Instance<'a, T, S>] {
+        &self.instances
+    }
+
+    /// Determine what a given Ray would observe.
+    /// Nodes are visited nearest first, and only hits within the maximum distance are returned.
+    #[inline]
+    #[must_use]
+    pub fn scan(&self, ray: &Ray, bump_dist: f64, max_dist: f64) -> Option<Hit<'a, T>>
+    where
+        S: Trace,
+    {
+        debug_assert!(bump_dist > 0.0);
+        debug_assert!(max_dist > 0.0);
+
+        let mut nearest: Option<Hit<'a, T>> = None;
+        let mut limit = max_dist;
+
+        let mut stack = Vec::with_capacity(64);
+        if let Some((entry, _)) = self.boundary().dist_range(ray) {
+            stack.push((0, entry));
+        }
+
+        while let Some((index, entry)) = stack.pop() {
+            if entry > limit {
+                continue;
+            }
+
+            match self.nodes[index] {
+                Node::Leaf { ref instances, .. } => {
+                    for inst in &self.instances[instances.clone()] {
+                        if let Some(hit) = inst.scan(ray, bump_dist, limit) {
+                            if hit.dist < limit {
+                                limit = hit.dist;
+                                nearest = Some(hit);
+                            }
+                        }
+                    }
+                }
+                Node::Branch { children, .. } => {
+                    let entries = children.map(|child| {
+                        self.nodes[child]
+                            .boundary()
+                            .dist_range(ray)
+                            .map(|(child_entry, _)| child_entry)
+                            .filter(|&child_entry| child_entry <= limit)
+                    });
+
+                    // Push the further child first, so that the nearer is visited first.
+                    let order = match entries {
+                        [Some(a), Some(b)] if b < a => [0, 1],
+                        _ => [1, 0],
+                    };
+                    for i in order {
+                        if let Some(child_entry) = entries[i] {
+                            stack.push((children[i], child_entry));
+                        }
+                    }
+                }
+            }
+        }
+
+        nearest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dom::{Bvh, BvhBuilder, Partition, Surface},
+        geom::{Mesh, TransformBuilder, Triangle},
+    };
+    use nalgebra::{Point3, Unit, Vector3};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use std::slice;
+
+    /// Construct an octahedron mesh with flat, outward facing normals.
+    fn octahedron() -> Mesh {
+        let verts = [
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(-1.0, 0.0, 0.0),
+            Point3::new(0.0, -1.0, 0.0),
+        ];
+        let poles = [Point3::new(0.0, 0.0, 1.0), Point3::new(0.0, 0.0, -1.0)];
+
+        let mut tris = Vec::new();
+        for i in 0..4 {
+            let (a, b) = (verts[i], verts[(i + 1) % 4]);
+            for (pole, face) in [(poles[0], [a, b, poles[0]]), (poles[1], [b, a, poles[1]])] {
+                let norm = Unit::new_normalize((a.coords + b.coords + pole.coords) / 3.0);
+                tris.push(Triangle::new(face, [norm; 3]));
+            }
+        }
+        Mesh::new(tris)
+    }
+
+    /// Construct transformations including non-uniform scaling and mirroring.
+    fn transforms() -> Vec<TransformBuilder> {
+        vec![
+            TransformBuilder {
+                translation: Some(Vector3::new(-3.0, 0.0, 0.0)),
+                rotation: Some(Vector3::new(30.0, 45.0, 60.0)),
+                scale: Some(Vector3::new(2.0, 0.5, 1.0)),
+                matrix: None,
+            },
+            TransformBuilder {
+                translation: Some(Vector3::new(3.0, 1.0, 0.0)),
+                rotation: Some(Vector3::new(-10.0, 0.0, 80.0)),
+                scale: Some(Vector3::new(0.25, 3.0, 1.5)),
+                matrix: None,
+            },
+            TransformBuilder {
+                translation: Some(Vector3::new(0.0, -2.0, 2.0)),
+                rotation: None,
+                scale: Some(Vector3::new(-1.0, 1.0, 2.0)),
+                matrix: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn instances_match_transformed_copies() {
+        let mut rng = StdRng::seed_from_u64(17);
+        let attrs = [0_u8, 1, 2];
+        let transforms: Vec<_> = transforms()
+            .iter()
+            .map(|trans| trans.build().expect("Failed to build transformation."))
+            .collect();
+
+        let object = Surface::new(octahedron(), &());
+        let object_bvh = Bvh::new(&BvhBuilder::default(), slice::from_ref(&object));
+        let instances = transforms
+            .iter()
+            .zip(&attrs)
+            .map(|(trans, attr)| {
+                Instance::new(&object_bvh, attr, trans).expect("Failed to place instance.")
+            })
+            .collect();
+        let partition = Partition::new_instanced(None, InstanceBvh::new(instances));
+
+        let copies: Vec<_> = transforms
+            .iter()
+            .zip(&attrs)
+            .map(|(trans, attr)| {
+                let mut mesh = octahedron();
+                mesh.transform(trans).expect("Failed to transform mesh.");
+                Surface::new(mesh, attr)
+            })
+            .collect();
+        let copies_bvh = Bvh::new(&BvhBuilder::default(), &copies);
+
+        let mut num_hits = 0;
+        for _ in 0..2000 {
+            let pos = Point3::new(
+                rng.gen_range(-8.0..8.0),
+                rng.gen_range(-8.0..8.0),
+                rng.gen_range(-8.0..8.0),
+            );
+            // Aim near the centre of a random instance.
+            let centre =
+                transforms[rng.gen_range(0..transforms.len())].transform_point(&Point3::origin());
+            let target = centre
+                + Vector3::new(
+                    rng.gen_range(-0.5..0.5),
+                    rng.gen_range(-0.5..0.5),
+                    rng.gen_range(-0.5..0.5),
+                );
+            let ray = Ray::new(pos, Unit::new_normalize(target - pos));
+
+            let expected = copies_bvh.scan(ray.clone(), 1.0e-9, 1.0e9);
+            let hit = partition.scan(ray, 1.0e-9, 1.0e9);
+
+            match (expected, hit) {
+                (None, None) => {}
+                (Some(a), Some(b)) => {
+                    num_hits += 1;
+                    assert_eq!(a.tag, b.tag);
+                    assert!((a.dist - b.dist).abs() < 1.0e-9);
+                    assert_eq!(a.side.is_inside(), b.side.is_inside());
+                    assert!((a.side.norm().as_ref() - b.side.norm().as_ref()).norm() < 1.0e-9);
+                }
+                (a, b) => panic!(
+                    "Mismatch: {:?} {:?}",
+                    a.map(|hit| hit.dist),
+                    b.map(|hit| hit.dist)
+                ),
+            }
+        }
+        assert!(num_hits > 500);
+    }
+}
@@ -1,8 +1,22 @@
 //! Domain partitioning.
 
+pub mod bvh;
+pub mod bvh_builder;
+pub mod instance;
+pub mod instance_builder;
+pub mod instance_bvh;
+pub mod partition;
 pub mod surface;
 pub mod surface_builder;
+pub mod traversal;
 pub mod tree;
 pub mod tree_builder;
+pub mod tree_cache;
+pub mod tree_export;
+pub mod tree_stats;
 
-pub use self::{surface::*, surface_builder::*, tree::*, tree_builder::*};
+pub use self::{
+    bvh::*, bvh_builder::*, instance::*, instance_builder::*, instance_bvh::*, partition::*,
+    surface::*, surface_builder::*, traversal::*, tree::*, tree_builder::*, tree_export::*,
+    tree_stats::*,
+};
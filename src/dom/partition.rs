@@ -0,0 +1,89 @@
+//! Scene partitioning scheme.
+
+use crate::{
+    dom::{Bvh, InstanceBvh, Tree},
+    geom::{Cube, Shape, Trace},
+    rt::{Hit, Ray},
+};
+
+/// Acceleration structure enumeration.
+#[allow(clippy::exhaustive_enums)]
+pub enum Partition<'a, T, S = Shape> {
+    /// Adaptive oct-tree.
+    Tree(Tree<'a, T, S>),
+    /// Bounding volume hierarchy.
+    Bvh(Bvh<'a, T, S>),
+    /// Two-level structure of mesh instances, alongside any directly partitioned surfaces.
+    Instanced {
+        /// Boundary enclosing both levels.
+        boundary: Cube,
+        /// Optional partition of the non-instanced surfaces.
+        base: Option<Box<Self>>,
+        /// Top-level hierarchy of instances.
+        instances: InstanceBvh<'a, T, S>,
+    },
+}
+
+impl<'a, T, S> Partition<'a, T, S> {
+    /// Construct a two-level partition of the given instances, alongside an optional base partition.
+    #[inline]
+    #[must_use]
+    pub fn new_instanced(base: Option<Self>, instances: InstanceBvh<'a, T, S>) -> Self {
+        let boundary = base.as_ref().map_or_else(
+            || instances.boundary().clone(),
+            |base| base.boundary().union(instances.boundary()),
+        );
+
+        Self::Instanced {
+            boundary,
+            base: base.map(Box::new),
+            instances,
+        }
+    }
+
+    /// Reference the boundary of the partitioned domain.
+    #[inline]
+    #[must_use]
+    pub fn boundary(&self) -> &Cube {
+        match *self {
+            Self::Tree(ref tree) => tree.boundary(),
+            Self::Bvh(ref bvh) => bvh.boundary(),
+            Self::Instanced { ref boundary, .. } => boundary,
+        }
+    }
+
+    /// Determine what a given Ray would observe.
+    /// The maximum distance provided does not guarantee that any hit retrieved is less than the given distance.
+    #[inline]
+    #[must_use]
+    pub fn scan(&self, ray: Ray, bump_dist: f64, max_dist: f64) -> Option<Hit<'_, T>>
+    where
+        S: Trace,
+    {
+        match *self {
+            Self::Tree(ref tree) => tree.scan(ray, bump_dist, max_dist),
+            Self::Bvh(ref bvh) => bvh.scan(ray, bump_dist, max_dist),
+            Self::Instanced {
+                ref base,
+                ref instances,
+                ..
+            } => {
+                let base_hit = base
+                    .as_ref()
+                    .and_then(|base| base.scan(ray.clone(), bump_dist, max_dist));
+                let limit = base_hit.as_ref().map_or(max_dist, |hit| hit.dist);
+
+                match instances.scan(&ray, bump_dist, limit) {
+                    Some(hit)
+                        if base_hit
+                            .as_ref()
+                            .map_or(true, |base_hit| hit.dist < base_hit.dist) =>
+                    {
+                        Some(hit)
+                    }
+                    _ => base_hit,
+                }
+            }
+        }
+    }
+}
@@ -1,20 +1,56 @@
 //! Surface.
 
-use crate::geom::Mesh;
+use crate::geom::{Collide, Cube, Mesh, Shape};
 
-/// Three-dimension triangular mesh with attribute data.
-pub struct Surface<'a, T> {
-    /// Mesh.
-    pub mesh: Mesh,
+/// Set of shapes with attribute data.
+/// Any geometry implementing `Collide` and `Trace` may be used in place of the built-in shapes.
+pub struct Surface<'a, T, S = Shape> {
+    /// Shapes.
+    pub shapes: Vec<S>,
+    /// Bounding box of the bounded shapes, if any.
+    pub boundary: Option<Cube>,
     /// Attribute data object.
     pub attr: &'a T,
 }
 
 impl<'a, T> Surface<'a, T> {
-    /// Construct a new instance.
+    /// Construct a new instance from the triangles of a mesh.
     #[inline]
     #[must_use]
-    pub const fn new(mesh: Mesh, attr: &'a T) -> Self {
-        Self { mesh, attr }
+    pub fn new(mesh: Mesh, attr: &'a T) -> Self {
+        Self {
+            shapes: mesh.tris.into_iter().map(Shape::Triangle).collect(),
+            boundary: Some(mesh.boundary),
+            attr,
+        }
+    }
+}
+
+impl<'a, T, S: Collide> Surface<'a, T, S> {
+    /// Construct a new instance from a set of shapes.
+    /// Flat dimensions of the boundary, such as those of a single disc, are padded.
+    #[inline]
+    #[must_use]
+    pub fn from_shapes(shapes: Vec<S>, attr: &'a T) -> Self {
+        let boundary = shapes
+            .iter()
+            .filter_map(Collide::bounds)
+            .reduce(|acc, cube| acc.union(&cube))
+            .map(|mut cube| {
+                let pad = cube.widths().max() * 0.005;
+                for axis in 0..3 {
+                    if cube.mins[axis] >= cube.maxs[axis] {
+                        cube.mins[axis] -= pad;
+                        cube.maxs[axis] += pad;
+                    }
+                }
+                cube
+            });
+
+        Self {
+            shapes,
+            boundary,
+            attr,
+        }
     }
 }
@@ -3,7 +3,11 @@
 use serde::Deserialize;
 use std::collections::HashMap;
 
-use crate::{dom::Surface, geom::Mesh};
+use crate::{
+    dom::Surface,
+    geom::{Mesh, TransformBuilder},
+    Error,
+};
 
 /// Surface parameterisation.
 #[derive(Clone, Deserialize)]
@@ -12,17 +16,36 @@ pub struct SurfaceBuilder(
     pub String,
     /// Attribute name.
     pub String,
+    /// Optional transformation applied to a copy of the mesh.
+    #[serde(default)]
+    pub Option<TransformBuilder>,
 );
 
 impl SurfaceBuilder {
     /// Construct a new instance.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `Mesh` or attribute is not present in the given dictionaries,
+    /// or if the transformation is invalid.
     #[inline]
-    #[must_use]
     pub fn build<'a, T>(
         self,
         meshes: &HashMap<String, Mesh>,
         attributes: &'a HashMap<String, T>,
-    ) -> Surface<'a, T> {
-        Surface::new(meshes[&self.0].clone(), &attributes[&self.1])
+    ) -> Result<Surface<'a, T>, Error> {
+        let mesh = meshes
+            .get(&self.0)
+            .ok_or_else(|| Error::MissingKey("mesh", self.0.clone()))?;
+        let attr = attributes
+            .get(&self.1)
+            .ok_or_else(|| Error::MissingKey("attribute", self.1.clone()))?;
+
+        let mut mesh = mesh.clone();
+        if let Some(ref trans) = self.2 {
+            mesh.transform(&trans.build()?)?;
+        }
+
+        Ok(Surface::new(mesh, attr))
     }
 }
@@ -0,0 +1,244 @@
+//! Ordered oct-tree traversal.
+
+use crate::{dom::TreeCell, geom::Shape, rt::Ray};
+
+/// Iterator over the leaf cells of a Tree pierced by a Ray.
+/// Leaves are visited in order along the Ray, with their exact entry and exit distances.
+pub struct Traversal<'t, 'a, T, S = Shape> {
+    /// Traversing ray.
+    ray: Ray,
+    /// Cells still to be visited, with their entry and exit distances, nearest last.
+    stack: Vec<(&'t TreeCell<'a, T, S>, f64, f64)>,
+}
+
+impl<'t, 'a, T, S> Traversal<'t, 'a, T, S> {
+    /// Construct a new instance.
+    /// Only cells, descending from the given cell, within the given distance range along the Ray are visited.
+    #[inline]
+    #[must_use]
+    pub fn new(cell: &'t TreeCell<'a, T, S>, ray: Ray, t_min: f64, t_max: f64) -> Self {
+        let mut stack = Vec::with_capacity(32);
+        if let Some((entry, exit)) = cell.boundary().dist_range(&ray) {
+            let entry = entry.max(t_min);
+            let exit = exit.min(t_max);
+            if entry <= exit {
+                stack.push((cell, entry, exit));
+            }
+        }
+
+        Self { ray, stack }
+    }
+
+    /// Push the children of a branching cell pierced over the given distance range, nearest last.
+    #[inline]
+    fn push_children(
+        &mut self,
+        children: &'t [TreeCell<'a, T, S>; 8],
+        split: [f64; 3],
+        t0: f64,
+        t1: f64,
+    ) {
+        let pos = self.ray.pos;
+        let dir = self.ray.dir;
+
+        // Distances to each of the splitting planes.
+        let t_mid = [0, 1, 2].map(|axis| (split[axis] - pos[axis]) / dir[axis]);
+
+        // Child containing the entry point.
+        let mut index = 0;
+        for axis in 0..3 {
+            let upper = if dir[axis] == 0.0 {
+                pos[axis] >= split[axis]
+            } else if t_mid[axis] > t0 {
+                dir[axis] < 0.0
+            } else {
+                dir[axis] > 0.0
+            };
+            if upper {
+                index |= 1 << axis;
+            }
+        }
+
+        // Planes crossed within the cell, in order.
+        let mut crossings = [(f64::INFINITY, 0); 3];
+        let mut num_crossings = 0;
+        for axis in 0..3 {
+            if t_mid[axis] > t0 && t_mid[axis] < t1 {
+                crossings[num_crossings] = (t_mid[axis], axis);
+                num_crossings += 1;
+            }
+        }
+        crossings.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut cells = [(0, t0, t1); 4];
+        let mut start = t0;
+        for (n, &(t, axis)) in crossings[..num_crossings].iter().enumerate() {
+            cells[n] = (index, start, t);
+            index ^= 1 << axis;
+            start = t;
+        }
+        cells[num_crossings] = (index, start, t1);
+
+        for &(index, start, end) in cells[..=num_crossings].iter().rev() {
+            self.stack.push((&children[index], start, end));
+        }
+    }
+}
+
+impl<'t, 'a, T, S> Iterator for Traversal<'t, 'a, T, S> {
+    type Item = (&'t TreeCell<'a, T, S>, f64, f64);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((cell, t0, t1)) = self.stack.pop() {
+            match *cell {
+                TreeCell::Leaf { .. } => return Some((cell, t0, t1)),
+                TreeCell::Branch { ref children, .. } => {
+                    // The first child's maximum bound is the split point.
+                    let c = children[0].boundary().maxs;
+                    self.push_children(children, [c.x, c.y, c.z], t0, t1);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dom::{Surface, Tree, TreeBuilder},
+        geom::{Sphere, Trace, Triangle},
+    };
+    use nalgebra::{Point3, Unit, Vector3};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// Generate a random point within a cube of the given half-width.
+    fn random_point(rng: &mut StdRng, half_width: f64) -> Point3<f64> {
+        Point3::new(
+            rng.gen_range(-half_width..half_width),
+            rng.gen_range(-half_width..half_width),
+            rng.gen_range(-half_width..half_width),
+        )
+    }
+
+    /// Generate a random direction, including axis-aligned directions.
+    fn random_dir(rng: &mut StdRng, n: usize) -> Unit<Vector3<f64>> {
+        match n % 8 {
+            0 => Vector3::x_axis(),
+            1 => -Vector3::z_axis(),
+            _ => Unit::new_normalize(random_point(rng, 1.0).coords + Vector3::new(0.0, 0.0, 1e-3)),
+        }
+    }
+
+    /// Generate a scene of random triangles and spheres.
+    fn random_shapes(rng: &mut StdRng) -> Vec<Shape> {
+        let mut shapes = Vec::new();
+        for _ in 0..300 {
+            let a = random_point(rng, 10.0);
+            let verts = [
+                a,
+                a + random_point(rng, 1.0).coords,
+                a + random_point(rng, 1.0).coords,
+            ];
+            let norm = Unit::new_normalize((verts[1] - verts[0]).cross(&(verts[2] - verts[0])));
+            shapes.push(Shape::Triangle(Triangle::new(verts, [norm; 3])));
+        }
+        for _ in 0..100 {
+            let centre = random_point(rng, 10.0);
+            shapes.push(Shape::Sphere(Sphere::new(centre, rng.gen_range(0.05..0.5))));
+        }
+        shapes
+    }
+
+    /// Construct the tree settings used by the tests.
+    fn settings() -> TreeBuilder {
+        TreeBuilder {
+            tar_tris: 4,
+            max_depth: 8,
+            padding: 0.01,
+            par_depth: None,
+            split_bins: None,
+            sah: None,
+        }
+    }
+
+    /// Collect the leaves descending from a cell.
+    fn leaves<'t, 'a>(cell: &'t TreeCell<'a, u8>, list: &mut Vec<&'t TreeCell<'a, u8>>) {
+        match *cell {
+            TreeCell::Leaf { .. } => list.push(cell),
+            TreeCell::Branch { ref children, .. } => {
+                for child in children.iter() {
+                    leaves(child, list);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn leaves_are_visited_in_ray_order() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let attr = 0;
+        let surfs = vec![Surface::from_shapes(random_shapes(&mut rng), &attr)];
+        let tree = Tree::new(&settings(), &surfs);
+        let mut all_leaves = Vec::new();
+        leaves(&tree.root, &mut all_leaves);
+
+        for n in 0..500 {
+            let ray = Ray::new(random_point(&mut rng, 12.0), random_dir(&mut rng, n));
+            let max_dist = 30.0;
+
+            let visited: Vec<_> = tree.traverse(ray.clone(), max_dist).collect();
+            for pair in visited.windows(2) {
+                assert!((pair[0].2 - pair[1].1).abs() < 1.0e-9);
+            }
+            for &(_, t0, t1) in &visited {
+                assert!(0.0 <= t0 && t0 <= t1 && t1 <= max_dist);
+            }
+
+            // Every leaf pierced over a non-zero length is visited.
+            for leaf in &all_leaves {
+                if let Some((entry, exit)) = leaf.boundary().dist_range(&ray) {
+                    if exit.min(max_dist) - entry > 1.0e-9 {
+                        assert!(visited
+                            .iter()
+                            .any(|&(cell, _, _)| core::ptr::eq(cell, *leaf)));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn scan_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let attr = 0;
+        let surfs = vec![Surface::from_shapes(random_shapes(&mut rng), &attr)];
+        let tree = Tree::new(&settings(), &surfs);
+
+        let mut num_hits = 0;
+        for n in 0..2000 {
+            let ray = Ray::new(random_point(&mut rng, 10.0), random_dir(&mut rng, n));
+
+            let nearest = surfs[0]
+                .shapes
+                .iter()
+                .filter_map(|shape| shape.dist(&ray))
+                .filter(|&dist| dist > 1.0e-9)
+                .min_by(f64::total_cmp);
+            let hit = tree.scan(ray, 1.0e-9, 1.0e9).map(|hit| hit.dist);
+
+            match (nearest, hit) {
+                (None, None) => {}
+                (Some(a), Some(b)) => {
+                    num_hits += 1;
+                    assert!((a - b).abs() < 1.0e-9);
+                }
+                _ => panic!("Mismatch: {nearest:?} {hit:?}"),
+            }
+        }
+        assert!(num_hits > 100);
+    }
+}
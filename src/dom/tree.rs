@@ -1,179 +1,362 @@
 //! Adaptive tree cell scheme.
 
 use nalgebra::Point3;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 use crate::{
-    dom::{Surface, TreeBuilder},
-    geom::{Cube, Triangle},
-    rt::{Hit, Ray, Scan},
+    dom::{Surface, Traversal, TreeBuilder},
+    geom::{Collide, Cube, Shape, Trace},
+    rt::{Hit, Ray},
     util::ProgressBar,
 };
 
+/// Default depth down to which cells are grown in parallel.
+const DEFAULT_PAR_DEPTH: u32 = 2;
+/// Relative reduction in cost required for a split plane to be preferred over one closer to the cell centre.
+const SPLIT_COST_TOLERANCE: f64 = 1.0e-9;
+
+/// Adaptive oct-tree of shapes.
+/// Bounded shapes are sorted into a hierarchy of cells.
+/// Unbounded shapes, such as planes, are held separately and tested against every Ray.
+pub struct Tree<'a, T, S = Shape> {
+    /// Root cell.
+    pub root: TreeCell<'a, T, S>,
+    /// Unbounded shapes and their corresponding attributes.
+    pub unbounded: Vec<(&'a S, &'a T)>,
+}
+
 /// Tree cell enumeration.
+/// Leaves reference shapes of any type, which must implement `Collide` to be sorted and `Trace` to be scanned.
 #[allow(clippy::exhaustive_enums)]
-pub enum Tree<'a, T> {
+pub enum TreeCell<'a, T, S = Shape> {
     /// Branching cell.
     Branch {
         /// Boundary.
         boundary: Cube,
         /// Children.
-        children: Box<[Tree<'a, T>; 8]>,
+        children: Box<[TreeCell<'a, T, S>; 8]>,
     },
     /// Terminal populated cell.
     Leaf {
         /// Boundary.
         boundary: Cube,
-        /// Intersecting triangles and their corresponding mesh index.
-        tris: Vec<(&'a Triangle, &'a T)>,
+        /// Intersecting shapes and their corresponding attributes.
+        shapes: Vec<(&'a S, &'a T)>,
     },
 }
 
-impl<'a, T> Tree<'a, T> {
+impl<'a, T, S> Tree<'a, T, S> {
     /// Construct a new instance.
+    /// Cells are grown in parallel down to the parallel depth of the settings.
+    /// If none of the shapes are bounded, the root is an empty leaf with a point boundary at the origin,
+    /// and only the unbounded shapes are tested.
     #[inline]
     #[must_use]
-    pub fn new(sett: &TreeBuilder, surfs: &'a [Surface<T>]) -> Self {
-        let mut boundary = Self::init_boundary(surfs);
+    pub fn new(sett: &TreeBuilder, surfs: &'a [Surface<T, S>]) -> Self
+    where
+        T: Sync,
+        S: Collide + Sync,
+    {
+        let mut boundary = match Self::init_boundary(surfs) {
+            Some(boundary) => boundary,
+            None => {
+                let origin = Point3::origin();
+                return Self {
+                    root: TreeCell::Leaf {
+                        boundary: Cube {
+                            mins: origin,
+                            maxs: origin,
+                        },
+                        shapes: Vec::new(),
+                    },
+                    unbounded: Self::init_unbounded(surfs),
+                };
+            }
+        };
         boundary.expand(sett.padding);
 
-        let mut tris = Vec::new();
+        let mut shapes = Vec::new();
         for surf in surfs {
-            tris.reserve(surf.mesh.tris.len());
-            for tri in &surf.mesh.tris {
-                tris.push((tri, surf.attr));
+            shapes.reserve(surf.shapes.len());
+            for shape in &surf.shapes {
+                if shape.bounds().is_some() {
+                    shapes.push((shape, surf.attr));
+                }
             }
         }
 
-        let mut pb = ProgressBar::new("Growing tree", 8_usize.pow(sett.max_depth));
-        if (sett.max_depth == 0) || (tris.len() <= sett.tar_tris) {
-            pb.finish_with_message("Tree grown");
-            return Self::Leaf { boundary, tris };
+        let pb = ProgressBar::new("Growing tree", 8_usize.pow(sett.max_depth));
+        let root = TreeCell::init_cell(&pb, sett, boundary, 0, shapes);
+        pb.finish_with_message("Tree grown");
+
+        Self {
+            root,
+            unbounded: Self::init_unbounded(surfs),
         }
+    }
 
-        let children = Box::new(Self::init_children(
-            &mut pb,
-            sett,
-            &boundary,
-            1,
-            tris.as_slice(),
-        ));
-        pb.finish_with_message("Tree grown");
+    /// Initialise the boundary encompassing all of the bounded shapes.
+    /// Returns `None` if there are no bounded shapes.
+    #[inline]
+    #[must_use]
+    fn init_boundary(surfs: &[Surface<T, S>]) -> Option<Cube> {
+        surfs.iter().filter_map(|surf| surf.boundary.as_ref()).fold(
+            None,
+            |acc: Option<Cube>, boundary| {
+                Some(acc.map_or_else(|| boundary.clone(), |acc| acc.union(boundary)))
+            },
+        )
+    }
 
-        Self::Branch { boundary, children }
+    /// Gather the unbounded shapes, and their attributes, of the given surfaces.
+    #[inline]
+    #[must_use]
+    pub fn init_unbounded(surfs: &'a [Surface<T, S>]) -> Vec<(&'a S, &'a T)>
+    where
+        S: Collide,
+    {
+        surfs
+            .iter()
+            .flat_map(|surf| {
+                surf.shapes
+                    .iter()
+                    .filter(|shape| shape.bounds().is_none())
+                    .map(move |shape| (shape, surf.attr))
+            })
+            .collect()
     }
 
-    /// Initialise the boundary encompassing all of the mesh vertices.
+    /// Reference the boundary of the bounded shapes.
     #[inline]
     #[must_use]
-    fn init_boundary(surfs: &[Surface<T>]) -> Cube {
-        let mut mins = None;
-        let mut maxs = None;
+    pub fn boundary(&self) -> &Cube {
+        self.root.boundary()
+    }
 
-        for surf in surfs {
-            let mesh_mins = surf.mesh.boundary.mins;
-            let mesh_maxs = surf.mesh.boundary.maxs;
-
-            if mins.is_none() {
-                mins = Some(mesh_mins);
-            } else {
-                for (grid_min, mesh_min) in mins
-                    .as_mut()
-                    .expect("Failed to initialise Tree boundary.")
-                    .iter_mut()
-                    .zip(mesh_mins.iter())
-                {
-                    if mesh_min < grid_min {
-                        *grid_min = *mesh_min;
-                    }
+    /// If a given position is contained within the Tree boundary,
+    /// determine the terminal leaf cell containing the given position.
+    #[inline]
+    #[must_use]
+    pub fn try_find_leaf(&self, pos: &Point3<f64>) -> Option<&TreeCell<'a, T, S>> {
+        self.root.try_find_leaf(pos)
+    }
+
+    /// Iterate over the leaf cells pierced by a given Ray, in order along the Ray.
+    /// Each leaf is accompanied by the distances at which the Ray enters and exits it.
+    /// Only cells within the maximum distance are visited.
+    #[inline]
+    #[must_use]
+    pub fn traverse(&self, ray: Ray, max_dist: f64) -> Traversal<'_, 'a, T, S> {
+        debug_assert!(max_dist > 0.0);
+
+        Traversal::new(&self.root, ray, 0.0, max_dist)
+    }
+
+    /// Determine what a given Ray would observe.
+    /// The Ray is first advanced by the bump distance, so that surfaces within it,
+    /// such as one the Ray has just left, are ignored.
+    /// Hit distances are measured from the original Ray position.
+    /// Unbounded shapes are tested first, and then leaf cells are visited in order along the Ray.
+    /// The maximum distance provided does not guarantee that any hit retrieved is less than the given distance.
+    #[inline]
+    #[must_use]
+    pub fn scan(&self, mut ray: Ray, bump_dist: f64, max_dist: f64) -> Option<Hit<'_, T>>
+    where
+        S: Trace,
+    {
+        debug_assert!(bump_dist > 0.0);
+        debug_assert!(max_dist > 0.0);
+
+        if max_dist <= bump_dist {
+            return None;
+        }
+        ray.travel(bump_dist);
+        let max_dist = max_dist - bump_dist;
+
+        let mut nearest: Option<Hit<T>> = None;
+        for &(shape, attr) in &self.unbounded {
+            if let Some((dist, side)) = shape.dist_side(&ray) {
+                if dist < nearest.as_ref().map_or(max_dist, |hit| hit.dist) {
+                    nearest = Some(Hit::new(attr, dist, side));
                 }
             }
+        }
 
-            if maxs.is_none() {
-                maxs = Some(mesh_maxs);
-            } else {
-                for (grid_max, mesh_max) in maxs
-                    .as_mut()
-                    .expect("Failed to initialise Tree boundary.")
-                    .iter_mut()
-                    .zip(mesh_maxs.iter())
-                {
-                    if mesh_max > grid_max {
-                        *grid_max = *mesh_max;
+        let limit = nearest.as_ref().map_or(max_dist, |hit| hit.dist);
+        for (cell, _, exit) in self.traverse(ray.clone(), limit) {
+            if let TreeCell::Leaf { ref shapes, .. } = *cell {
+                for &(shape, attr) in shapes {
+                    if let Some((dist, side)) = shape.dist_side(&ray) {
+                        if nearest.as_ref().map_or(true, |hit| dist < hit.dist) {
+                            nearest = Some(Hit::new(attr, dist, side));
+                        }
                     }
                 }
             }
+
+            // Shapes may span several cells, so only a hit within the current cell is final.
+            if nearest.as_ref().map_or(false, |hit| hit.dist <= exit) {
+                break;
+            }
         }
 
-        Cube::new(
-            mins.expect("Failed to initialise Tree boundary."),
-            maxs.expect("Failed to initialise Tree boundary."),
-        )
+        nearest.map(|mut hit| {
+            hit.dist += bump_dist;
+            hit
+        })
     }
+}
 
-    /// Initialise the children of a branching cell.
-    #[allow(clippy::similar_names)]
+impl<'a, T, S> TreeCell<'a, T, S> {
+    /// Initialise a cell, at the given depth, containing the given triangles.
+    /// Children are ordered such that the first, second and third bits of their index
+    /// indicate the upper side of the split point along the x, y and z axes respectively.
     #[inline]
     #[must_use]
-    fn init_children(
-        pb: &mut ProgressBar,
+    fn init_cell(
+        pb: &ProgressBar,
         sett: &TreeBuilder,
-        parent_boundary: &Cube,
+        boundary: Cube,
         depth: u32,
-        potential_tris: &[(&'a Triangle, &'a T)],
-    ) -> [Self; 8] {
+        shapes: Vec<(&'a S, &'a T)>,
+    ) -> Self
+    where
+        T: Sync,
+        S: Collide + Sync,
+    {
         debug_assert!(depth <= sett.max_depth);
-        debug_assert!(!potential_tris.is_empty());
 
-        let hws = parent_boundary.half_widths();
-        let mut make_child = |min_x: f64, min_y: f64, min_z: f64| {
-            let min = Point3::new(min_x, min_y, min_z);
-            Self::init_child(pb, sett, Cube::new(min, min + hws), depth, potential_tris)
+        if (shapes.len() <= sett.tar_tris) || (depth >= sett.max_depth) {
+            pb.block(8_usize.pow(sett.max_depth - depth));
+            return Self::Leaf { boundary, shapes };
+        }
+
+        let split = sett.split_bins.map_or_else(
+            || boundary.centre(),
+            |bins| Self::sah_split(&boundary, &shapes, bins),
+        );
+
+        let make_child = |index: usize| {
+            let mut mins = boundary.mins;
+            let mut maxs = split;
+            for axis in 0..3 {
+                if index & (1 << axis) != 0 {
+                    mins[axis] = split[axis];
+                    maxs[axis] = boundary.maxs[axis];
+                }
+            }
+            let child_boundary = Cube::new(mins, maxs);
+
+            let mut detection_vol = child_boundary.clone();
+            detection_vol.expand(sett.padding);
+            let child_shapes: Vec<_> = shapes
+                .iter()
+                .filter(|&&(shape, _)| shape.collides(&detection_vol))
+                .copied()
+                .collect();
+
+            (child_boundary, child_shapes)
         };
 
-        let min = parent_boundary.mins;
+        let parallel = depth < sett.par_depth.unwrap_or(DEFAULT_PAR_DEPTH);
+        let cells: Vec<_> = if parallel {
+            (0..8).into_par_iter().map(make_child).collect()
+        } else {
+            (0..8).map(make_child).collect()
+        };
 
-        let nnn = make_child(min.x, min.y, min.z);
-        let pnn = make_child(min.x + hws.x, min.y, min.z);
-        let npn = make_child(min.x, min.y + hws.y, min.z);
-        let ppn = make_child(min.x + hws.x, min.y + hws.y, min.z);
-        let nnp = make_child(min.x, min.y, min.z + hws.z);
-        let pnp = make_child(min.x + hws.x, min.y, min.z + hws.z);
-        let npp = make_child(min.x, min.y + hws.y, min.z + hws.z);
-        let ppp = make_child(min.x + hws.x, min.y + hws.y, min.z + hws.z);
+        if let Some(costs) = sett.sah {
+            let area = boundary.area();
+            let split_cost = costs.traversal
+                + cells
+                    .iter()
+                    .map(|&(ref child_boundary, ref child_shapes)| {
+                        costs.intersection * child_shapes.len() as f64 * child_boundary.area()
+                            / area
+                    })
+                    .sum::<f64>();
+            if split_cost >= costs.intersection * shapes.len() as f64 {
+                pb.block(8_usize.pow(sett.max_depth - depth));
+                return Self::Leaf { boundary, shapes };
+            }
+        }
+        drop(shapes);
+
+        let make_cell = |(child_boundary, child_shapes)| {
+            Self::init_cell(pb, sett, child_boundary, depth + 1, child_shapes)
+        };
+        let children: Vec<_> = if parallel {
+            cells.into_par_iter().map(make_cell).collect()
+        } else {
+            cells.into_iter().map(make_cell).collect()
+        };
 
-        [nnn, pnn, npn, ppn, nnp, pnp, npp, ppp]
+        match children.try_into() {
+            Ok(children) => Self::Branch {
+                boundary,
+                children: Box::new(children),
+            },
+            Err(_) => panic!("Failed to initialise Tree children."),
+        }
     }
 
-    /// Initialise a child cell.
+    /// Determine the split point of a cell using the surface area heuristic.
+    /// Each axis is considered independently, with candidate planes at the boundaries between bins.
+    /// Ties are resolved in favour of the plane closest to the centre.
     #[inline]
     #[must_use]
-    fn init_child(
-        pb: &mut ProgressBar,
-        sett: &TreeBuilder,
-        boundary: Cube,
-        depth: u32,
-        potential_tris: &[(&'a Triangle, &'a T)],
-    ) -> Tree<'a, T> {
-        debug_assert!(depth <= sett.max_depth);
+    fn sah_split(boundary: &Cube, shapes: &[(&'a S, &'a T)], bins: usize) -> Point3<f64>
+    where
+        S: Collide,
+    {
+        debug_assert!(bins >= 2);
+
+        let widths = boundary.widths();
+        let mut split = boundary.centre();
+
+        // Only bounded shapes are sorted into cells.
+        let bounds: Vec<_> = shapes
+            .iter()
+            .filter_map(|&(shape, _)| shape.bounds())
+            .collect();
+
+        for axis in 0..3 {
+            let (b, c) = ((axis + 1) % 3, (axis + 2) % 3);
+            let min = boundary.mins[axis];
+            let width = widths[axis];
+            let bin_of = |x: f64| (((x - min) / width * bins as f64) as usize).min(bins - 1);
+
+            // Number of shapes starting and ending in each bin.
+            let mut starts = vec![0_usize; bins];
+            let mut ends = vec![0_usize; bins];
+            for shape_bounds in &bounds {
+                starts[bin_of(shape_bounds.mins[axis].max(min))] += 1;
+                ends[bin_of(shape_bounds.maxs[axis].min(boundary.maxs[axis]))] += 1;
+            }
 
-        let mut detection_vol = boundary.clone();
-        detection_vol.expand(sett.padding);
+            // Surface area of a part of the cell of the given length along the axis.
+            let part_area =
+                |len: f64| 2.0 * len.mul_add(widths[b] + widths[c], widths[b] * widths[c]);
 
-        let mut tris = Vec::new();
-        for &(tri, attr) in potential_tris {
-            if tri.collides(&detection_vol) {
-                tris.push((tri, attr));
-            }
-        }
+            let mut candidates: Vec<_> = (1..bins).collect();
+            candidates.sort_by_key(|&i| (2 * i).abs_diff(bins));
 
-        if (tris.len() <= sett.tar_tris) || (depth >= sett.max_depth) {
-            pb.block(8_usize.pow(sett.max_depth - depth));
-            return Tree::Leaf { boundary, tris };
-        }
+            let mut best_cost = f64::INFINITY;
+            for i in candidates {
+                let num_left: usize = starts[..i].iter().sum();
+                let num_right: usize = ends[i..].iter().sum();
+                let len = width * i as f64 / bins as f64;
+                let cost = part_area(len)
+                    .mul_add(num_left as f64, part_area(width - len) * num_right as f64);
 
-        let children = Box::new(Self::init_children(pb, sett, &boundary, depth + 1, &tris));
+                if cost < best_cost * (1.0 - SPLIT_COST_TOLERANCE) {
+                    best_cost = cost;
+                    split[axis] = min + len;
+                }
+            }
+        }
 
-        Tree::Branch { boundary, children }
+        split
     }
 
     /// Reference the cell's boundary.
@@ -186,7 +369,7 @@ impl<'a, T> Tree<'a, T> {
         }
     }
 
-    /// If a given position is contained within the cell to being with,
+    /// If a given position is contained within the cell to begin with,
     /// determine the terminal leaf cell containing the given position.
     #[inline]
     #[must_use]
@@ -206,12 +389,10 @@ impl<'a, T> Tree<'a, T> {
 
         match *self {
             Self::Leaf { .. } => self,
-            Self::Branch {
-                ref boundary,
-                ref children,
-            } => {
+            Self::Branch { ref children, .. } => {
+                // The first child's maximum bound is the split point.
                 let mut index = 0;
-                let c = boundary.centre();
+                let c = children[0].boundary().maxs;
 
                 if pos.x >= c.x {
                     index += 1;
@@ -226,92 +407,94 @@ impl<'a, T> Tree<'a, T> {
             }
         }
     }
+}
 
-    /// Scan for what a given Ray, known to be within the cell, would observe.
-    #[inline]
-    #[must_use]
-    fn leaf_scan(&self, ray: &Ray, bump_dist: f64) -> Scan<T> {
-        debug_assert!(self.boundary().contains(&ray.pos));
-        debug_assert!(bump_dist > 0.0);
-
-        match *self {
-            Self::Branch { .. } => {
-                panic!("Should not be performing hit scans on branching cells!");
-            }
-            Self::Leaf {
-                ref boundary,
-                ref tris,
-            } => {
-                let boundary_dist = boundary
-                    .dist(ray)
-                    .expect("Failed to perform Ray-Tree intersection.");
-                if tris.is_empty() {
-                    return Scan::new_boundary(boundary_dist);
-                }
-
-                let mut nearest: Option<Hit<T>> = None;
-                for &(tri, attr) in tris {
-                    if let Some((dist, side)) = tri.dist_side(ray) {
-                        if let Some(ref hit) = nearest {
-                            if dist < hit.dist {
-                                nearest = Some(Hit::new(attr, dist, side));
-                            }
-                        } else {
-                            nearest = Some(Hit::new(attr, dist, side));
-                        }
-                    }
-                }
-
-                if let Some(hit) = nearest {
-                    if hit.dist < (boundary_dist + bump_dist) {
-                        return Scan::new_surface(hit);
-                    }
-                }
-
-                Scan::new_boundary(boundary_dist)
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dom::BvhBuilder,
+        geom::{Plane, Sphere},
+    };
+    use nalgebra::Vector3;
+
+    /// Construct the tree settings used by the tests.
+    fn settings() -> TreeBuilder {
+        TreeBuilder {
+            tar_tris: 1,
+            max_depth: 3,
+            padding: 0.01,
+            par_depth: None,
+            split_bins: None,
+            sah: None,
         }
     }
 
-    /// Determine what a given Ray would observe.
-    /// The maximum distance provided does not guarantee that any hit retrieved is less than the given distance.
-    #[inline]
-    #[must_use]
-    pub fn scan(&self, mut ray: Ray, bump_dist: f64, max_dist: f64) -> Option<Hit<T>> {
-        debug_assert!(bump_dist > 0.0);
-        debug_assert!(max_dist > 0.0);
+    #[test]
+    fn scan_skips_surfaces_within_the_bump_distance() {
+        let attr = 0;
+        let surfs = vec![Surface::from_shapes(
+            vec![
+                Shape::Sphere(Sphere::new(Point3::origin(), 1.0)),
+                Shape::Sphere(Sphere::new(Point3::new(5.0, 5.0, 5.0), 1.0)),
+            ],
+            &attr,
+        )];
+        let tree = settings().build(&surfs).expect("Failed to build tree.");
+        let bvh = BvhBuilder::default().build(&surfs);
+
+        // Start just outside of the sphere, heading inwards.
+        let ray = Ray::new(Point3::new(1.0 + 1.0e-7, 0.0, 0.0), -Vector3::x_axis());
+        let scans = [
+            tree.scan(ray.clone(), 1.0e-8, 10.0),
+            bvh.scan(ray.clone(), 1.0e-8, 10.0),
+            tree.scan(ray.clone(), 1.0e-6, 10.0),
+            bvh.scan(ray.clone(), 1.0e-6, 10.0),
+        ];
+        let [near_tree, near_bvh, far_tree, far_bvh] = scans.map(|hit| hit.expect("Missing hit."));
+
+        for near in [near_tree, near_bvh] {
+            assert!((near.dist - 1.0e-7).abs() < 1.0e-12);
+            assert!(!near.side.is_inside());
+        }
+        for far in [far_tree, far_bvh] {
+            assert!((far.dist - (2.0 + 1.0e-7)).abs() < 1.0e-12);
+            assert!(far.side.is_inside());
+        }
 
-        let mut dist_traveled = 0.0;
+        assert!(tree.scan(ray.clone(), 1.0e-6, 1.0e-6).is_none());
+        assert!(bvh.scan(ray, 1.0e-6, 1.0e-6).is_none());
+    }
 
-        // Move the ray to within the domain of the cell if it isn't already within it.
-        if !self.boundary().contains(&ray.pos) {
-            if let Some(dist) = self.boundary().dist(&ray) {
-                let d = dist + bump_dist;
-                ray.travel(d);
-                dist_traveled += d;
-            } else {
-                return None;
-            }
+    #[test]
+    fn unbounded_shapes_alone_form_an_empty_tree() {
+        let attr = 0;
+        let surfs = vec![Surface::from_shapes(
+            vec![Shape::Plane(Plane::new(
+                Point3::origin(),
+                Vector3::z_axis(),
+            ))],
+            &attr,
+        )];
+        let tree = settings().build(&surfs).expect("Failed to build tree.");
+
+        match tree.root {
+            TreeCell::Leaf { ref shapes, .. } => assert!(shapes.is_empty()),
+            TreeCell::Branch { .. } => panic!("Root cell should be a leaf."),
         }
+        assert_eq!(tree.unbounded.len(), 1);
+        assert_eq!(tree.boundary().mins, Point3::origin());
+        assert_eq!(tree.boundary().maxs, Point3::origin());
 
-        while let Some(cell) = self.try_find_leaf(&ray.pos) {
-            if dist_traveled > max_dist {
-                return None;
-            }
+        let down = Ray::new(Point3::new(100.0, 0.0, 10.0), -Vector3::z_axis());
+        let hit = tree.scan(down, 1.0e-9, 100.0).expect("Missing hit.");
+        assert!((hit.dist - 10.0).abs() < 1.0e-9);
+        assert!(!hit.side.is_inside());
 
-            match cell.leaf_scan(&ray, bump_dist) {
-                Scan::Surface(mut hit) => {
-                    hit.dist += dist_traveled;
-                    return Some(hit);
-                }
-                Scan::Boundary(dist) => {
-                    let d = dist + bump_dist;
-                    ray.travel(d);
-                    dist_traveled += d;
-                }
-            }
-        }
+        let up = Ray::new(Point3::new(100.0, 0.0, 10.0), Vector3::z_axis());
+        assert!(tree.scan(up, 1.0e-9, 100.0).is_none());
 
-        None
+        let empty: Vec<Surface<i32>> = Vec::new();
+        assert!(settings().build(&empty).is_ok());
     }
 }
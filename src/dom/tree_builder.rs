@@ -2,24 +2,194 @@
 
 use serde::Deserialize;
 
-use crate::dom::{Surface, Tree};
+use crate::{
+    dom::{Surface, Tree},
+    geom::{Collide, Trace},
+    Error,
+};
 
 /// Tree construction settings.
 #[derive(Deserialize)]
 pub struct TreeBuilder {
-    /// Target maximum number of triangles per cell.
+    /// Target maximum number of shapes per cell.
     pub tar_tris: usize,
     /// Maximum mesh depth.
     pub max_depth: u32,
     /// Collision detection expansion parameter.
     pub padding: f64,
+    /// Optional depth down to which cells are grown in parallel.
+    pub par_depth: Option<u32>,
+    /// Optional number of bins used to place split planes by the surface area heuristic.
+    /// Cells are split at their centre if not given.
+    pub split_bins: Option<usize>,
+    /// Optional surface area heuristic costs.
+    /// If given, cells are only split when it is expected to reduce the cost of tracing through them.
+    pub sah: Option<SahCosts>,
+}
+
+/// Relative costs used by the surface area heuristic.
+#[derive(Clone, Copy, Deserialize)]
+pub struct SahCosts {
+    /// Cost of visiting a branching cell.
+    pub traversal: f64,
+    /// Cost of testing a shape for intersection.
+    pub intersection: f64,
 }
 
 impl TreeBuilder {
     /// Build a Tree instance.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fewer than two split bins are requested,
+    /// or if a surface area heuristic cost is not positive.
     #[inline]
-    #[must_use]
-    pub fn build<'a, T>(&self, surfs: &'a [Surface<T>]) -> Tree<'a, T> {
-        Tree::new(self, surfs)
+    pub fn build<'a, T: Sync, S: Collide + Trace + Sync>(
+        &self,
+        surfs: &'a [Surface<T, S>],
+    ) -> Result<Tree<'a, T, S>, Error> {
+        if let Some(bins) = self.split_bins {
+            if bins < 2 {
+                return Err(Error::InvalidParameter(format!(
+                    "Tree requires at least two split bins: {bins}."
+                )));
+            }
+        }
+        if let Some(costs) = self.sah {
+            if !(costs.traversal > 0.0 && costs.intersection > 0.0) {
+                return Err(Error::InvalidParameter(format!(
+                    "Tree surface area heuristic costs must be positive: {}, {}.",
+                    costs.traversal, costs.intersection
+                )));
+            }
+        }
+
+        Ok(Tree::new(self, surfs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dom::TreeCell,
+        geom::{Shape, Sphere, Triangle},
+        rt::Ray,
+    };
+    use nalgebra::{Point3, Unit, Vector3};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// Construct tree settings placing splits by the surface area heuristic.
+    fn settings() -> TreeBuilder {
+        TreeBuilder {
+            tar_tris: 4,
+            max_depth: 8,
+            padding: 0.01,
+            par_depth: None,
+            split_bins: Some(8),
+            sah: Some(SahCosts {
+                traversal: 1.0,
+                intersection: 2.0,
+            }),
+        }
+    }
+
+    /// Generate a random point within a cube of the given half-width.
+    fn random_point(rng: &mut StdRng, half_width: f64) -> Point3<f64> {
+        Point3::new(
+            rng.gen_range(-half_width..half_width),
+            rng.gen_range(-half_width..half_width),
+            rng.gen_range(-half_width..half_width),
+        )
+    }
+
+    /// Generate clustered triangles and spheres, so split planes are placed unevenly.
+    fn random_shapes(rng: &mut StdRng) -> Vec<Shape> {
+        let mut shapes = Vec::new();
+        for n in 0..300 {
+            let offset = if n % 3 == 0 { 6.0 } else { -4.0 };
+            let a = random_point(rng, 2.0) + Vector3::repeat(offset);
+            let verts = [
+                a,
+                a + random_point(rng, 1.0).coords,
+                a + random_point(rng, 1.0).coords,
+            ];
+            let norm = Unit::new_normalize((verts[1] - verts[0]).cross(&(verts[2] - verts[0])));
+            shapes.push(Shape::Triangle(Triangle::new(verts, [norm; 3])));
+        }
+        for _ in 0..50 {
+            let centre = random_point(rng, 8.0);
+            shapes.push(Shape::Sphere(Sphere::new(centre, rng.gen_range(0.05..0.5))));
+        }
+        shapes
+    }
+
+    #[test]
+    fn sah_tree_scan_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let attr = 0;
+        let surfs = vec![Surface::from_shapes(random_shapes(&mut rng), &attr)];
+        let tree = settings().build(&surfs).expect("Failed to build tree.");
+        assert!(matches!(tree.root, TreeCell::Branch { .. }));
+
+        let mut num_hits = 0;
+        for _ in 0..2000 {
+            let dir = Unit::new_normalize(random_point(&mut rng, 1.0).coords);
+            let ray = Ray::new(random_point(&mut rng, 8.0), dir);
+
+            let nearest = surfs[0]
+                .shapes
+                .iter()
+                .filter_map(|shape| shape.dist(&ray))
+                .filter(|&dist| dist > 1.0e-9)
+                .min_by(f64::total_cmp);
+            let hit = tree.scan(ray, 1.0e-9, 1.0e9).map(|hit| hit.dist);
+
+            match (nearest, hit) {
+                (None, None) => {}
+                (Some(a), Some(b)) => {
+                    num_hits += 1;
+                    assert!((a - b).abs() < 1.0e-9);
+                }
+                _ => panic!("Mismatch: {nearest:?} {hit:?}"),
+            }
+        }
+        assert!(num_hits > 100);
+    }
+
+    #[test]
+    fn invalid_split_settings_are_rejected() {
+        let attr = 0;
+        let surfs = vec![Surface::from_shapes(
+            vec![Shape::Sphere(Sphere::new(Point3::origin(), 1.0))],
+            &attr,
+        )];
+
+        for bins in [0, 1] {
+            let sett = TreeBuilder {
+                split_bins: Some(bins),
+                ..settings()
+            };
+            assert!(matches!(
+                sett.build(&surfs),
+                Err(Error::InvalidParameter(_))
+            ));
+        }
+
+        for (traversal, intersection) in [(0.0, 1.0), (1.0, -1.0), (f64::NAN, 1.0)] {
+            let sett = TreeBuilder {
+                sah: Some(SahCosts {
+                    traversal,
+                    intersection,
+                }),
+                ..settings()
+            };
+            assert!(matches!(
+                sett.build(&surfs),
+                Err(Error::InvalidParameter(_))
+            ));
+        }
+
+        assert!(settings().build(&surfs).is_ok());
     }
 }
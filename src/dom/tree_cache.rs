@@ -0,0 +1,579 @@
+//! Oct-tree persistence.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+
+use crate::{
+    dom::{Surface, Tree, TreeBuilder, TreeCell},
+    geom::{Collide, Cube, Fingerprint, Trace},
+    Error,
+};
+
+/// Cache file format version.
+/// Incremented whenever the layout or cache key changes, so that stale files are rebuilt.
+const FORMAT_VERSION: u32 = 4;
+
+/// Prefix of cache file names.
+const FILE_PREFIX: &str = "tree_";
+
+/// FNV-1a offset basis.
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// FNV-1a prime.
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// 64-bit FNV-1a hasher.
+/// Unlike the standard library hasher, the result is stable between builds and platforms.
+struct Fnv(u64);
+
+impl Fnv {
+    /// Construct a new instance.
+    #[inline]
+    #[must_use]
+    const fn new() -> Self {
+        Self(FNV_OFFSET)
+    }
+
+    /// Feed a value into the hash, as little-endian bytes.
+    #[inline]
+    fn write(&mut self, x: u64) {
+        for byte in x.to_le_bytes() {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    /// Feed an optional value into the hash.
+    #[inline]
+    fn write_opt(&mut self, x: Option<u64>) {
+        match x {
+            None => self.write(0),
+            Some(x) => {
+                self.write(1);
+                self.write(x);
+            }
+        }
+    }
+
+    /// Get the hash value.
+    #[inline]
+    #[must_use]
+    const fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Serialised tree cell.
+#[derive(Serialize, Deserialize)]
+enum CellData {
+    /// Branching cell.
+    Branch {
+        /// Boundary.
+        boundary: Cube,
+        /// Children.
+        children: Box<[CellData; 8]>,
+    },
+    /// Terminal cell.
+    Leaf {
+        /// Boundary.
+        boundary: Cube,
+        /// Surface and shape indices of the intersecting shapes.
+        shapes: Vec<[u32; 2]>,
+    },
+}
+
+/// Serialised tree.
+#[derive(Serialize, Deserialize)]
+struct TreeFile {
+    /// Format version.
+    version: u32,
+    /// Hash of the construction settings and geometry.
+    key: u64,
+    /// Root cell.
+    root: CellData,
+}
+
+impl TreeBuilder {
+    /// Calculate the cache key of the Tree built from the given surfaces.
+    /// The key depends on the cache format version, the construction settings and the surface geometry,
+    /// but not the surface attributes.
+    /// It is stable between builds and platforms.
+    #[inline]
+    #[must_use]
+    pub fn cache_key<T, S: Fingerprint>(&self, surfs: &[Surface<T, S>]) -> u64 {
+        let mut hasher = Fnv::new();
+
+        hasher.write(u64::from(FORMAT_VERSION));
+        hasher.write(self.tar_tris as u64);
+        hasher.write(u64::from(self.max_depth));
+        hasher.write(self.padding.to_bits());
+        hasher.write_opt(self.split_bins.map(|bins| bins as u64));
+        hasher.write_opt(self.sah.map(|costs| costs.traversal.to_bits()));
+        hasher.write_opt(self.sah.map(|costs| costs.intersection.to_bits()));
+
+        hasher.write(surfs.len() as u64);
+        for surf in surfs {
+            hasher.write(surf.shapes.len() as u64);
+            for shape in &surf.shapes {
+                shape.fingerprint(&mut |x| hasher.write(x));
+            }
+        }
+
+        hasher.finish()
+    }
+
+    /// Load the Tree from the cache directory if a matching one has been saved,
+    /// otherwise build it and save it to the cache directory.
+    /// Files written by other cache format versions are removed when a new Tree is saved.
+    /// Files for other settings or geometry are kept, so the directory grows
+    /// with each distinct scene and should be cleared manually when no longer needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings are invalid,
+    /// or if a newly built Tree can not be written to the cache directory.
+    #[inline]
+    pub fn build_cached<'a, T: Sync, S: Collide + Trace + Fingerprint + Sync>(
+        &self,
+        surfs: &'a [Surface<T, S>],
+        cache_dir: &Path,
+    ) -> Result<Tree<'a, T, S>, Error> {
+        let version_prefix = format!("{FILE_PREFIX}v{FORMAT_VERSION}_");
+        let path = cache_dir.join(format!(
+            "{version_prefix}{:016x}.bin",
+            self.cache_key(surfs)
+        ));
+
+        if path.exists() {
+            if let Ok(tree) = Tree::load(self, surfs, &path) {
+                return Ok(tree);
+            }
+        }
+
+        let tree = self.build(surfs)?;
+        fs::create_dir_all(cache_dir).map_err(|err| Error::Io(cache_dir.to_path_buf(), err))?;
+        tree.save(self, surfs, &path)?;
+        prune_cache(cache_dir, &version_prefix);
+
+        Ok(tree)
+    }
+}
+
+impl<'a, T, S: Collide + Fingerprint> Tree<'a, T, S> {
+    /// Save the structure of the Tree, built from the given surfaces with the given settings, as a binary file.
+    /// Shapes are stored as indices into the surfaces.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can not be written,
+    /// or if the Tree references a shape which is not part of the given surfaces.
+    #[inline]
+    pub fn save(
+        &self,
+        sett: &TreeBuilder,
+        surfs: &[Surface<T, S>],
+        path: &Path,
+    ) -> Result<(), Error> {
+        let mut indices = HashMap::new();
+        for (si, surf) in surfs.iter().enumerate() {
+            for (shi, shape) in surf.shapes.iter().enumerate() {
+                indices.insert(shape as *const S, [si as u32, shi as u32]);
+            }
+        }
+
+        let root = self.root.to_data(&indices).ok_or_else(|| {
+            Error::Cache(
+                path.to_path_buf(),
+                "Tree references a shape outside of the given surfaces.".to_owned(),
+            )
+        })?;
+        let file = TreeFile {
+            version: FORMAT_VERSION,
+            key: sett.cache_key(surfs),
+            root,
+        };
+
+        let writer =
+            BufWriter::new(File::create(path).map_err(|err| Error::Io(path.to_path_buf(), err))?);
+        bincode::serialize_into(writer, &file)
+            .map_err(|err| Error::Cache(path.to_path_buf(), err.to_string()))
+    }
+
+    /// Load a Tree, saved with the given settings, referencing the given surfaces.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can not be read,
+    /// or if it was not saved from the given settings and surface geometry.
+    #[inline]
+    pub fn load(
+        sett: &TreeBuilder,
+        surfs: &'a [Surface<T, S>],
+        path: &Path,
+    ) -> Result<Self, Error> {
+        let reader =
+            BufReader::new(File::open(path).map_err(|err| Error::Io(path.to_path_buf(), err))?);
+        let file: TreeFile = bincode::deserialize_from(reader)
+            .map_err(|err| Error::Cache(path.to_path_buf(), err.to_string()))?;
+
+        if file.version != FORMAT_VERSION {
+            return Err(Error::Cache(
+                path.to_path_buf(),
+                format!("Unsupported format version: {}", file.version),
+            ));
+        }
+        if file.key != sett.cache_key(surfs) {
+            return Err(Error::Cache(
+                path.to_path_buf(),
+                "Tree was built from different settings or geometry.".to_owned(),
+            ));
+        }
+
+        let root = TreeCell::from_data(file.root, surfs).ok_or_else(|| {
+            Error::Cache(
+                path.to_path_buf(),
+                "Tree references a missing shape.".to_owned(),
+            )
+        })?;
+
+        Ok(Self {
+            root,
+            unbounded: Self::init_unbounded(surfs),
+        })
+    }
+}
+
+impl<'a, T, S> TreeCell<'a, T, S> {
+    /// Convert to the serialisable form, using the given shape indices.
+    #[inline]
+    #[must_use]
+    fn to_data(&self, indices: &HashMap<*const S, [u32; 2]>) -> Option<CellData> {
+        Some(match *self {
+            Self::Branch {
+                ref boundary,
+                ref children,
+            } => {
+                let [c0, c1, c2, c3, c4, c5, c6, c7] = &**children;
+                CellData::Branch {
+                    boundary: boundary.clone(),
+                    children: Box::new([
+                        c0.to_data(indices)?,
+                        c1.to_data(indices)?,
+                        c2.to_data(indices)?,
+                        c3.to_data(indices)?,
+                        c4.to_data(indices)?,
+                        c5.to_data(indices)?,
+                        c6.to_data(indices)?,
+                        c7.to_data(indices)?,
+                    ]),
+                }
+            }
+            Self::Leaf {
+                ref boundary,
+                ref shapes,
+            } => CellData::Leaf {
+                boundary: boundary.clone(),
+                shapes: shapes
+                    .iter()
+                    .map(|&(shape, _)| indices.get(&(shape as *const S)).copied())
+                    .collect::<Option<_>>()?,
+            },
+        })
+    }
+
+    /// Reconstruct from the serialisable form, referencing the given surfaces.
+    #[inline]
+    #[must_use]
+    fn from_data(data: CellData, surfs: &'a [Surface<T, S>]) -> Option<Self> {
+        Some(match data {
+            CellData::Branch { boundary, children } => {
+                let [c0, c1, c2, c3, c4, c5, c6, c7] = *children;
+                Self::Branch {
+                    boundary,
+                    children: Box::new([
+                        Self::from_data(c0, surfs)?,
+                        Self::from_data(c1, surfs)?,
+                        Self::from_data(c2, surfs)?,
+                        Self::from_data(c3, surfs)?,
+                        Self::from_data(c4, surfs)?,
+                        Self::from_data(c5, surfs)?,
+                        Self::from_data(c6, surfs)?,
+                        Self::from_data(c7, surfs)?,
+                    ]),
+                }
+            }
+            CellData::Leaf { boundary, shapes } => Self::Leaf {
+                boundary,
+                shapes: shapes
+                    .into_iter()
+                    .map(|[si, shi]| {
+                        let surf = surfs.get(si as usize)?;
+                        Some((surf.shapes.get(shi as usize)?, surf.attr))
+                    })
+                    .collect::<Option<_>>()?,
+            },
+        })
+    }
+}
+
+/// Remove the cache files of other format versions from the cache directory.
+/// Failures are ignored, as stale files are never loaded.
+#[inline]
+fn prune_cache(cache_dir: &Path, version_prefix: &str) {
+    if let Ok(entries) = fs::read_dir(cache_dir) {
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with(FILE_PREFIX)
+                && name.ends_with(".bin")
+                && !name.starts_with(version_prefix)
+            {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dom::{BvhBuilder, Partition},
+        geom::{Mesh, Plane, Shape, Sphere},
+        rt::{Ray, Side},
+    };
+    use nalgebra::{Point3, Unit, Vector3};
+    use std::path::PathBuf;
+
+    /// Construct the tree settings used by the tests.
+    fn settings() -> TreeBuilder {
+        TreeBuilder {
+            tar_tris: 2,
+            max_depth: 4,
+            padding: 0.01,
+            par_depth: None,
+            split_bins: None,
+            sah: None,
+        }
+    }
+
+    /// Construct surfaces containing bounded and unbounded shapes.
+    fn surfaces(attrs: &[u8; 2]) -> Vec<Surface<'_, u8>> {
+        let cube = Cube::new(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0));
+        vec![
+            Surface::new(Mesh::new(cube.tris()), &attrs[0]),
+            Surface::from_shapes(
+                vec![
+                    Shape::Sphere(Sphere::new(Point3::new(2.0, 0.5, 0.0), 0.5)),
+                    Shape::Plane(Plane::new(Point3::new(0.0, 0.0, -3.0), Vector3::z_axis())),
+                ],
+                &attrs[1],
+            ),
+        ]
+    }
+
+    /// Sphere wrapper, standing in for geometry defined outside of the crate.
+    struct Ball(Sphere);
+
+    impl Collide for Ball {
+        fn bounds(&self) -> Option<Cube> {
+            self.0.bounds()
+        }
+
+        fn collides(&self, cube: &Cube) -> bool {
+            self.0.collides(cube)
+        }
+    }
+
+    impl Trace for Ball {
+        fn dist_side(&self, ray: &Ray) -> Option<(f64, Side)> {
+            self.0.dist_side(ray)
+        }
+    }
+
+    impl Fingerprint for Ball {
+        fn fingerprint(&self, write: &mut dyn FnMut(u64)) {
+            for x in self.0.centre.iter().chain(Some(&self.0.radius)) {
+                write(x.to_bits());
+            }
+        }
+    }
+
+    /// Create an empty scratch directory for a test.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("arctk_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("Failed to create scratch directory.");
+        dir
+    }
+
+    #[test]
+    fn save_load_round_trip() {
+        let attrs = [0, 1];
+        let surfs = surfaces(&attrs);
+        let sett = settings();
+        let tree = sett.build(&surfs).expect("Failed to build tree.");
+        let dir = scratch_dir("round_trip");
+        let path = dir.join("tree.bin");
+
+        tree.save(&sett, &surfs, &path)
+            .expect("Failed to save tree.");
+        let loaded = Tree::load(&sett, &surfs, &path).expect("Failed to load tree.");
+
+        let indices: HashMap<_, _> = surfs
+            .iter()
+            .enumerate()
+            .flat_map(|(si, surf)| {
+                surf.shapes
+                    .iter()
+                    .enumerate()
+                    .map(move |(shi, shape)| (shape as *const Shape, [si as u32, shi as u32]))
+            })
+            .collect();
+        let encode = |tree: &Tree<'_, u8>| {
+            bincode::serialize(
+                &tree
+                    .root
+                    .to_data(&indices)
+                    .expect("Failed to convert tree."),
+            )
+            .expect("Failed to serialise tree.")
+        };
+        assert_eq!(encode(&tree), encode(&loaded));
+        assert_eq!(loaded.unbounded.len(), 1);
+
+        for n in 0..100 {
+            let theta = n as f64 * 0.1;
+            let dir = Unit::new_normalize(Vector3::new(theta.cos(), theta.sin(), -0.3));
+            let ray = Ray::new(Point3::new(0.1, 0.2, 0.3), dir);
+            let a = tree
+                .scan(ray.clone(), 1.0e-9, 100.0)
+                .map(|hit| (hit.dist, *hit.tag));
+            let b = loaded
+                .scan(ray, 1.0e-9, 100.0)
+                .map(|hit| (hit.dist, *hit.tag));
+            assert_eq!(a, b);
+        }
+
+        fs::remove_dir_all(dir).expect("Failed to remove scratch directory.");
+    }
+
+    #[test]
+    fn load_rejects_different_settings_and_geometry() {
+        let attrs = [0, 1];
+        let surfs = surfaces(&attrs);
+        let sett = settings();
+        let tree = sett.build(&surfs).expect("Failed to build tree.");
+        let dir = scratch_dir("mismatch");
+        let path = dir.join("tree.bin");
+        tree.save(&sett, &surfs, &path)
+            .expect("Failed to save tree.");
+
+        let other_sett = TreeBuilder {
+            max_depth: 5,
+            ..settings()
+        };
+        assert!(Tree::load(&other_sett, &surfs, &path).is_err());
+        assert!(Tree::load(&sett, &surfs[..1], &path).is_err());
+
+        fs::remove_dir_all(dir).expect("Failed to remove scratch directory.");
+    }
+
+    #[test]
+    fn cache_key_is_stable() {
+        let attrs = [0, 1];
+        let surfs = surfaces(&attrs);
+
+        let key = settings().cache_key(&surfs);
+
+        // Keys must not change between builds or platforms, or cached trees are never reused.
+        assert_eq!(key, 0xd1fa_254c_ce0a_b7b7);
+        assert_ne!(key, settings().cache_key(&surfs[..1]));
+        assert_ne!(
+            key,
+            TreeBuilder {
+                split_bins: Some(8),
+                ..settings()
+            }
+            .cache_key(&surfs)
+        );
+    }
+
+    #[test]
+    fn build_cached_prunes_other_versions() {
+        let attrs = [0, 1];
+        let surfs = surfaces(&attrs);
+        let dir = scratch_dir("prune");
+        let stale = dir.join("tree_0123456789abcdef.bin");
+        let unrelated = dir.join("notes.txt");
+        fs::write(&stale, b"stale").expect("Failed to write file.");
+        fs::write(&unrelated, b"notes").expect("Failed to write file.");
+
+        settings()
+            .build_cached(&surfs, &dir)
+            .expect("Failed to build tree.");
+        let cached = dir.join(format!(
+            "{FILE_PREFIX}v{FORMAT_VERSION}_{:016x}.bin",
+            settings().cache_key(&surfs)
+        ));
+
+        assert!(cached.exists());
+        assert!(!stale.exists());
+        assert!(unrelated.exists());
+        assert!(settings().build_cached(&surfs, &dir).is_ok());
+
+        fs::remove_dir_all(dir).expect("Failed to remove scratch directory.");
+    }
+
+    #[test]
+    fn custom_shapes_are_cached_and_partitioned() {
+        let attr = 0;
+        let balls = (0..27)
+            .map(|n| {
+                let centre =
+                    Point3::new(f64::from(n % 3), f64::from((n / 3) % 3), f64::from(n / 9));
+                Ball(Sphere::new(centre, 0.1 + 0.01 * f64::from(n)))
+            })
+            .collect();
+        let surfs = vec![Surface::from_shapes(balls, &attr)];
+        let dir = scratch_dir("custom");
+
+        let built = settings()
+            .build_cached(&surfs, &dir)
+            .expect("Failed to build tree.");
+        let loaded = settings()
+            .build_cached(&surfs, &dir)
+            .expect("Failed to load tree.");
+        let bvh = Partition::Bvh(BvhBuilder::default().build(&surfs));
+
+        for n in 0..100 {
+            let theta = f64::from(n) * 0.1;
+            let dir = Unit::new_normalize(Vector3::new(theta.cos(), theta.sin(), 0.3));
+            let ray = Ray::new(Point3::new(1.1, 0.9, -0.5), dir);
+            let expected = surfs[0]
+                .shapes
+                .iter()
+                .filter_map(|ball| ball.dist(&ray))
+                .min_by(f64::total_cmp);
+
+            let hits = [
+                built.scan(ray.clone(), 1.0e-9, 100.0),
+                loaded.scan(ray.clone(), 1.0e-9, 100.0),
+                bvh.scan(ray, 1.0e-9, 100.0),
+            ];
+            for hit in hits {
+                match (hit.map(|hit| hit.dist), expected) {
+                    (None, None) => {}
+                    (Some(a), Some(b)) => assert!((a - b).abs() < 1.0e-9),
+                    (a, b) => panic!("Mismatch: {a:?} {b:?}"),
+                }
+            }
+        }
+
+        fs::remove_dir_all(dir).expect("Failed to remove scratch directory.");
+    }
+}
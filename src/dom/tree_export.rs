@@ -0,0 +1,196 @@
+//! Oct-tree cell visualisation.
+
+use std::{collections::HashMap, path::Path};
+
+use crate::{
+    dom::{Tree, TreeCell},
+    geom::{Mesh, Triangle},
+    parse::{ply, wavefront, PlyFormat},
+    Error,
+};
+
+/// Geometry used to draw each exported cell.
+#[derive(Clone, Copy)]
+#[non_exhaustive]
+pub enum CellStyle {
+    /// Closed box surface.
+    Box,
+    /// Edge outline, with edges of the given fraction of the cell's smallest width.
+    Wireframe(f64),
+}
+
+/// Selection of the leaf cells to export.
+#[derive(Clone, Copy, Default)]
+pub struct LeafFilter {
+    /// Skip leaves which contain no triangles.
+    pub populated_only: bool,
+    /// Only include leaves at this depth.
+    pub depth: Option<usize>,
+}
+
+impl<'a, T, S> Tree<'a, T, S> {
+    /// Construct a mesh of the selected leaf cells.
+    /// Returns `None` if no leaves are selected.
+    #[inline]
+    #[must_use]
+    pub fn leaf_mesh(&self, style: CellStyle, filter: LeafFilter) -> Option<Mesh> {
+        let mut tris = Vec::new();
+        self.root.gather_leaf_tris(0, style, filter, &mut tris);
+
+        if tris.is_empty() {
+            None
+        } else {
+            Some(Mesh::new(tris))
+        }
+    }
+
+    /// Save the selected leaf cells as a mesh file.
+    /// The format is chosen by the file extension: `obj` or `ply`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the extension is not supported, if no leaves are selected,
+    /// or if the file can not be written.
+    #[inline]
+    pub fn save_leaves(
+        &self,
+        style: CellStyle,
+        filter: LeafFilter,
+        path: &Path,
+    ) -> Result<(), Error> {
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_lowercase);
+        if !matches!(ext.as_deref(), Some("obj" | "ply")) {
+            return Err(Error::InvalidParameter(format!(
+                "Unsupported cell mesh file extension: {}",
+                path.display()
+            )));
+        }
+
+        let mesh = self
+            .leaf_mesh(style, filter)
+            .ok_or_else(|| Error::InvalidParameter("No tree leaves selected.".to_owned()))?;
+
+        if ext.as_deref() == Some("obj") {
+            wavefront::save(&mesh, path)
+        } else {
+            ply::save(&mesh, &HashMap::new(), PlyFormat::BinaryLittleEndian, path)
+        }
+    }
+}
+
+impl<'a, T, S> TreeCell<'a, T, S> {
+    /// Accumulate the triangles of the selected leaves of this cell, at the given depth, and its descendants.
+    #[inline]
+    fn gather_leaf_tris(
+        &self,
+        depth: usize,
+        style: CellStyle,
+        filter: LeafFilter,
+        tris: &mut Vec<Triangle>,
+    ) {
+        match *self {
+            Self::Branch { ref children, .. } => {
+                if filter.depth.map_or(true, |max| depth < max) {
+                    for child in children.iter() {
+                        child.gather_leaf_tris(depth + 1, style, filter, tris);
+                    }
+                }
+            }
+            Self::Leaf {
+                ref boundary,
+                shapes: ref leaf_shapes,
+            } => {
+                if (filter.populated_only && leaf_shapes.is_empty())
+                    || filter.depth.map_or(false, |d| d != depth)
+                {
+                    return;
+                }
+
+                match style {
+                    CellStyle::Box => tris.extend(boundary.tris()),
+                    CellStyle::Wireframe(frac) => {
+                        debug_assert!(frac > 0.0);
+                        tris.extend(boundary.wireframe_tris(frac * boundary.widths().min()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dom::{Surface, TreeBuilder},
+        geom::{Plane, Shape, Sphere},
+    };
+    use nalgebra::{Point3, Vector3};
+
+    /// Construct surfaces with a sphere in opposite octants, and a plane.
+    fn surfaces(attr: &i32) -> Vec<Surface<'_, i32>> {
+        vec![Surface::from_shapes(
+            vec![
+                Shape::Sphere(Sphere::new(Point3::new(-2.0, -2.0, -2.0), 0.5)),
+                Shape::Sphere(Sphere::new(Point3::new(2.0, 2.0, 2.0), 0.5)),
+                Shape::Plane(Plane::new(Point3::new(0.0, 0.0, -10.0), Vector3::z_axis())),
+            ],
+            attr,
+        )]
+    }
+
+    /// Construct the tree settings used by the tests, splitting the root once.
+    fn settings() -> TreeBuilder {
+        TreeBuilder {
+            tar_tris: 1,
+            max_depth: 1,
+            padding: 0.01,
+            par_depth: None,
+            split_bins: None,
+            sah: None,
+        }
+    }
+
+    #[test]
+    fn box_leaves_are_drawn_with_twelve_triangles() {
+        let attr = 0;
+        let surfs = surfaces(&attr);
+        let tree = settings().build(&surfs).expect("Failed to build tree.");
+
+        let all = tree
+            .leaf_mesh(CellStyle::Box, LeafFilter::default())
+            .expect("Missing leaves.");
+        assert_eq!(all.tris.len(), 8 * 12);
+
+        let populated = LeafFilter {
+            populated_only: true,
+            depth: None,
+        };
+        let mesh = tree
+            .leaf_mesh(CellStyle::Box, populated)
+            .expect("Missing leaves.");
+        assert_eq!(mesh.tris.len(), 2 * 12);
+
+        let root_only = LeafFilter {
+            populated_only: false,
+            depth: Some(0),
+        };
+        assert!(tree.leaf_mesh(CellStyle::Box, root_only).is_none());
+    }
+
+    #[test]
+    fn unsupported_extensions_are_rejected() {
+        let attr = 0;
+        let surfs = surfaces(&attr);
+        let tree = settings().build(&surfs).expect("Failed to build tree.");
+
+        let path = Path::new("leaves.stl");
+        assert!(matches!(
+            tree.save_leaves(CellStyle::Box, LeafFilter::default(), path),
+            Err(Error::InvalidParameter(_))
+        ));
+    }
+}
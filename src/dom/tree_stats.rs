@@ -0,0 +1,172 @@
+//! Oct-tree statistics.
+
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::dom::{Tree, TreeCell};
+
+/// Summary of the structure of a Tree.
+#[derive(Serialize)]
+pub struct TreeStats {
+    /// Total number of cells.
+    pub num_cells: usize,
+    /// Number of branching cells.
+    pub num_branches: usize,
+    /// Number of leaf cells.
+    pub num_leaves: usize,
+    /// Number of leaf cells without any shapes.
+    pub num_empty_leaves: usize,
+    /// Fraction of leaf cells without any shapes.
+    pub empty_leaf_fraction: f64,
+    /// Depth of the deepest leaf.
+    pub max_depth: usize,
+    /// Number of leaves at each depth.
+    pub leaf_depths: Vec<usize>,
+    /// Number of distinct bounded shapes.
+    pub num_tris: usize,
+    /// Number of unbounded shapes, tested by every scan.
+    pub num_unbounded: usize,
+    /// Total number of shape references held by the leaves.
+    pub num_tri_refs: usize,
+    /// Average number of leaves referencing each shape.
+    pub duplication_factor: f64,
+    /// Smallest number of shapes in a leaf.
+    pub min_leaf_tris: usize,
+    /// Largest number of shapes in a leaf.
+    pub max_leaf_tris: usize,
+    /// Average number of shapes in a leaf.
+    pub mean_leaf_tris: f64,
+    /// Number of leaves holding each number of shapes.
+    pub leaf_tris: Vec<usize>,
+}
+
+impl<'a, T, S> Tree<'a, T, S> {
+    /// Gather statistics describing the structure of the Tree.
+    #[inline]
+    #[must_use]
+    pub fn stats(&self) -> TreeStats {
+        let mut stats = TreeStats {
+            num_cells: 0,
+            num_branches: 0,
+            num_leaves: 0,
+            num_empty_leaves: 0,
+            empty_leaf_fraction: 0.0,
+            max_depth: 0,
+            leaf_depths: Vec::new(),
+            num_tris: 0,
+            num_unbounded: self.unbounded.len(),
+            num_tri_refs: 0,
+            duplication_factor: 0.0,
+            min_leaf_tris: usize::MAX,
+            max_leaf_tris: 0,
+            mean_leaf_tris: 0.0,
+            leaf_tris: Vec::new(),
+        };
+        let mut unique = HashSet::new();
+
+        self.root.gather_stats(0, &mut stats, &mut unique);
+
+        stats.num_cells = stats.num_branches + stats.num_leaves;
+        stats.empty_leaf_fraction = stats.num_empty_leaves as f64 / stats.num_leaves as f64;
+        stats.num_tris = unique.len();
+        if stats.num_tris > 0 {
+            stats.duplication_factor = stats.num_tri_refs as f64 / stats.num_tris as f64;
+        }
+        stats.mean_leaf_tris = stats.num_tri_refs as f64 / stats.num_leaves as f64;
+
+        stats
+    }
+}
+
+impl<'a, T, S> TreeCell<'a, T, S> {
+    /// Accumulate the statistics of this cell, at the given depth, and its descendants.
+    #[inline]
+    fn gather_stats(&self, depth: usize, stats: &mut TreeStats, unique: &mut HashSet<*const S>) {
+        match *self {
+            Self::Branch { ref children, .. } => {
+                stats.num_branches += 1;
+                for child in children.iter() {
+                    child.gather_stats(depth + 1, stats, unique);
+                }
+            }
+            Self::Leaf { ref shapes, .. } => {
+                stats.num_leaves += 1;
+                if shapes.is_empty() {
+                    stats.num_empty_leaves += 1;
+                }
+
+                stats.max_depth = stats.max_depth.max(depth);
+                if stats.leaf_depths.len() <= depth {
+                    stats.leaf_depths.resize(depth + 1, 0);
+                }
+                stats.leaf_depths[depth] += 1;
+
+                stats.num_tri_refs += shapes.len();
+                stats.min_leaf_tris = stats.min_leaf_tris.min(shapes.len());
+                stats.max_leaf_tris = stats.max_leaf_tris.max(shapes.len());
+                if stats.leaf_tris.len() <= shapes.len() {
+                    stats.leaf_tris.resize(shapes.len() + 1, 0);
+                }
+                stats.leaf_tris[shapes.len()] += 1;
+
+                unique.extend(shapes.iter().map(|&(shape, _)| shape as *const S));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        dom::{Surface, TreeBuilder},
+        geom::{Plane, Shape, Sphere},
+    };
+    use nalgebra::{Point3, Vector3};
+
+    /// Construct surfaces with a sphere in opposite octants, and a plane.
+    fn surfaces(attr: &i32) -> Vec<Surface<'_, i32>> {
+        vec![Surface::from_shapes(
+            vec![
+                Shape::Sphere(Sphere::new(Point3::new(-2.0, -2.0, -2.0), 0.5)),
+                Shape::Sphere(Sphere::new(Point3::new(2.0, 2.0, 2.0), 0.5)),
+                Shape::Plane(Plane::new(Point3::new(0.0, 0.0, -10.0), Vector3::z_axis())),
+            ],
+            attr,
+        )]
+    }
+
+    /// Construct the tree settings used by the tests, splitting the root once.
+    fn settings() -> TreeBuilder {
+        TreeBuilder {
+            tar_tris: 1,
+            max_depth: 1,
+            padding: 0.01,
+            par_depth: None,
+            split_bins: None,
+            sah: None,
+        }
+    }
+
+    #[test]
+    fn stats_count_cells_and_shapes() {
+        let attr = 0;
+        let surfs = surfaces(&attr);
+        let tree = settings().build(&surfs).expect("Failed to build tree.");
+
+        let stats = tree.stats();
+        assert_eq!(stats.num_cells, 9);
+        assert_eq!(stats.num_branches, 1);
+        assert_eq!(stats.num_leaves, 8);
+        assert_eq!(stats.num_empty_leaves, 6);
+        assert!((stats.empty_leaf_fraction - 0.75).abs() < 1.0e-12);
+        assert_eq!(stats.max_depth, 1);
+        assert_eq!(stats.leaf_depths, vec![0, 8]);
+        assert_eq!(stats.num_tris, 2);
+        assert_eq!(stats.num_unbounded, 1);
+        assert_eq!(stats.num_tri_refs, 2);
+        assert!((stats.duplication_factor - 1.0).abs() < 1.0e-12);
+        assert_eq!(stats.min_leaf_tris, 0);
+        assert_eq!(stats.max_leaf_tris, 1);
+        assert_eq!(stats.leaf_tris, vec![6, 2]);
+    }
+}
@@ -0,0 +1,119 @@
+//! Error handling.
+
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use std::{
+    error::Error as StdError,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Crate-wide error enumeration.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Input/output failure, file path and cause.
+    Io(PathBuf, io::Error),
+    /// JSON5 parsing failure, optional file path and cause.
+    Json5(Option<PathBuf>, json5::Error),
+    /// JSON serialisation failure.
+    Json(serde_json::Error),
+    /// Wavefront parsing failure, optional file path, line number and description.
+    Wavefront(Option<PathBuf>, usize, String),
+    /// STL parsing failure, optional file path and description.
+    Stl(Option<PathBuf>, String),
+    /// PLY parsing failure, optional file path and description.
+    Ply(Option<PathBuf>, String),
+    /// glTF parsing failure, optional file path and description.
+    Gltf(Option<PathBuf>, String),
+    /// Invalid or incompatible cache file, file path and description.
+    Cache(PathBuf, String),
+    /// PNG encoding failure, file path and cause.
+    Png(PathBuf, png::EncodingError),
+    /// Missing resource, resource kind and key.
+    MissingKey(&'static str, String),
+    /// Invalid parameter value, description.
+    InvalidParameter(String),
+}
+
+impl Error {
+    /// Attach the path of the file being parsed, if not already known.
+    #[inline]
+    #[must_use]
+    pub fn with_path(self, path: &Path) -> Self {
+        match self {
+            Self::Json5(None, err) => Self::Json5(Some(path.to_path_buf()), err),
+            Self::Wavefront(None, line, msg) => {
+                Self::Wavefront(Some(path.to_path_buf()), line, msg)
+            }
+            Self::Stl(None, msg) => Self::Stl(Some(path.to_path_buf()), msg),
+            Self::Ply(None, msg) => Self::Ply(Some(path.to_path_buf()), msg),
+            Self::Gltf(None, msg) => Self::Gltf(Some(path.to_path_buf()), msg),
+            err => err,
+        }
+    }
+}
+
+impl Display for Error {
+    #[inline]
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        match *self {
+            Self::Io(ref path, ref err) => {
+                write!(fmt, "IO failure at {}: {err}", path.display())
+            }
+            Self::Json5(Some(ref path), ref err) => {
+                write!(fmt, "Failed to parse JSON file {}: {err}", path.display())
+            }
+            Self::Json5(None, ref err) => write!(fmt, "Failed to parse JSON: {err}"),
+            Self::Json(ref err) => write!(fmt, "Failed to write JSON: {err}"),
+            Self::Wavefront(Some(ref path), line, ref msg) => {
+                write!(
+                    fmt,
+                    "Failed to parse wavefront file {} (line {line}): {msg}",
+                    path.display()
+                )
+            }
+            Self::Wavefront(None, line, ref msg) => {
+                write!(fmt, "Failed to parse wavefront (line {line}): {msg}")
+            }
+            Self::Stl(Some(ref path), ref msg) => {
+                write!(fmt, "Failed to parse STL file {}: {msg}", path.display())
+            }
+            Self::Stl(None, ref msg) => write!(fmt, "Failed to parse STL: {msg}"),
+            Self::Ply(Some(ref path), ref msg) => {
+                write!(fmt, "Failed to parse PLY file {}: {msg}", path.display())
+            }
+            Self::Ply(None, ref msg) => write!(fmt, "Failed to parse PLY: {msg}"),
+            Self::Gltf(Some(ref path), ref msg) => {
+                write!(fmt, "Failed to parse glTF file {}: {msg}", path.display())
+            }
+            Self::Gltf(None, ref msg) => write!(fmt, "Failed to parse glTF: {msg}"),
+            Self::Cache(ref path, ref msg) => {
+                write!(fmt, "Invalid cache file {}: {msg}", path.display())
+            }
+            Self::Png(ref path, ref err) => {
+                write!(fmt, "Failed to write PNG file {}: {err}", path.display())
+            }
+            Self::MissingKey(kind, ref key) => write!(fmt, "Missing {kind} resource: {key}"),
+            Self::InvalidParameter(ref msg) => write!(fmt, "Invalid parameter: {msg}"),
+        }
+    }
+}
+
+impl StdError for Error {
+    #[inline]
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            Self::Io(_, ref err) => Some(err),
+            Self::Json5(_, ref err) => Some(err),
+            Self::Json(ref err) => Some(err),
+            Self::Png(_, ref err) => Some(err),
+            Self::Wavefront(..)
+            | Self::Stl(..)
+            | Self::Ply(..)
+            | Self::Gltf(..)
+            | Self::Cache(..)
+            | Self::MissingKey(..)
+            | Self::InvalidParameter(..) => None,
+        }
+    }
+}
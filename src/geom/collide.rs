@@ -0,0 +1,15 @@
+//! Bounding box intersection trait.
+
+use crate::geom::Cube;
+
+/// Geometry which may be sorted into axis-aligned cells.
+pub trait Collide {
+    /// Calculate the bounding box.
+    /// Unbounded geometry returns `None`, and is treated as spanning every cell.
+    #[must_use]
+    fn bounds(&self) -> Option<Cube>;
+
+    /// Check for an intersection with a given bounding box.
+    #[must_use]
+    fn collides(&self, cube: &Cube) -> bool;
+}
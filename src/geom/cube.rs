@@ -1,12 +1,15 @@
 //! Axis-aligned cuboid.
 
-use core::cmp::Ordering;
 use nalgebra::{Point3, Unit, Vector3};
+use serde::{Deserialize, Serialize};
 
-use crate::rt::{Ray, Side};
+use crate::{
+    geom::{Collide, Trace, Triangle},
+    rt::{Ray, Side},
+};
 
 /// Cuboid oriented along the Cartesian axes.
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Cube {
     /// Minimum bound.
     pub mins: Point3<f64>,
@@ -94,64 +97,144 @@ impl Cube {
         self.maxs += delta;
     }
 
-    /// Check for an intersection with another Cube.
+    /// Construct the smallest box enclosing both this and another Cube.
     #[inline]
     #[must_use]
-    pub fn collides(&self, cube: &Self) -> bool {
-        self.mins <= cube.maxs && self.maxs >= cube.mins
+    pub fn union(&self, cube: &Self) -> Self {
+        Self {
+            mins: self.mins.inf(&cube.mins),
+            maxs: self.maxs.sup(&cube.maxs),
+        }
+    }
+
+    /// Construct the outward-facing triangles of the boundary surface.
+    #[inline]
+    #[must_use]
+    pub fn tris(&self) -> Vec<Triangle> {
+        let mut tris = Vec::with_capacity(12);
+        for a in 0..3 {
+            let (b, c) = ((a + 1) % 3, (a + 2) % 3);
+            for upper in [false, true] {
+                let mut norm = Vector3::zeros();
+                norm[a] = if upper { 1.0 } else { -1.0 };
+                let norm = Unit::new_unchecked(norm);
+
+                let corner = |i: usize, j: usize| {
+                    let mut p = self.mins;
+                    if upper {
+                        p[a] = self.maxs[a];
+                    }
+                    if i == 1 {
+                        p[b] = self.maxs[b];
+                    }
+                    if j == 1 {
+                        p[c] = self.maxs[c];
+                    }
+                    p
+                };
+                let (p00, p10, p11, p01) = (corner(0, 0), corner(1, 0), corner(1, 1), corner(0, 1));
+
+                if upper {
+                    tris.push(Triangle::new([p00, p10, p11], [norm; 3]));
+                    tris.push(Triangle::new([p00, p11, p01], [norm; 3]));
+                } else {
+                    tris.push(Triangle::new([p00, p11, p10], [norm; 3]));
+                    tris.push(Triangle::new([p00, p01, p11], [norm; 3]));
+                }
+            }
+        }
+        tris
+    }
+
+    /// Construct the triangles of a wireframe outline.
+    /// Each edge is drawn as a thin box of the given width, centred on the edge.
+    #[inline]
+    #[must_use]
+    pub fn wireframe_tris(&self, width: f64) -> Vec<Triangle> {
+        debug_assert!(width > 0.0);
+
+        let half = width * 0.5;
+        let mut tris = Vec::with_capacity(12 * 12);
+        for a in 0..3 {
+            let (b, c) = ((a + 1) % 3, (a + 2) % 3);
+            for (pb, pc) in [
+                (self.mins[b], self.mins[c]),
+                (self.maxs[b], self.mins[c]),
+                (self.mins[b], self.maxs[c]),
+                (self.maxs[b], self.maxs[c]),
+            ] {
+                let mut edge = Self {
+                    mins: self.mins,
+                    maxs: self.maxs,
+                };
+                edge.mins[a] -= half;
+                edge.maxs[a] += half;
+                edge.mins[b] = pb - half;
+                edge.maxs[b] = pb + half;
+                edge.mins[c] = pc - half;
+                edge.maxs[c] = pc + half;
+                tris.extend(edge.tris());
+            }
+        }
+        tris
     }
 
     /// Determine the distances from a given Ray to the axial planes of a Cube's minimum and maximal bounds.
     #[inline]
     #[must_use]
     fn intersections(&self, ray: &Ray) -> (f64, f64) {
-        let t_0: Vec<_> = self
+        let mut t_min = f64::NEG_INFINITY;
+        let mut t_max = f64::INFINITY;
+
+        for ((min, max), (p, d)) in self
             .mins
             .iter()
+            .zip(self.maxs.iter())
             .zip(ray.pos.iter().zip(ray.dir.iter()))
-            .map(|(m, (p, d))| (m - p) / d)
-            .collect();
+        {
+            let t_0 = (min - p) / d;
+            let t_1 = (max - p) / d;
 
-        let t_1: Vec<_> = self
-            .maxs
-            .iter()
-            .zip(ray.pos.iter().zip(ray.dir.iter()))
-            .map(|(m, (p, d))| (m - p) / d)
-            .collect();
+            t_min = t_min.max(t_0.min(t_1));
+            t_max = t_max.min(t_0.max(t_1));
+        }
 
-        let t_min = t_0
-            .iter()
-            .zip(t_1.iter())
-            .map(|(a, b)| a.min(*b))
-            .max_by(|a, b| {
-                if a < b {
-                    Ordering::Less
-                } else {
-                    Ordering::Greater
-                }
-            })
-            .expect("Failed to perform Ray-Cube intersection.");
+        (t_min, t_max)
+    }
 
-        let t_max = t_0
-            .iter()
-            .zip(t_1.iter())
-            .map(|(a, b)| a.max(*b))
-            .min_by(|a, b| {
-                if a < b {
-                    Ordering::Less
-                } else {
-                    Ordering::Greater
-                }
-            })
-            .expect("Failed to perform Ray-Cube intersection.");
+    /// Determine the entry and exit distances of a Ray-Cube intersection.
+    /// The entry distance is zero if the Ray starts within the Cube.
+    #[inline]
+    #[must_use]
+    pub fn dist_range(&self, ray: &Ray) -> Option<(f64, f64)> {
+        let (t_min, t_max) = self.intersections(ray);
 
-        (t_min, t_max)
+        if t_max <= 0.0 || t_min > t_max {
+            return None;
+        }
+
+        Some((t_min.max(0.0), t_max))
+    }
+}
+
+impl Collide for Cube {
+    /// A Cube is its own bounding box.
+    #[inline]
+    fn bounds(&self) -> Option<Cube> {
+        Some(self.clone())
+    }
+
+    /// Check for an intersection with another Cube.
+    #[inline]
+    fn collides(&self, cube: &Cube) -> bool {
+        self.mins <= cube.maxs && self.maxs >= cube.mins
     }
+}
 
+impl Trace for Cube {
     /// Determine if a Ray-Cube intersection occurs.
     #[inline]
-    #[must_use]
-    pub fn hit(&self, ray: &Ray) -> bool {
+    fn hit(&self, ray: &Ray) -> bool {
         let (t_min, t_max) = self.intersections(ray);
 
         !(t_max <= 0.0 || t_min > t_max)
@@ -159,8 +242,7 @@ impl Cube {
 
     /// Determine the distance to a Ray-Cube intersection.
     #[inline]
-    #[must_use]
-    pub fn dist(&self, ray: &Ray) -> Option<f64> {
+    fn dist(&self, ray: &Ray) -> Option<f64> {
         let (t_min, t_max) = self.intersections(ray);
 
         if t_max <= 0.0 || t_min > t_max {
@@ -176,8 +258,7 @@ impl Cube {
 
     /// Determine the distance and facing side of a Ray-Cube intersection.
     #[inline]
-    #[must_use]
-    pub fn dist_side(&self, ray: &Ray) -> Option<(f64, Side)> {
+    fn dist_side(&self, ray: &Ray) -> Option<(f64, Side)> {
         if let Some(dist) = self.dist(ray) {
             let hit = ray.pos + (dist * ray.dir.as_ref());
             let relative = hit - self.centre();
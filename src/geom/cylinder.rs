@@ -0,0 +1,215 @@
+//! Cylinder.
+
+use nalgebra::{Point3, Unit, Vector3};
+
+use crate::{
+    geom::{Collide, Cube, Trace},
+    rt::{Ray, Side},
+};
+
+/// Open cylindrical tube, without end caps.
+#[derive(Clone)]
+pub struct Cylinder {
+    /// Centre of the first end.
+    pub start: Point3<f64>,
+    /// Centre of the second end.
+    pub end: Point3<f64>,
+    /// Radius.
+    pub radius: f64,
+}
+
+impl Cylinder {
+    /// Construct a new instance.
+    #[inline]
+    #[must_use]
+    pub fn new(start: Point3<f64>, end: Point3<f64>, radius: f64) -> Self {
+        debug_assert!(start != end);
+        debug_assert!(radius > 0.0);
+
+        Self { start, end, radius }
+    }
+
+    /// Calculate the direction of the central axis.
+    #[inline]
+    #[must_use]
+    pub fn axis(&self) -> Unit<Vector3<f64>> {
+        Unit::new_normalize(self.end - self.start)
+    }
+
+    /// Calculate the length.
+    #[inline]
+    #[must_use]
+    pub fn length(&self) -> f64 {
+        nalgebra::distance(&self.start, &self.end)
+    }
+
+    /// Calculate the bounding box.
+    #[inline]
+    #[must_use]
+    pub fn boundary(&self) -> Cube {
+        let ext = self
+            .axis()
+            .map(|a| self.radius * a.mul_add(-a, 1.0).max(0.0).sqrt());
+
+        Cube {
+            mins: self.start.inf(&self.end) - ext,
+            maxs: self.start.sup(&self.end) + ext,
+        }
+    }
+
+    /// Determine the distance along a Ray's direction to the nearest intersection.
+    #[inline]
+    #[must_use]
+    fn intersection(&self, ray: &Ray) -> Option<f64> {
+        let axis = self.axis();
+        let len = self.length();
+
+        let rel_pos = ray.pos - self.start;
+        let dir_perp = ray.dir.as_ref() - (axis.as_ref() * ray.dir.dot(&axis));
+        let pos_perp = rel_pos - (axis.as_ref() * rel_pos.dot(&axis));
+
+        let a = dir_perp.norm_squared();
+        if a <= 0.0 {
+            return None;
+        }
+        let b = dir_perp.dot(&pos_perp);
+        let c = self.radius.mul_add(-self.radius, pos_perp.norm_squared());
+
+        let discriminant = b.mul_add(b, -(a * c));
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let root = discriminant.sqrt();
+        [(-b - root) / a, (-b + root) / a]
+            .into_iter()
+            .find(|&dist| {
+                let height = ray.dir.dot(&axis).mul_add(dist, rel_pos.dot(&axis));
+                dist > 0.0 && (0.0..=len).contains(&height)
+            })
+    }
+}
+
+impl Collide for Cylinder {
+    /// Calculate the bounding box.
+    #[inline]
+    fn bounds(&self) -> Option<Cube> {
+        Some(self.boundary())
+    }
+
+    /// Check for an intersection between the surface and a given bounding box.
+    /// The test is conservative, although boxes lying entirely inside the tube do not collide.
+    #[inline]
+    fn collides(&self, cube: &Cube) -> bool {
+        if !self.boundary().collides(cube) {
+            return false;
+        }
+
+        let axis = self.axis();
+        let r_sq = self.radius * self.radius;
+        (0..8).any(|index: usize| {
+            let corner = Point3::new(
+                if index & 1 == 0 {
+                    cube.mins.x
+                } else {
+                    cube.maxs.x
+                },
+                if index & 2 == 0 {
+                    cube.mins.y
+                } else {
+                    cube.maxs.y
+                },
+                if index & 4 == 0 {
+                    cube.mins.z
+                } else {
+                    cube.maxs.z
+                },
+            );
+            let rel_pos = corner - self.start;
+            (rel_pos - (axis.as_ref() * rel_pos.dot(&axis))).norm_squared() >= r_sq
+        })
+    }
+}
+
+impl Trace for Cylinder {
+    /// Determine if a Ray-Cylinder intersection occurs.
+    #[inline]
+    fn hit(&self, ray: &Ray) -> bool {
+        self.intersection(ray).is_some()
+    }
+
+    /// Determine the distance to a Ray-Cylinder intersection.
+    #[inline]
+    fn dist(&self, ray: &Ray) -> Option<f64> {
+        self.intersection(ray)
+    }
+
+    /// Determine the distance and facing side of a Ray-Cylinder intersection.
+    #[inline]
+    fn dist_side(&self, ray: &Ray) -> Option<(f64, Side)> {
+        self.intersection(ray).map(|dist| {
+            let axis = self.axis();
+            let rel_pos = ray.pos + (ray.dir.as_ref() * dist) - self.start;
+            let radial = rel_pos - (axis.as_ref() * rel_pos.dot(&axis));
+            (dist, Side::new(&ray.dir, Unit::new_normalize(radial)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rays_hit_the_tube_from_outside() {
+        let cyl = Cylinder::new(Point3::origin(), Point3::new(0.0, 0.0, 2.0), 1.0);
+
+        let ray = Ray::new(Point3::new(3.0, 0.0, 1.0), -Vector3::x_axis());
+        let (dist, side) = cyl.dist_side(&ray).expect("Missing hit.");
+        assert!((dist - 2.0).abs() < 1.0e-12);
+        assert!(!side.is_inside());
+        assert!((side.norm().x - 1.0).abs() < 1.0e-12);
+
+        let offset = Ray::new(Point3::new(3.0, 0.5, 1.0), -Vector3::x_axis());
+        let expected = 3.0 - 0.75_f64.sqrt();
+        assert!((cyl.dist(&offset).expect("Missing hit.") - expected).abs() < 1.0e-12);
+    }
+
+    #[test]
+    fn rays_miss_beyond_the_ends_or_along_the_axis() {
+        let cyl = Cylinder::new(Point3::origin(), Point3::new(0.0, 0.0, 2.0), 1.0);
+
+        assert!(!cyl.hit(&Ray::new(Point3::new(3.0, 0.0, 3.0), -Vector3::x_axis())));
+        assert!(!cyl.hit(&Ray::new(Point3::new(3.0, 1.5, 1.0), -Vector3::x_axis())));
+        assert!(!cyl.hit(&Ray::new(Point3::new(0.0, 0.0, -1.0), Vector3::z_axis())));
+    }
+
+    #[test]
+    fn rays_from_inside_hit_the_far_wall() {
+        let cyl = Cylinder::new(Point3::origin(), Point3::new(0.0, 0.0, 2.0), 1.0);
+
+        let ray = Ray::new(Point3::new(0.0, 0.0, 1.0), Vector3::x_axis());
+        let (dist, side) = cyl.dist_side(&ray).expect("Missing hit.");
+        assert!((dist - 1.0).abs() < 1.0e-12);
+        assert!(side.is_inside());
+
+        // Rays leaving through an open end miss.
+        let up = Ray::new(
+            Point3::new(0.0, 0.0, 1.0),
+            Unit::new_normalize(Vector3::new(0.1, 0.0, 1.0)),
+        );
+        assert!(!cyl.hit(&up));
+    }
+
+    #[test]
+    fn boxes_inside_the_tube_do_not_collide() {
+        let cyl = Cylinder::new(Point3::origin(), Point3::new(0.0, 0.0, 2.0), 1.0);
+
+        let inside = Cube::new(Point3::new(-0.5, -0.5, 0.5), Point3::new(0.5, 0.5, 1.5));
+        let crossing = Cube::new(Point3::new(0.5, -0.5, 0.5), Point3::new(1.5, 0.5, 1.5));
+        let outside = Cube::new(Point3::new(2.0, 2.0, 0.5), Point3::new(3.0, 3.0, 1.5));
+        assert!(!cyl.collides(&inside));
+        assert!(cyl.collides(&crossing));
+        assert!(!cyl.collides(&outside));
+    }
+}
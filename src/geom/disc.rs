@@ -0,0 +1,152 @@
+//! Disc.
+
+use nalgebra::{Point3, Unit, Vector3};
+
+use crate::{
+    geom::{Collide, Cube, Plane, Trace},
+    rt::{Ray, Side},
+};
+
+/// Flat circular surface.
+#[derive(Clone)]
+pub struct Disc {
+    /// Centre position.
+    pub centre: Point3<f64>,
+    /// Surface normal.
+    pub norm: Unit<Vector3<f64>>,
+    /// Radius.
+    pub radius: f64,
+}
+
+impl Disc {
+    /// Construct a new instance.
+    #[inline]
+    #[must_use]
+    pub fn new(centre: Point3<f64>, norm: Unit<Vector3<f64>>, radius: f64) -> Self {
+        debug_assert!(radius > 0.0);
+
+        Self {
+            centre,
+            norm,
+            radius,
+        }
+    }
+
+    /// Calculate the bounding box.
+    /// Discs lying perpendicular to an axis have no width along it.
+    #[inline]
+    #[must_use]
+    pub fn boundary(&self) -> Cube {
+        let ext = self
+            .norm
+            .map(|n| self.radius * n.mul_add(-n, 1.0).max(0.0).sqrt());
+
+        Cube {
+            mins: self.centre - ext,
+            maxs: self.centre + ext,
+        }
+    }
+
+    /// Determine the distance along a Ray's direction to the intersection.
+    #[inline]
+    #[must_use]
+    fn intersection(&self, ray: &Ray) -> Option<f64> {
+        let dist = Plane::new(self.centre, self.norm).dist(ray)?;
+        let pos = ray.pos + (ray.dir.as_ref() * dist);
+
+        ((pos - self.centre).norm_squared() <= self.radius * self.radius).then_some(dist)
+    }
+}
+
+impl Collide for Disc {
+    /// Calculate the bounding box.
+    #[inline]
+    fn bounds(&self) -> Option<Cube> {
+        Some(self.boundary())
+    }
+
+    /// Check for an intersection with a given bounding box.
+    /// The test is conservative, and may report collisions near the rim which do not occur.
+    #[inline]
+    fn collides(&self, cube: &Cube) -> bool {
+        if !Plane::new(self.centre, self.norm).collides(cube) {
+            return false;
+        }
+
+        let mut near_sq = 0.0;
+        for axis in 0..3 {
+            let c = self.centre[axis];
+            let near = (cube.mins[axis] - c).max(0.0) + (c - cube.maxs[axis]).max(0.0);
+            near_sq += near * near;
+        }
+
+        near_sq <= self.radius * self.radius
+    }
+}
+
+impl Trace for Disc {
+    /// Determine if a Ray-Disc intersection occurs.
+    #[inline]
+    fn hit(&self, ray: &Ray) -> bool {
+        self.intersection(ray).is_some()
+    }
+
+    /// Determine the distance to a Ray-Disc intersection.
+    #[inline]
+    fn dist(&self, ray: &Ray) -> Option<f64> {
+        self.intersection(ray)
+    }
+
+    /// Determine the distance and facing side of a Ray-Disc intersection.
+    #[inline]
+    fn dist_side(&self, ray: &Ray) -> Option<(f64, Side)> {
+        self.intersection(ray)
+            .map(|dist| (dist, Side::new(&ray.dir, self.norm)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rays_hit_within_the_radius() {
+        let disc = Disc::new(Point3::new(0.0, 0.0, 1.0), Vector3::z_axis(), 1.0);
+
+        let down = Ray::new(Point3::new(0.5, 0.0, 3.0), -Vector3::z_axis());
+        let (dist, side) = disc.dist_side(&down).expect("Missing hit.");
+        assert!((dist - 2.0).abs() < 1.0e-12);
+        assert!(!side.is_inside());
+
+        let up = Ray::new(Point3::new(0.5, 0.0, 0.0), Vector3::z_axis());
+        let (dist, side) = disc.dist_side(&up).expect("Missing hit.");
+        assert!((dist - 1.0).abs() < 1.0e-12);
+        assert!(side.is_inside());
+    }
+
+    #[test]
+    fn rays_miss_beyond_the_radius() {
+        let disc = Disc::new(Point3::new(0.0, 0.0, 1.0), Vector3::z_axis(), 1.0);
+
+        assert!(!disc.hit(&Ray::new(Point3::new(1.5, 0.0, 3.0), -Vector3::z_axis())));
+        assert!(!disc.hit(&Ray::new(Point3::new(0.5, 0.0, 3.0), Vector3::z_axis())));
+        assert!(!disc.hit(&Ray::new(Point3::new(0.0, 0.0, 1.0), Vector3::x_axis())));
+    }
+
+    #[test]
+    fn boundary_is_flat_along_the_normal() {
+        let disc = Disc::new(Point3::new(0.0, 0.0, 1.0), Vector3::z_axis(), 1.0);
+
+        let boundary = disc.boundary();
+        assert_eq!(boundary.mins, Point3::new(-1.0, -1.0, 1.0));
+        assert_eq!(boundary.maxs, Point3::new(1.0, 1.0, 1.0));
+        assert!(disc.collides(&Cube::new(
+            Point3::new(0.5, 0.5, 0.5),
+            Point3::new(1.5, 1.5, 1.5)
+        )));
+        assert!(!disc.collides(&Cube::new(
+            Point3::new(2.0, 2.0, 0.5),
+            Point3::new(3.0, 3.0, 1.5)
+        )));
+    }
+}
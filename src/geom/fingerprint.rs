@@ -0,0 +1,9 @@
+//! Geometry fingerprint trait.
+
+/// Geometry which can be summarised as a sequence of values, such as to detect changes between runs.
+pub trait Fingerprint {
+    /// Feed the type and geometry into the given writer.
+    /// The values should be stable between builds and platforms,
+    /// and change whenever the geometry does.
+    fn fingerprint(&self, write: &mut dyn FnMut(u64));
+}
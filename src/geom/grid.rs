@@ -40,6 +40,7 @@ impl Grid {
     }
 
     /// Determine the voxel index of a given point.
+    /// Points lying on the maximum faces of the grid belong to the outermost voxels.
     #[inline]
     #[must_use]
     pub fn voxel_index(&self, point: &Point3<f64>) -> Option<[usize; 3]> {
@@ -51,7 +52,11 @@ impl Grid {
         let y = ((point.y - self.boundary.mins.y) * self.voxel_inv_size.y) as usize;
         let z = ((point.z - self.boundary.mins.z) * self.voxel_inv_size.z) as usize;
 
-        Some([x, y, z])
+        Some([
+            x.min(self.num_voxels[0] - 1),
+            y.min(self.num_voxels[1] - 1),
+            z.min(self.num_voxels[2] - 1),
+        ])
     }
 
     /// Generate voxel boundary of a given voxel index.
@@ -1,11 +1,13 @@
 //! Triangle-mesh.
 
 use itertools::izip;
+use nalgebra::Affine3;
 use ndarray::parallel::prelude::{IntoParallelRefIterator, ParallelIterator};
 
 use crate::{
-    geom::{Cube, Triangle},
+    geom::{Collide, Cube, Trace, Triangle},
     rt::{Ray, Side},
+    Error,
 };
 
 /// Mesh of two-dimensional triangles embedded in space as a three-dimensional mesh.
@@ -24,10 +26,37 @@ impl Mesh {
     pub fn new(tris: Vec<Triangle>) -> Self {
         debug_assert!(!tris.is_empty());
 
+        let boundary = Self::init_boundary(&tris);
+
+        Self { boundary, tris }
+    }
+
+    /// Apply an affine transformation to each of the triangles, and recalculate the boundary.
+    /// Similarity and isometry transformations may be converted using `nalgebra::convert`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transformation is not invertible, leaving the mesh unchanged.
+    #[inline]
+    pub fn transform(&mut self, trans: &Affine3<f64>) -> Result<(), Error> {
+        // Every triangle shares the transformation, so only the first can fail.
+        for tri in &mut self.tris {
+            tri.transform(trans)?;
+        }
+
+        self.boundary = Self::init_boundary(&self.tris);
+
+        Ok(())
+    }
+
+    /// Calculate the padded bounding box of the given triangles.
+    #[inline]
+    #[must_use]
+    fn init_boundary(tris: &[Triangle]) -> Cube {
         // Calculate bounding box.
         let mut mins = tris[0].centre();
         let mut maxs = mins;
-        for tri in &tris {
+        for tri in tris {
             for vert in tri.verts {
                 for (a, (min, max)) in izip!(vert.iter(), izip!(mins.iter_mut(), maxs.iter_mut())) {
                     if *min > *a {
@@ -38,27 +67,44 @@ impl Mesh {
                 }
             }
         }
+
+        // Pad any flat dimensions, such as those of axis-aligned planes.
+        let pad = (maxs - mins).max() * 0.005;
+        for (min, max) in izip!(mins.iter_mut(), maxs.iter_mut()) {
+            if *min >= *max {
+                *min -= pad;
+                *max += pad;
+            }
+        }
         let mut boundary = Cube::new(mins, maxs);
         boundary.expand(0.01); // TODO: Consider what value is best here.
 
-        Self { boundary, tris }
+        boundary
+    }
+}
+
+impl Collide for Mesh {
+    /// Copy the bounding box of the Mesh.
+    #[inline]
+    fn bounds(&self) -> Option<Cube> {
+        Some(self.boundary.clone())
     }
 
     /// Check for an intersection with a given bounding box.
     #[inline]
-    #[must_use]
-    pub fn collides(&self, cube: &Cube) -> bool {
+    fn collides(&self, cube: &Cube) -> bool {
         if !self.boundary.collides(cube) {
             return false;
         }
 
         self.tris.par_iter().any(|tri| tri.collides(cube))
     }
+}
 
+impl Trace for Mesh {
     /// Determine if a Ray-Mesh intersection occurs.
     #[inline]
-    #[must_use]
-    pub fn hit(&self, ray: &Ray) -> bool {
+    fn hit(&self, ray: &Ray) -> bool {
         if !self.boundary.hit(ray) {
             return false;
         }
@@ -68,8 +114,7 @@ impl Mesh {
 
     /// Determine the distance to a Ray-Mesh intersection.
     #[inline]
-    #[must_use]
-    pub fn dist(&self, ray: &Ray) -> Option<f64> {
+    fn dist(&self, ray: &Ray) -> Option<f64> {
         if !self.boundary.hit(ray) {
             return None;
         }
@@ -85,8 +130,7 @@ impl Mesh {
 
     /// Determine the distance and facing side of a Ray-Mesh intersection.
     #[inline]
-    #[must_use]
-    pub fn dist_side(&self, ray: &Ray) -> Option<(f64, Side)> {
+    fn dist_side(&self, ray: &Ray) -> Option<(f64, Side)> {
         if !self.boundary.hit(ray) {
             return None;
         }
@@ -100,3 +144,71 @@ impl Mesh {
             })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geom::TransformBuilder;
+    use nalgebra::{Matrix4, Point3, Vector3};
+
+    /// Construct a unit cube mesh at the origin.
+    fn cube() -> Mesh {
+        Mesh::new(Cube::new(Point3::origin(), Point3::new(1.0, 1.0, 1.0)).tris())
+    }
+
+    #[test]
+    fn transform_moves_boundary_and_keeps_normals_outward() {
+        for scale in [Vector3::new(2.0, 3.0, 0.5), Vector3::new(-2.0, 3.0, 0.5)] {
+            let mut mesh = cube();
+            let trans = TransformBuilder {
+                translation: Some(Vector3::new(10.0, 0.0, 0.0)),
+                scale: Some(scale),
+                ..TransformBuilder::default()
+            };
+            mesh.transform(&trans.build().expect("Failed to build transformation."))
+                .expect("Failed to transform mesh.");
+
+            let centre = mesh.boundary.centre();
+            let expected = Point3::new(10.0 + scale.x * 0.5, 1.5, 0.25);
+            assert!((centre - expected).norm() < 1.0e-12);
+            assert!((mesh.boundary.widths() - scale.abs() * 1.01).norm() < 1.0e-12);
+
+            for tri in &mesh.tris {
+                assert!(tri.plane_norm.dot(&(tri.centre() - centre)) > 0.0);
+                for norm in &tri.norms {
+                    assert!((norm.as_ref() - tri.plane_norm.as_ref()).norm() < 1.0e-12);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn boundary_encloses_every_vertex() {
+        // Vertices lie on both sides of the centre of the first triangle along every axis.
+        let mesh = Mesh::new(
+            Cube::new(Point3::new(-3.0, -1.0, -2.0), Point3::new(1.0, 5.0, 0.5))
+                .tris()
+                .into_iter()
+                .rev()
+                .collect(),
+        );
+
+        let half_widths = Vector3::new(2.0, 3.0, 1.25);
+        let pad = half_widths * 0.01;
+        assert!((mesh.boundary.mins - (Point3::new(-3.0, -1.0, -2.0) - pad)).norm() < 1.0e-12);
+        assert!((mesh.boundary.maxs - (Point3::new(1.0, 5.0, 0.5) + pad)).norm() < 1.0e-12);
+    }
+
+    #[test]
+    fn singular_transform_leaves_mesh_unchanged() {
+        let mut mesh = cube();
+        let flatten = Affine3::from_matrix_unchecked(Matrix4::new_nonuniform_scaling(
+            &Vector3::new(1.0, 1.0, 0.0),
+        ));
+
+        assert!(mesh.transform(&flatten).is_err());
+        for (tri, original) in mesh.tris.iter().zip(&cube().tris) {
+            assert_eq!(tri.verts, original.verts);
+        }
+    }
+}
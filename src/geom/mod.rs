@@ -1,8 +1,21 @@
 //! Spatial constructs.
 
+pub mod collide;
 pub mod cube;
+pub mod cylinder;
+pub mod disc;
+pub mod fingerprint;
 pub mod grid;
 pub mod mesh;
+pub mod plane;
+pub mod shape;
+pub mod shape_builder;
+pub mod sphere;
+pub mod trace;
+pub mod transform_builder;
 pub mod triangle;
 
-pub use self::{cube::*, grid::*, mesh::*, triangle::*};
+pub use self::{
+    collide::*, cube::*, cylinder::*, disc::*, fingerprint::*, grid::*, mesh::*, plane::*,
+    shape::*, shape_builder::*, sphere::*, trace::*, transform_builder::*, triangle::*,
+};
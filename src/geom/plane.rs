@@ -0,0 +1,127 @@
+//! Infinite plane.
+
+use nalgebra::{Point3, Unit, Vector3};
+
+use crate::{
+    geom::{Collide, Cube, Trace},
+    rt::{Ray, Side},
+};
+
+/// Unbounded flat surface.
+#[derive(Clone)]
+pub struct Plane {
+    /// Position lying on the plane.
+    pub pos: Point3<f64>,
+    /// Surface normal.
+    pub norm: Unit<Vector3<f64>>,
+}
+
+impl Plane {
+    /// Construct a new instance.
+    #[inline]
+    #[must_use]
+    pub const fn new(pos: Point3<f64>, norm: Unit<Vector3<f64>>) -> Self {
+        Self { pos, norm }
+    }
+
+    /// Determine the distance along a Ray's direction to the intersection.
+    #[inline]
+    #[must_use]
+    fn intersection(&self, ray: &Ray) -> Option<f64> {
+        let denom = self.norm.dot(&ray.dir);
+        if denom == 0.0 {
+            return None;
+        }
+
+        let dist = self.norm.dot(&(self.pos - ray.pos)) / denom;
+        (dist > 0.0).then_some(dist)
+    }
+}
+
+impl Collide for Plane {
+    /// Planes are unbounded.
+    #[inline]
+    fn bounds(&self) -> Option<Cube> {
+        None
+    }
+
+    /// Check for an intersection with a given bounding box.
+    #[inline]
+    fn collides(&self, cube: &Cube) -> bool {
+        let dist = self.norm.dot(&(cube.centre() - self.pos));
+        let reach = cube.half_widths().dot(&self.norm.map(f64::abs));
+
+        dist.abs() <= reach
+    }
+}
+
+impl Trace for Plane {
+    /// Determine if a Ray-Plane intersection occurs.
+    #[inline]
+    fn hit(&self, ray: &Ray) -> bool {
+        self.intersection(ray).is_some()
+    }
+
+    /// Determine the distance to a Ray-Plane intersection.
+    #[inline]
+    fn dist(&self, ray: &Ray) -> Option<f64> {
+        self.intersection(ray)
+    }
+
+    /// Determine the distance and facing side of a Ray-Plane intersection.
+    #[inline]
+    fn dist_side(&self, ray: &Ray) -> Option<(f64, Side)> {
+        self.intersection(ray)
+            .map(|dist| (dist, Side::new(&ray.dir, self.norm)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rays_hit_either_side() {
+        let plane = Plane::new(Point3::new(0.0, 0.0, 1.0), Vector3::z_axis());
+
+        let down = Ray::new(Point3::new(3.0, 4.0, 5.0), -Vector3::z_axis());
+        let (dist, side) = plane.dist_side(&down).expect("Missing hit.");
+        assert!((dist - 4.0).abs() < 1.0e-12);
+        assert!(!side.is_inside());
+
+        let up = Ray::new(Point3::new(3.0, 4.0, -1.0), Vector3::z_axis());
+        let (dist, side) = plane.dist_side(&up).expect("Missing hit.");
+        assert!((dist - 2.0).abs() < 1.0e-12);
+        assert!(side.is_inside());
+        assert!((side.norm().z + 1.0).abs() < 1.0e-12);
+
+        let oblique = Ray::new(
+            Point3::new(0.0, 0.0, 2.0),
+            Unit::new_normalize(Vector3::new(1.0, 0.0, -1.0)),
+        );
+        assert!((plane.dist(&oblique).expect("Missing hit.") - 2.0_f64.sqrt()).abs() < 1.0e-12);
+    }
+
+    #[test]
+    fn parallel_and_receding_rays_miss() {
+        let plane = Plane::new(Point3::new(0.0, 0.0, 1.0), Vector3::z_axis());
+
+        assert!(!plane.hit(&Ray::new(Point3::new(0.0, 0.0, 2.0), Vector3::x_axis())));
+        assert!(!plane.hit(&Ray::new(Point3::new(0.0, 0.0, 2.0), Vector3::z_axis())));
+    }
+
+    #[test]
+    fn planes_are_unbounded_and_collide_with_straddling_boxes() {
+        let plane = Plane::new(Point3::new(0.0, 0.0, 1.0), Vector3::z_axis());
+
+        assert!(plane.bounds().is_none());
+        assert!(plane.collides(&Cube::new(
+            Point3::new(10.0, 10.0, 0.5),
+            Point3::new(11.0, 11.0, 1.5)
+        )));
+        assert!(!plane.collides(&Cube::new(
+            Point3::new(10.0, 10.0, 2.0),
+            Point3::new(11.0, 11.0, 3.0)
+        )));
+    }
+}
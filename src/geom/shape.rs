@@ -0,0 +1,136 @@
+//! Traceable shape.
+
+use crate::{
+    geom::{Collide, Cube, Cylinder, Disc, Fingerprint, Plane, Sphere, Trace, Triangle},
+    rt::{Ray, Side},
+};
+
+/// Shape enumeration.
+#[allow(clippy::exhaustive_enums)]
+#[derive(Clone)]
+pub enum Shape {
+    /// Mesh triangle.
+    Triangle(Triangle),
+    /// Sphere.
+    Sphere(Sphere),
+    /// Infinite plane.
+    Plane(Plane),
+    /// Disc.
+    Disc(Disc),
+    /// Open cylinder.
+    Cylinder(Cylinder),
+}
+
+impl Collide for Shape {
+    /// Calculate the bounding box.
+    /// Unbounded shapes return `None`.
+    #[inline]
+    fn bounds(&self) -> Option<Cube> {
+        match *self {
+            Self::Triangle(ref tri) => Some(tri.boundary()),
+            Self::Sphere(ref sphere) => Some(sphere.boundary()),
+            Self::Plane(..) => None,
+            Self::Disc(ref disc) => Some(disc.boundary()),
+            Self::Cylinder(ref cyl) => Some(cyl.boundary()),
+        }
+    }
+
+    /// Check for an intersection with a given bounding box.
+    #[inline]
+    fn collides(&self, cube: &Cube) -> bool {
+        match *self {
+            Self::Triangle(ref tri) => tri.collides(cube),
+            Self::Sphere(ref sphere) => sphere.collides(cube),
+            Self::Plane(ref plane) => plane.collides(cube),
+            Self::Disc(ref disc) => disc.collides(cube),
+            Self::Cylinder(ref cyl) => cyl.collides(cube),
+        }
+    }
+}
+
+impl Trace for Shape {
+    /// Determine if a Ray-Shape intersection occurs.
+    #[inline]
+    fn hit(&self, ray: &Ray) -> bool {
+        match *self {
+            Self::Triangle(ref tri) => tri.hit(ray),
+            Self::Sphere(ref sphere) => sphere.hit(ray),
+            Self::Plane(ref plane) => plane.hit(ray),
+            Self::Disc(ref disc) => disc.hit(ray),
+            Self::Cylinder(ref cyl) => cyl.hit(ray),
+        }
+    }
+
+    /// Determine the distance to a Ray-Shape intersection.
+    #[inline]
+    fn dist(&self, ray: &Ray) -> Option<f64> {
+        match *self {
+            Self::Triangle(ref tri) => tri.dist(ray),
+            Self::Sphere(ref sphere) => sphere.dist(ray),
+            Self::Plane(ref plane) => plane.dist(ray),
+            Self::Disc(ref disc) => disc.dist(ray),
+            Self::Cylinder(ref cyl) => cyl.dist(ray),
+        }
+    }
+
+    /// Determine the distance and facing side of a Ray-Shape intersection.
+    #[inline]
+    fn dist_side(&self, ray: &Ray) -> Option<(f64, Side)> {
+        match *self {
+            Self::Triangle(ref tri) => tri.dist_side(ray),
+            Self::Sphere(ref sphere) => sphere.dist_side(ray),
+            Self::Plane(ref plane) => plane.dist_side(ray),
+            Self::Disc(ref disc) => disc.dist_side(ray),
+            Self::Cylinder(ref cyl) => cyl.dist_side(ray),
+        }
+    }
+}
+
+impl Fingerprint for Shape {
+    /// Feed a kind index, followed by the bits of each geometric value, into the writer.
+    #[inline]
+    fn fingerprint(&self, write: &mut dyn FnMut(u64)) {
+        let mut write_values = |kind: u64, values: &mut dyn Iterator<Item = f64>| {
+            write(kind);
+            for x in values {
+                write(x.to_bits());
+            }
+        };
+
+        match *self {
+            Self::Triangle(ref tri) => write_values(
+                0,
+                &mut tri
+                    .verts
+                    .iter()
+                    .zip(tri.norms.iter())
+                    .flat_map(|(vert, norm)| vert.iter().chain(norm.iter()).copied()),
+            ),
+            Self::Sphere(ref sphere) => write_values(
+                1,
+                &mut sphere.centre.iter().copied().chain(Some(sphere.radius)),
+            ),
+            Self::Plane(ref plane) => {
+                write_values(2, &mut plane.pos.iter().chain(plane.norm.iter()).copied())
+            }
+            Self::Disc(ref disc) => write_values(
+                3,
+                &mut disc
+                    .centre
+                    .iter()
+                    .chain(disc.norm.iter())
+                    .copied()
+                    .chain(Some(disc.radius)),
+            ),
+            Self::Cylinder(ref cyl) => write_values(
+                4,
+                &mut cyl
+                    .start
+                    .iter()
+                    .chain(cyl.end.iter())
+                    .copied()
+                    .chain(Some(cyl.radius)),
+            ),
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Analytic shape settings.
+
+use nalgebra::{Point3, Unit, Vector3};
+use serde::Deserialize;
+
+use crate::{
+    geom::{Cylinder, Disc, Plane, Shape, Sphere},
+    Error,
+};
+
+/// Analytic shape settings.
+#[derive(Clone, Deserialize)]
+pub enum ShapeBuilder {
+    /// Sphere.
+    Sphere {
+        /// Centre position.
+        centre: Point3<f64>,
+        /// Radius.
+        radius: f64,
+    },
+    /// Infinite plane.
+    Plane {
+        /// Position lying on the plane.
+        pos: Point3<f64>,
+        /// Surface normal.
+        norm: Vector3<f64>,
+    },
+    /// Disc.
+    Disc {
+        /// Centre position.
+        centre: Point3<f64>,
+        /// Surface normal.
+        norm: Vector3<f64>,
+        /// Radius.
+        radius: f64,
+    },
+    /// Open cylinder.
+    Cylinder {
+        /// Centre of the first end.
+        start: Point3<f64>,
+        /// Centre of the second end.
+        end: Point3<f64>,
+        /// Radius.
+        radius: f64,
+    },
+}
+
+impl ShapeBuilder {
+    /// Build the Shape.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a radius is not positive, if a normal has no length,
+    /// or if the ends of a cylinder coincide.
+    #[inline]
+    pub fn build(&self) -> Result<Shape, Error> {
+        match *self {
+            Self::Sphere { centre, radius } => {
+                check_radius(radius)?;
+                Ok(Shape::Sphere(Sphere::new(centre, radius)))
+            }
+            Self::Plane { pos, norm } => Ok(Shape::Plane(Plane::new(pos, check_norm(&norm)?))),
+            Self::Disc {
+                centre,
+                norm,
+                radius,
+            } => {
+                check_radius(radius)?;
+                Ok(Shape::Disc(Disc::new(centre, check_norm(&norm)?, radius)))
+            }
+            Self::Cylinder { start, end, radius } => {
+                check_radius(radius)?;
+                if start == end {
+                    return Err(Error::InvalidParameter(
+                        "Cylinder ends must not coincide.".to_owned(),
+                    ));
+                }
+                Ok(Shape::Cylinder(Cylinder::new(start, end, radius)))
+            }
+        }
+    }
+}
+
+/// Check that a radius is positive.
+#[inline]
+fn check_radius(radius: f64) -> Result<(), Error> {
+    if radius > 0.0 {
+        Ok(())
+    } else {
+        Err(Error::InvalidParameter(format!(
+            "Shape radius {radius} must be positive."
+        )))
+    }
+}
+
+/// Normalise a surface normal, checking that it has a length.
+#[inline]
+fn check_norm(norm: &Vector3<f64>) -> Result<Unit<Vector3<f64>>, Error> {
+    Unit::try_new(*norm, 0.0).ok_or_else(|| {
+        Error::InvalidParameter(format!("Shape normal {norm:?} must have a length."))
+    })
+}
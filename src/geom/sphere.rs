@@ -0,0 +1,161 @@
+//! Sphere.
+
+use nalgebra::{Point3, Unit, Vector3};
+
+use crate::{
+    geom::{Collide, Cube, Trace},
+    rt::{Ray, Side},
+};
+
+/// Spherical surface.
+#[derive(Clone)]
+pub struct Sphere {
+    /// Centre position.
+    pub centre: Point3<f64>,
+    /// Radius.
+    pub radius: f64,
+}
+
+impl Sphere {
+    /// Construct a new instance.
+    #[inline]
+    #[must_use]
+    pub fn new(centre: Point3<f64>, radius: f64) -> Self {
+        debug_assert!(radius > 0.0);
+
+        Self { centre, radius }
+    }
+
+    /// Calculate the bounding box.
+    #[inline]
+    #[must_use]
+    pub fn boundary(&self) -> Cube {
+        let r = Vector3::repeat(self.radius);
+        Cube::new(self.centre - r, self.centre + r)
+    }
+
+    /// Determine the distance along a Ray's direction to the nearest intersection.
+    #[inline]
+    #[must_use]
+    fn intersection(&self, ray: &Ray) -> Option<f64> {
+        let rel_pos = ray.pos - self.centre;
+        let b = rel_pos.dot(&ray.dir);
+        let c = self.radius.mul_add(-self.radius, rel_pos.norm_squared());
+
+        let discriminant = b.mul_add(b, -c);
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let root = discriminant.sqrt();
+        [-b - root, -b + root].into_iter().find(|&dist| dist > 0.0)
+    }
+}
+
+impl Collide for Sphere {
+    /// Calculate the bounding box.
+    #[inline]
+    fn bounds(&self) -> Option<Cube> {
+        Some(self.boundary())
+    }
+
+    /// Check for an intersection between the surface and a given bounding box.
+    /// Boxes lying entirely inside the sphere do not collide.
+    #[inline]
+    fn collides(&self, cube: &Cube) -> bool {
+        let mut near_sq = 0.0;
+        let mut far_sq = 0.0;
+        for axis in 0..3 {
+            let c = self.centre[axis];
+            let (min, max) = (cube.mins[axis], cube.maxs[axis]);
+
+            let near = (min - c).max(0.0) + (c - max).max(0.0);
+            let far = (c - min).abs().max((c - max).abs());
+            near_sq += near * near;
+            far_sq += far * far;
+        }
+
+        let r_sq = self.radius * self.radius;
+        near_sq <= r_sq && r_sq <= far_sq
+    }
+}
+
+impl Trace for Sphere {
+    /// Determine if a Ray-Sphere intersection occurs.
+    #[inline]
+    fn hit(&self, ray: &Ray) -> bool {
+        self.intersection(ray).is_some()
+    }
+
+    /// Determine the distance to a Ray-Sphere intersection.
+    #[inline]
+    fn dist(&self, ray: &Ray) -> Option<f64> {
+        self.intersection(ray)
+    }
+
+    /// Determine the distance and facing side of a Ray-Sphere intersection.
+    #[inline]
+    fn dist_side(&self, ray: &Ray) -> Option<(f64, Side)> {
+        self.intersection(ray).map(|dist| {
+            let pos = ray.pos + (ray.dir.as_ref() * dist);
+            (
+                dist,
+                Side::new(&ray.dir, Unit::new_normalize(pos - self.centre)),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn rays_hit_the_near_surface_from_outside() {
+        let sphere = Sphere::new(Point3::new(1.0, 2.0, 3.0), 2.0);
+
+        let ray = Ray::new(Point3::new(1.0, 2.0, -3.0), Vector3::z_axis());
+        let (dist, side) = sphere.dist_side(&ray).expect("Missing hit.");
+        assert!((dist - 4.0).abs() < 1.0e-12);
+        assert!(!side.is_inside());
+        assert!((side.norm().z + 1.0).abs() < 1.0e-12);
+
+        let offset = Ray::new(
+            Point3::new(1.0, 2.0 + 3.0_f64.sqrt(), -3.0),
+            Vector3::z_axis(),
+        );
+        assert!((sphere.dist(&offset).expect("Missing hit.") - 5.0).abs() < 1.0e-12);
+    }
+
+    #[test]
+    fn rays_miss_when_passing_by_or_heading_away() {
+        let sphere = Sphere::new(Point3::new(1.0, 2.0, 3.0), 2.0);
+
+        assert!(!sphere.hit(&Ray::new(Point3::new(1.0, 4.5, -3.0), Vector3::z_axis())));
+        assert!(!sphere.hit(&Ray::new(Point3::new(1.0, 2.0, -3.0), -Vector3::z_axis())));
+    }
+
+    #[test]
+    fn rays_from_inside_hit_the_far_surface() {
+        let sphere = Sphere::new(Point3::new(1.0, 2.0, 3.0), 2.0);
+
+        let ray = Ray::new(Point3::new(1.0, 2.0, 3.0), Vector3::x_axis());
+        let (dist, side) = sphere.dist_side(&ray).expect("Missing hit.");
+        assert!((dist - 2.0).abs() < 1.0e-12);
+        assert!(side.is_inside());
+        assert!((side.norm().x + 1.0).abs() < 1.0e-12);
+    }
+
+    #[test]
+    fn only_boxes_crossing_the_surface_collide() {
+        let sphere = Sphere::new(Point3::origin(), 2.0);
+
+        let crossing = Cube::new(Point3::new(1.5, -0.5, -0.5), Point3::new(2.5, 0.5, 0.5));
+        let inside = Cube::new(Point3::new(-0.5, -0.5, -0.5), Point3::new(0.5, 0.5, 0.5));
+        let outside = Cube::new(Point3::new(1.5, 1.5, 1.5), Point3::new(2.5, 2.5, 2.5));
+        assert!(sphere.collides(&crossing));
+        assert!(!sphere.collides(&inside));
+        assert!(!sphere.collides(&outside));
+    }
+}
@@ -0,0 +1,24 @@
+//! Ray intersection trait.
+
+use crate::rt::{Ray, Side};
+
+/// Geometry which may be intersected by a Ray.
+pub trait Trace {
+    /// Determine if a Ray intersection occurs.
+    #[inline]
+    #[must_use]
+    fn hit(&self, ray: &Ray) -> bool {
+        self.dist(ray).is_some()
+    }
+
+    /// Determine the distance to a Ray intersection.
+    #[inline]
+    #[must_use]
+    fn dist(&self, ray: &Ray) -> Option<f64> {
+        self.dist_side(ray).map(|(dist, _)| dist)
+    }
+
+    /// Determine the distance and facing side of a Ray intersection.
+    #[must_use]
+    fn dist_side(&self, ray: &Ray) -> Option<(f64, Side)>;
+}
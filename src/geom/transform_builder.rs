@@ -0,0 +1,145 @@
+//! Affine transformation settings.
+
+use nalgebra::{Affine3, Matrix4, Rotation3, Vector3};
+use serde::Deserialize;
+
+use crate::Error;
+
+/// Affine transformation settings.
+/// Scaling is applied first, followed by rotation, translation and finally the matrix.
+#[derive(Clone, Default, Deserialize)]
+pub struct TransformBuilder {
+    /// Optional translation.
+    pub translation: Option<Vector3<f64>>,
+    /// Optional rotation about the x, y and z axes (deg), applied in that order.
+    pub rotation: Option<Vector3<f64>>,
+    /// Optional scaling along each axis.
+    pub scale: Option<Vector3<f64>>,
+    /// Optional affine transformation matrix, in row-major order.
+    pub matrix: Option<[[f64; 4]; 4]>,
+}
+
+impl TransformBuilder {
+    /// Build the transformation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the matrix is not affine, or if the transformation is not invertible.
+    #[inline]
+    pub fn build(&self) -> Result<Affine3<f64>, Error> {
+        let mut trans = Matrix4::identity();
+
+        if let Some(scale) = self.scale {
+            trans = Matrix4::new_nonuniform_scaling(&scale) * trans;
+        }
+        if let Some(rotation) = self.rotation {
+            let angles = rotation.map(f64::to_radians);
+            trans =
+                Rotation3::from_euler_angles(angles.x, angles.y, angles.z).to_homogeneous() * trans;
+        }
+        if let Some(translation) = self.translation {
+            trans = Matrix4::new_translation(&translation) * trans;
+        }
+        if let Some(rows) = self.matrix {
+            if rows[3] != [0.0, 0.0, 0.0, 1.0] {
+                return Err(Error::InvalidParameter(format!(
+                    "Transformation matrix {rows:?} is not affine."
+                )));
+            }
+            trans = Matrix4::from_fn(|r, c| rows[r][c]) * trans;
+        }
+
+        if trans.fixed_view::<3, 3>(0, 0).determinant() == 0.0 {
+            return Err(Error::InvalidParameter(
+                "Transformation is not invertible.".to_owned(),
+            ));
+        }
+
+        Ok(Affine3::from_matrix_unchecked(trans))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Point3;
+
+    /// Transform a point, asserting that it lands at the expected position.
+    fn assert_maps(trans: &TransformBuilder, from: [f64; 3], to: [f64; 3]) {
+        let trans = trans.build().expect("Failed to build transformation.");
+        let pos = trans.transform_point(&Point3::from(from));
+        assert!((pos - Point3::from(to)).norm() < 1.0e-12, "{pos} != {to:?}");
+    }
+
+    #[test]
+    fn components_combine_in_order() {
+        // Scale, then rotate, then translate.
+        let trans = TransformBuilder {
+            translation: Some(Vector3::new(1.0, 0.0, 0.0)),
+            rotation: Some(Vector3::new(0.0, 0.0, 90.0)),
+            scale: Some(Vector3::new(2.0, 1.0, 1.0)),
+            matrix: None,
+        };
+        assert_maps(&trans, [1.0, 0.0, 0.0], [1.0, 2.0, 0.0]);
+
+        // Rotations about x, then y, then z.
+        let trans = TransformBuilder {
+            rotation: Some(Vector3::new(90.0, 90.0, 0.0)),
+            ..TransformBuilder::default()
+        };
+        assert_maps(&trans, [0.0, 1.0, 0.0], [1.0, 0.0, 0.0]);
+
+        // The matrix is applied last.
+        let trans = TransformBuilder {
+            scale: Some(Vector3::new(2.0, 2.0, 2.0)),
+            matrix: Some([
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 5.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ]),
+            ..TransformBuilder::default()
+        };
+        assert_maps(&trans, [1.0, 1.0, 1.0], [2.0, 7.0, 2.0]);
+
+        assert_maps(
+            &TransformBuilder::default(),
+            [1.0, 2.0, 3.0],
+            [1.0, 2.0, 3.0],
+        );
+    }
+
+    #[test]
+    fn invalid_transformations_are_rejected() {
+        let singular = TransformBuilder {
+            matrix: Some([
+                [1.0, 2.0, 3.0, 0.0],
+                [2.0, 4.0, 6.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ]),
+            ..TransformBuilder::default()
+        };
+        assert!(matches!(singular.build(), Err(Error::InvalidParameter(_))));
+
+        let projective = TransformBuilder {
+            matrix: Some([
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 1.0, 1.0],
+            ]),
+            ..TransformBuilder::default()
+        };
+        assert!(matches!(
+            projective.build(),
+            Err(Error::InvalidParameter(_))
+        ));
+
+        let flat = TransformBuilder {
+            scale: Some(Vector3::new(1.0, 0.0, 1.0)),
+            ..TransformBuilder::default()
+        };
+        assert!(matches!(flat.build(), Err(Error::InvalidParameter(_))));
+    }
+}
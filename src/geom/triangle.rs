@@ -1,10 +1,11 @@
 //! Triangle.
 
-use nalgebra::{Point3, Unit, Vector3};
+use nalgebra::{Affine3, Point3, Unit, Vector3};
 
 use crate::{
-    geom::Cube,
+    geom::{Collide, Cube, Trace},
     rt::{Ray, Side},
+    Error,
 };
 
 /// Two-dimensional triangle embedded in three-dimensional space.
@@ -34,6 +35,48 @@ impl Triangle {
         }
     }
 
+    /// Apply an affine transformation.
+    /// Normals are transformed by the inverse-transpose, and the winding is reversed by mirroring
+    /// transformations so that the plane-normal continues to agree with the vertex normals.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transformation is not invertible, leaving the triangle unchanged.
+    #[inline]
+    pub fn transform(&mut self, trans: &Affine3<f64>) -> Result<(), Error> {
+        let lin = trans.matrix().fixed_view::<3, 3>(0, 0).into_owned();
+        let norm_trans = lin
+            .try_inverse()
+            .ok_or_else(|| {
+                Error::InvalidParameter("Triangle transformation is not invertible.".to_owned())
+            })?
+            .transpose();
+
+        let mut verts = self.verts.map(|vert| trans.transform_point(&vert));
+        let mut norms = self
+            .norms
+            .map(|norm| Unit::new_normalize(norm_trans * norm.as_ref()));
+        if lin.determinant() < 0.0 {
+            verts.swap(1, 2);
+            norms.swap(1, 2);
+        }
+
+        *self = Self::new(verts, norms);
+
+        Ok(())
+    }
+
+    /// Calculate the bounding box.
+    /// Axis-aligned triangles have no width along one axis.
+    #[inline]
+    #[must_use]
+    pub fn boundary(&self) -> Cube {
+        Cube {
+            mins: self.verts[0].inf(&self.verts[1]).inf(&self.verts[2]),
+            maxs: self.verts[0].sup(&self.verts[1]).sup(&self.verts[2]),
+        }
+    }
+
     /// Calculate the central position.
     #[inline]
     #[must_use]
@@ -74,10 +117,60 @@ impl Triangle {
         (s * (s - ab) * (s - bc) * (s - ca)).sqrt()
     }
 
-    /// Check for an intersection with a given bounding box.
+    /// Determine the intersection distance along a Ray's direction.
+    /// Also return the barycentric intersection coordinates.
     #[inline]
     #[must_use]
-    pub fn collides(&self, cube: &Cube) -> bool {
+    fn intersection_coors(&self, ray: &Ray) -> Option<(f64, [f64; 3])> {
+        let verts = self.verts;
+
+        let e1 = verts[1] - verts[0];
+        let e2 = verts[2] - verts[0];
+
+        let d_cross_e2 = ray.dir.cross(&e2);
+        let e1_dot_d_cross_e2 = e1.dot(&d_cross_e2);
+
+        if e1_dot_d_cross_e2.abs() <= 0.0 {
+            return None;
+        }
+
+        let inv_e1_dot_d_cross_e2 = 1.0 / e1_dot_d_cross_e2;
+        let rel_pos = ray.pos - verts[0];
+        let u = inv_e1_dot_d_cross_e2 * rel_pos.dot(&d_cross_e2);
+
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = rel_pos.cross(&e1);
+        let v = inv_e1_dot_d_cross_e2 * ray.dir.dot(&q);
+
+        if (v < 0.0) || ((u + v) > 1.0) {
+            return None;
+        }
+
+        let dist = inv_e1_dot_d_cross_e2 * e2.dot(&q);
+
+        if dist <= 0.0 {
+            return None;
+        }
+
+        let w = 1.0 - (u + v);
+
+        Some((dist, [u, v, w]))
+    }
+}
+
+impl Collide for Triangle {
+    /// Calculate the bounding box.
+    #[inline]
+    fn bounds(&self) -> Option<Cube> {
+        Some(self.boundary())
+    }
+
+    /// Check for an intersection with a given bounding box.
+    #[inline]
+    fn collides(&self, cube: &Cube) -> bool {
         let c = cube.centre();
         let e = cube.half_widths();
 
@@ -168,61 +261,18 @@ impl Triangle {
 
         true
     }
+}
 
-    /// Determine the intersection distance along a Ray's direction.
-    /// Also return the barycentric intersection coordinates.
-    #[inline]
-    #[must_use]
-    fn intersection_coors(&self, ray: &Ray) -> Option<(f64, [f64; 3])> {
-        let verts = self.verts;
-
-        let e1 = verts[1] - verts[0];
-        let e2 = verts[2] - verts[0];
-
-        let d_cross_e2 = ray.dir.cross(&e2);
-        let e1_dot_d_cross_e2 = e1.dot(&d_cross_e2);
-
-        if e1_dot_d_cross_e2.abs() <= 0.0 {
-            return None;
-        }
-
-        let inv_e1_dot_d_cross_e2 = 1.0 / e1_dot_d_cross_e2;
-        let rel_pos = ray.pos - verts[0];
-        let u = inv_e1_dot_d_cross_e2 * rel_pos.dot(&d_cross_e2);
-
-        if !(0.0..=1.0).contains(&u) {
-            return None;
-        }
-
-        let q = rel_pos.cross(&e1);
-        let v = inv_e1_dot_d_cross_e2 * ray.dir.dot(&q);
-
-        if (v < 0.0) || ((u + v) > 1.0) {
-            return None;
-        }
-
-        let dist = inv_e1_dot_d_cross_e2 * e2.dot(&q);
-
-        if dist <= 0.0 {
-            return None;
-        }
-
-        let w = 1.0 - (u + v);
-
-        Some((dist, [u, v, w]))
-    }
-
+impl Trace for Triangle {
     /// Determine if a Ray-Triangle intersection occurs.
     #[inline]
-    #[must_use]
-    pub fn hit(&self, ray: &Ray) -> bool {
+    fn hit(&self, ray: &Ray) -> bool {
         self.intersection_coors(ray).is_some()
     }
 
     /// Determine the distance to a Ray-Triangle intersection.
     #[inline]
-    #[must_use]
-    pub fn dist(&self, ray: &Ray) -> Option<f64> {
+    fn dist(&self, ray: &Ray) -> Option<f64> {
         if let Some((dist, _coors)) = self.intersection_coors(ray) {
             return Some(dist);
         }
@@ -232,8 +282,7 @@ impl Triangle {
 
     /// Determine the distance and facing side of a Ray-Triangle intersection.
     #[inline]
-    #[must_use]
-    pub fn dist_side(&self, ray: &Ray) -> Option<(f64, Side)> {
+    fn dist_side(&self, ray: &Ray) -> Option<(f64, Side)> {
         if let Some((dist, [u, v, w])) = self.intersection_coors(ray) {
             Some((
                 dist,
@@ -251,3 +300,46 @@ impl Triangle {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Matrix4;
+
+    /// Construct a unit right triangle in the xy-plane, facing +z.
+    fn triangle() -> Triangle {
+        Triangle::new(
+            [
+                Point3::origin(),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0),
+            ],
+            [Vector3::z_axis(); 3],
+        )
+    }
+
+    #[test]
+    fn mirroring_keeps_normals_consistent() {
+        let mut tri = triangle();
+        let mirror = Affine3::from_matrix_unchecked(Matrix4::new_nonuniform_scaling(
+            &Vector3::new(1.0, 1.0, -1.0),
+        ));
+
+        tri.transform(&mirror)
+            .expect("Failed to transform triangle.");
+
+        assert_eq!(tri.plane_norm, -Vector3::z_axis());
+        assert_eq!(tri.norms, [-Vector3::z_axis(); 3]);
+    }
+
+    #[test]
+    fn singular_transformation_is_rejected() {
+        let mut tri = triangle();
+        let flatten = Affine3::from_matrix_unchecked(Matrix4::new_nonuniform_scaling(
+            &Vector3::new(1.0, 0.0, 1.0),
+        ));
+
+        assert!(tri.transform(&flatten).is_err());
+        assert_eq!(tri.verts, triangle().verts);
+    }
+}
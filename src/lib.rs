@@ -38,9 +38,12 @@
 )]
 
 pub mod dom;
+pub mod error;
 pub mod geom;
 pub mod parse;
 pub mod phys;
 pub mod render;
 pub mod rt;
 pub mod util;
+
+pub use self::error::Error;
@@ -0,0 +1,515 @@
+//! GL transmission format.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use gltf::{buffer::Source, camera::Projection, material::AlphaMode, mesh::Mode, Gltf, Node};
+use nalgebra::{Matrix3, Matrix4, Point3, Unit, Vector3};
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::{
+    geom::{Mesh, Triangle},
+    Error,
+};
+
+/// Name given to the material of primitives without one.
+const DEFAULT_MATERIAL_NAME: &str = "default";
+
+/// Physically based surface material properties.
+#[derive(Clone)]
+pub struct PbrMaterial {
+    /// Base colour and alpha.
+    pub base_colour: [f64; 4],
+    /// Metalness factor.
+    pub metallic: f64,
+    /// Roughness factor.
+    pub roughness: f64,
+    /// Emissive colour.
+    pub emissive: [f64; 3],
+    /// Whether the alpha channel should be blended.
+    pub blend: bool,
+    /// Transmission factor.
+    pub transmission: f64,
+    /// Refractive index.
+    pub ref_index: f64,
+}
+
+impl Default for PbrMaterial {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            base_colour: [1.0; 4],
+            metallic: 1.0,
+            roughness: 1.0,
+            emissive: [0.0; 3],
+            blend: false,
+            transmission: 0.0,
+            ref_index: 1.5,
+        }
+    }
+}
+
+/// Perspective camera view.
+pub struct SceneCamera {
+    /// Position.
+    pub pos: Point3<f64>,
+    /// Target.
+    pub tar: Point3<f64>,
+    /// Vertical field-of-view (rad).
+    pub yfov: f64,
+    /// Optional aspect ratio (width / height).
+    pub aspect: Option<f64>,
+}
+
+impl SceneCamera {
+    /// Calculate the horizontal field-of-view (rad) for the given image resolution.
+    /// The aspect ratio of the camera is used if known.
+    #[inline]
+    #[must_use]
+    pub fn hfov(&self, res: [usize; 2]) -> f64 {
+        let aspect = self
+            .aspect
+            .unwrap_or_else(|| res[0] as f64 / res[1].max(1) as f64);
+        2.0 * ((self.yfov * 0.5).tan() * aspect).atan()
+    }
+}
+
+/// Triangles of a scene sharing a material.
+pub struct SceneSurface {
+    /// Material name.
+    pub name: String,
+    /// Material properties.
+    pub material: PbrMaterial,
+    /// Geometry, in world space.
+    pub mesh: Mesh,
+}
+
+/// Scene read from a glTF file.
+pub struct Scene {
+    /// Surfaces, one per material.
+    pub surfaces: Vec<SceneSurface>,
+    /// First perspective camera, if any.
+    pub camera: Option<SceneCamera>,
+}
+
+/// Load a scene from a `.gltf` or `.glb` file.
+/// External buffers are resolved relative to the file.
+///
+/// # Errors
+///
+/// Returns an error if the file, or a buffer it references, can not be read,
+/// or if the contents are not a valid glTF scene.
+#[inline]
+pub fn load(path: &Path) -> Result<Scene, Error> {
+    let bytes = fs::read(path).map_err(|err| Error::Io(path.to_path_buf(), err))?;
+    read_with_dir(&bytes, path.parent()).map_err(|err| err.with_path(path))
+}
+
+/// Read a scene from glTF or binary glTF data.
+/// Only buffers embedded in the binary chunk or as data URIs can be read.
+///
+/// # Errors
+///
+/// Returns an error if the data is not a valid glTF scene,
+/// or if it references an external buffer.
+#[inline]
+pub fn read(bytes: &[u8]) -> Result<Scene, Error> {
+    read_with_dir(bytes, None)
+}
+
+/// Read a scene, resolving any external buffers relative to the given directory.
+/// The y-up glTF coordinate system is converted to z-up.
+#[inline]
+fn read_with_dir(bytes: &[u8], dir: Option<&Path>) -> Result<Scene, Error> {
+    let Gltf { document, mut blob } =
+        Gltf::from_slice(bytes).map_err(|err| error(&err.to_string()))?;
+
+    let buffers = document
+        .buffers()
+        .map(|buffer| {
+            let data = match buffer.source() {
+                Source::Bin => blob.take().ok_or_else(|| error("Missing binary chunk."))?,
+                Source::Uri(uri) => read_uri(uri, dir)?,
+            };
+            if data.len() < buffer.length() {
+                return Err(error(&format!("Buffer {} is too short.", buffer.index())));
+            }
+            Ok(data)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let scene = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+        .ok_or_else(|| error("No scenes found."))?;
+
+    let mut data = Data {
+        buffers: &buffers,
+        tris: HashMap::new(),
+        materials: HashMap::new(),
+        material_names: HashMap::new(),
+        camera: None,
+    };
+    let y_up_to_z_up = Matrix4::new(
+        1.0, 0.0, 0.0, 0.0, //
+        0.0, 0.0, -1.0, 0.0, //
+        0.0, 1.0, 0.0, 0.0, //
+        0.0, 0.0, 0.0, 1.0,
+    );
+    for node in scene.nodes() {
+        data.add_node(&node, &y_up_to_z_up)?;
+    }
+
+    let mut names: Vec<_> = data.tris.keys().cloned().collect();
+    names.sort();
+    let surfaces = names
+        .into_iter()
+        .filter_map(|name| {
+            let tris = data.tris.remove(&name)?;
+            let material = data.materials.remove(&name).unwrap_or_default();
+            Some(SceneSurface {
+                name,
+                material,
+                mesh: Mesh::new(tris),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if surfaces.is_empty() {
+        return Err(error("No triangles found."));
+    }
+
+    Ok(Scene {
+        surfaces,
+        camera: data.camera,
+    })
+}
+
+/// Create a parsing error with the given description.
+#[inline]
+#[must_use]
+fn error(msg: &str) -> Error {
+    Error::Gltf(None, msg.to_owned())
+}
+
+/// Read the contents of a buffer URI.
+#[inline]
+fn read_uri(uri: &str, dir: Option<&Path>) -> Result<Vec<u8>, Error> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        let (_, encoded) = data
+            .split_once(";base64,")
+            .ok_or_else(|| error("Only base64 data URIs are supported."))?;
+        return STANDARD
+            .decode(encoded)
+            .map_err(|err| error(&format!("Invalid base64 data: {err}")));
+    }
+
+    let dir = dir.ok_or_else(|| error(&format!("Unable to resolve external buffer: {uri}")))?;
+    let path = dir.join(uri.replace("%20", " "));
+    fs::read(&path).map_err(|err| Error::Io(path, err))
+}
+
+/// Scene data being gathered.
+struct Data<'a> {
+    /// Buffer contents.
+    buffers: &'a [Vec<u8>],
+    /// Triangles grouped by material name.
+    tris: HashMap<String, Vec<Triangle>>,
+    /// Materials by name.
+    materials: HashMap<String, PbrMaterial>,
+    /// Names assigned to each material index.
+    material_names: HashMap<Option<usize>, String>,
+    /// First perspective camera.
+    camera: Option<SceneCamera>,
+}
+
+impl Data<'_> {
+    /// Add a node, and its children, with the given parent transform.
+    #[inline]
+    fn add_node(&mut self, node: &Node, parent: &Matrix4<f64>) -> Result<(), Error> {
+        let cols = node.transform().matrix();
+        let local = Matrix4::from_fn(|r, c| f64::from(cols[c][r]));
+        let trans = parent * local;
+
+        if let Some(mesh) = node.mesh() {
+            self.add_mesh(&mesh, &trans)?;
+        }
+
+        if let (None, Some(cam)) = (&self.camera, node.camera()) {
+            if let Projection::Perspective(persp) = cam.projection() {
+                let pos = trans.transform_point(&Point3::origin());
+                let forward = trans.transform_vector(&-Vector3::z());
+                self.camera = Some(SceneCamera {
+                    pos,
+                    tar: pos + forward.normalize(),
+                    yfov: f64::from(persp.yfov()),
+                    aspect: persp.aspect_ratio().map(f64::from),
+                });
+            }
+        }
+
+        for child in node.children() {
+            self.add_node(&child, &trans)?;
+        }
+
+        Ok(())
+    }
+
+    /// Add the triangles of each primitive of a mesh, with the given world transform.
+    #[inline]
+    fn add_mesh(&mut self, mesh: &gltf::Mesh, trans: &Matrix4<f64>) -> Result<(), Error> {
+        let lin: Matrix3<f64> = trans.fixed_view::<3, 3>(0, 0).into();
+        let norm_trans = lin.try_inverse().map_or(lin, |inv| inv.transpose());
+        let mirrored = lin.determinant() < 0.0;
+
+        for prim in mesh.primitives() {
+            let reader = prim.reader(|buffer| self.buffers.get(buffer.index()).map(Vec::as_slice));
+
+            let verts: Vec<_> = reader
+                .read_positions()
+                .ok_or_else(|| error("Primitive is missing vertex positions."))?
+                .map(|[x, y, z]| {
+                    trans.transform_point(&Point3::new(f64::from(x), f64::from(y), f64::from(z)))
+                })
+                .collect();
+            let norms: Option<Vec<_>> = reader.read_normals().map(|norms| {
+                norms
+                    .map(|[x, y, z]| {
+                        norm_trans * Vector3::new(f64::from(x), f64::from(y), f64::from(z))
+                    })
+                    .collect()
+            });
+            if let Some(ref norms) = norms {
+                if norms.len() != verts.len() {
+                    return Err(error(&format!(
+                        "Primitive has {} normals for {} vertex positions.",
+                        norms.len(),
+                        verts.len()
+                    )));
+                }
+                if norms.iter().any(|norm| !(norm.norm_squared() > 0.0)) {
+                    return Err(error("Primitive has a zero length vertex normal."));
+                }
+            }
+            let indices: Vec<_> = reader.read_indices().map_or_else(
+                || (0..verts.len()).collect(),
+                |indices| indices.into_u32().map(|i| i as usize).collect(),
+            );
+            if let Some(&i) = indices.iter().find(|&&i| i >= verts.len()) {
+                return Err(error(&format!("Invalid vertex index: {i}")));
+            }
+
+            let faces: Vec<[usize; 3]> = match prim.mode() {
+                Mode::Triangles => indices
+                    .chunks_exact(3)
+                    .map(|c| [c[0], c[1], c[2]])
+                    .collect(),
+                Mode::TriangleStrip => indices
+                    .windows(3)
+                    .enumerate()
+                    .map(|(n, w)| {
+                        if n % 2 == 0 {
+                            [w[0], w[1], w[2]]
+                        } else {
+                            [w[1], w[0], w[2]]
+                        }
+                    })
+                    .collect(),
+                Mode::TriangleFan => (1..indices.len().saturating_sub(1))
+                    .map(|n| [indices[0], indices[n], indices[n + 1]])
+                    .collect(),
+                Mode::Points | Mode::Lines | Mode::LineLoop | Mode::LineStrip => continue,
+            };
+
+            let mut tris = Vec::with_capacity(faces.len());
+            for mut face in faces {
+                // Mirroring transforms reverse the winding, as in `Triangle::transform`.
+                if mirrored {
+                    face.swap(1, 2);
+                }
+
+                let tri_verts = face.map(|vi| verts[vi]);
+                let plane_norm =
+                    (tri_verts[1] - tri_verts[0]).cross(&(tri_verts[2] - tri_verts[0]));
+                if plane_norm.norm_squared() <= 0.0 {
+                    continue;
+                }
+
+                let tri_norms = norms.as_ref().map_or_else(
+                    || [Unit::new_normalize(plane_norm); 3],
+                    |norms| face.map(|vi| Unit::new_normalize(norms[vi])),
+                );
+                tris.push(Triangle::new(tri_verts, tri_norms));
+            }
+
+            // Primitives without any valid triangles do not create a surface.
+            if !tris.is_empty() {
+                let name = self.add_material(&prim.material());
+                self.tris.entry(name).or_insert_with(Vec::new).extend(tris);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Record a material, returning its unique name.
+    #[inline]
+    fn add_material(&mut self, mat: &gltf::Material) -> String {
+        if let Some(name) = self.material_names.get(&mat.index()) {
+            return name.clone();
+        }
+
+        let mut name = match (mat.index(), mat.name()) {
+            (None, _) => DEFAULT_MATERIAL_NAME.to_owned(),
+            (Some(_), Some(name)) if !name.is_empty() => name.to_owned(),
+            (Some(index), _) => format!("material_{index}"),
+        };
+        if let Some(index) = mat.index() {
+            if self.materials.contains_key(&name) {
+                name = format!("{name}_{index}");
+            }
+        }
+        self.material_names.insert(mat.index(), name.clone());
+
+        let pbr = mat.pbr_metallic_roughness();
+        self.materials.insert(
+            name.clone(),
+            PbrMaterial {
+                base_colour: pbr.base_color_factor().map(f64::from),
+                metallic: f64::from(pbr.metallic_factor()),
+                roughness: f64::from(pbr.roughness_factor()),
+                emissive: mat.emissive_factor().map(f64::from),
+                blend: mat.alpha_mode() == AlphaMode::Blend,
+                transmission: mat
+                    .transmission()
+                    .map_or(0.0, |trans| f64::from(trans.transmission_factor())),
+                ref_index: mat.ior().map_or(1.5, f64::from),
+            },
+        );
+
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write a glTF scene with one valid and one degenerate triangle primitive, and a camera.
+    /// Unused accessors hold three valid normals, and two normals, for the first primitive.
+    fn scene() -> String {
+        let data: [f32; 27] = [
+            0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, //
+            0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 2.0, 0.0, 0.0, //
+            0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0,
+        ];
+        let bytes: Vec<u8> = data.iter().flat_map(|x| x.to_le_bytes()).collect();
+        let uri = format!(
+            "data:application/octet-stream;base64,{}",
+            STANDARD.encode(&bytes)
+        );
+
+        format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "scene": 0,
+                "scenes": [{{ "nodes": [0, 1] }}],
+                "nodes": [
+                    {{ "mesh": 0 }},
+                    {{ "camera": 0, "translation": [0.0, 1.0, 5.0] }}
+                ],
+                "cameras": [{{ "type": "perspective", "perspective": {{ "yfov": 0.5, "znear": 0.1 }} }}],
+                "meshes": [{{ "primitives": [
+                    {{ "attributes": {{ "POSITION": 0 }}, "material": 0 }},
+                    {{ "attributes": {{ "POSITION": 1 }}, "material": 1 }}
+                ] }}],
+                "materials": [
+                    {{ "name": "good", "pbrMetallicRoughness": {{ "baseColorFactor": [0.25, 0.5, 0.75, 1.0] }} }},
+                    {{ "name": "bad" }}
+                ],
+                "accessors": [
+                    {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                       "min": [0.0, 0.0, 0.0], "max": [1.0, 1.0, 0.0] }},
+                    {{ "bufferView": 0, "byteOffset": 36, "componentType": 5126, "count": 3, "type": "VEC3",
+                       "min": [0.0, 0.0, 0.0], "max": [2.0, 0.0, 0.0] }},
+                    {{ "bufferView": 0, "byteOffset": 72, "componentType": 5126, "count": 3, "type": "VEC3" }},
+                    {{ "bufferView": 0, "byteOffset": 72, "componentType": 5126, "count": 2, "type": "VEC3" }}
+                ],
+                "bufferViews": [{{ "buffer": 0, "byteLength": {len} }}],
+                "buffers": [{{ "byteLength": {len}, "uri": "{uri}" }}]
+            }}"#,
+            len = bytes.len()
+        )
+    }
+
+    #[test]
+    fn surfaces_are_read_in_z_up_space() {
+        let scene = read(scene().as_bytes()).expect("Failed to read scene.");
+
+        assert_eq!(scene.surfaces.len(), 1);
+        let surf = &scene.surfaces[0];
+        assert_eq!(surf.name, "good");
+        assert_eq!(surf.material.base_colour, [0.25, 0.5, 0.75, 1.0]);
+        assert_eq!(surf.mesh.tris.len(), 1);
+        assert_eq!(surf.mesh.tris[0].verts[2], Point3::new(0.0, 0.0, 1.0));
+        assert_eq!(surf.mesh.tris[0].plane_norm, -Vector3::y_axis());
+    }
+
+    #[test]
+    fn camera_is_read_in_z_up_space() {
+        let scene = read(scene().as_bytes()).expect("Failed to read scene.");
+
+        let cam = scene.camera.expect("Missing camera.");
+        assert!((cam.pos - Point3::new(0.0, -5.0, 1.0)).norm() < 1.0e-6);
+        assert!((cam.tar - Point3::new(0.0, -4.0, 1.0)).norm() < 1.0e-6);
+        assert!((cam.yfov - 0.5).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn mirrored_nodes_keep_their_facing() {
+        let s = scene().replace(
+            r#"{ "mesh": 0 }"#,
+            r#"{ "mesh": 0, "scale": [-1.0, 1.0, 1.0] }"#,
+        );
+        let scene = read(s.as_bytes()).expect("Failed to read scene.");
+
+        let tri = &scene.surfaces[0].mesh.tris[0];
+        assert!(tri.verts.contains(&Point3::new(-1.0, 0.0, 0.0)));
+        assert_eq!(tri.plane_norm, -Vector3::y_axis());
+    }
+
+    #[test]
+    fn vertex_normals_are_checked() {
+        let with_norms = |accessor: usize| {
+            scene().replace(
+                r#""POSITION": 0 }"#,
+                &format!(r#""POSITION": 0, "NORMAL": {accessor} }}"#),
+            )
+        };
+
+        let scene = read(with_norms(2).as_bytes()).expect("Failed to read scene.");
+        assert_eq!(
+            scene.surfaces[0].mesh.tris[0].norms,
+            [-Vector3::y_axis(); 3]
+        );
+
+        // Zero length normals.
+        assert!(read(with_norms(1).as_bytes()).is_err());
+        // Fewer normals than positions.
+        assert!(read(with_norms(3).as_bytes()).is_err());
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        let s = scene();
+        assert!(read(&s.as_bytes()[1..]).is_err());
+        assert!(read(
+            s.replace("data:application/octet-stream;base64,", "external.bin")
+                .as_bytes()
+        )
+        .is_err());
+        assert!(read(
+            s.replace("\"byteLength\": 108 }]", "\"byteLength\": 116 }]")
+                .as_bytes()
+        )
+        .is_err());
+    }
+}
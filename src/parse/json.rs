@@ -1,49 +1,56 @@
 //! JavaScript Object Notation.
 
-use core::any::type_name;
 use serde::{Deserialize, Serialize};
 use serde_json::to_string;
 use std::{fs, path::Path};
 
+use crate::Error;
+
 /// Load a type from a JSON file.
+///
+/// # Errors
+///
+/// Returns an error if the file can not be read,
+/// or if the contents can not be parsed into the requested type.
 #[inline]
-#[must_use]
-pub fn load<T>(path: &Path) -> T
+pub fn load<T>(path: &Path) -> Result<T, Error>
 where
     for<'de> T: Deserialize<'de>,
 {
-    read(
-        &fs::read_to_string(path)
-            .unwrap_or_else(|_| panic!("Failed to read file: {}.", path.display())),
-    )
+    read(&fs::read_to_string(path).map_err(|err| Error::Io(path.to_path_buf(), err))?)
+        .map_err(|err| err.with_path(path))
 }
 
 /// Read a type from a JSON string.
+///
+/// # Errors
+///
+/// Returns an error if the string can not be parsed into the requested type.
 #[inline]
-#[must_use]
-pub fn read<T>(s: &str) -> T
+pub fn read<T>(s: &str) -> Result<T, Error>
 where
     for<'de> T: Deserialize<'de>,
 {
-    json5::from_str(s).unwrap_or_else(|_| {
-        panic!(
-            "Failed to create type {} from JSON string.",
-            type_name::<T>()
-        )
-    })
+    json5::from_str(s).map_err(|err| Error::Json5(None, err))
 }
 
 /// Serialise a type in json format.
+///
+/// # Errors
+///
+/// Returns an error if the instance can not be serialised,
+/// or if the file can not be written.
 #[inline]
-pub fn save<T: Serialize>(instance: &T, path: &Path) {
-    fs::write(path, write(instance))
-        .unwrap_or_else(|_| panic!("Failed to write file: {}.", path.display()));
+pub fn save<T: Serialize>(instance: &T, path: &Path) -> Result<(), Error> {
+    fs::write(path, write(instance)?).map_err(|err| Error::Io(path.to_path_buf(), err))
 }
 
 /// Serialise a type in json format.
+///
+/// # Errors
+///
+/// Returns an error if the instance can not be serialised.
 #[inline]
-#[must_use]
-pub fn write<T: Serialize>(instance: &T) -> String {
-    to_string(instance)
-        .unwrap_or_else(|_| panic!("Failed to write type {} to JSON string.", type_name::<T>()))
+pub fn write<T: Serialize>(instance: &T) -> Result<String, Error> {
+    to_string(instance).map_err(Error::Json)
 }
@@ -1,7 +1,11 @@
 //! File format parsers.
 
+pub mod gltf;
 pub mod json;
+pub mod mtl;
+pub mod ply;
 pub mod png;
+pub mod stl;
 pub mod wavefront;
 
-pub use self::{json::*, png::*, wavefront::*};
+pub use self::{gltf::*, json::*, mtl::*, ply::*, png::*, stl::*, wavefront::*};
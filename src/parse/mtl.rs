@@ -0,0 +1,219 @@
+//! Wavefront material library.
+
+use core::str::SplitWhitespace;
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::Error;
+
+/// Surface material properties read from a wavefront material library.
+#[derive(Clone)]
+pub struct Material {
+    /// Diffuse colour.
+    pub diffuse: [f64; 3],
+    /// Specular colour.
+    pub specular: [f64; 3],
+    /// Specular exponent.
+    pub spec_exp: f64,
+    /// Opacity.
+    pub opacity: f64,
+    /// Optical density (refractive index).
+    pub ref_index: f64,
+    /// Optional illumination model.
+    pub illum: Option<u32>,
+}
+
+impl Default for Material {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            diffuse: [0.8; 3],
+            specular: [0.0; 3],
+            spec_exp: 0.0,
+            opacity: 1.0,
+            ref_index: 1.0,
+            illum: None,
+        }
+    }
+}
+
+/// Load a dictionary of materials from a wavefront material library file.
+///
+/// # Errors
+///
+/// Returns an error if the file can not be read,
+/// or if the contents are not a valid material library.
+#[inline]
+pub fn load(path: &Path) -> Result<HashMap<String, Material>, Error> {
+    read(&fs::read_to_string(path).map_err(|err| Error::Io(path.to_path_buf(), err))?)
+        .map_err(|err| err.with_path(path))
+}
+
+/// Read a dictionary of materials from a wavefront material library string.
+///
+/// # Errors
+///
+/// Returns an error if the string is not a valid material library.
+#[inline]
+pub fn read(s: &str) -> Result<HashMap<String, Material>, Error> {
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, Material)> = None;
+
+    for (index, raw_line) in s.lines().enumerate() {
+        let line_num = index + 1;
+        let line = raw_line.split('#').next().unwrap_or_default();
+        let mut words = line.split_whitespace();
+
+        let keyword = match words.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+
+        if keyword == "newmtl" {
+            if let Some((name, mat)) = current.take() {
+                materials.insert(name, mat);
+            }
+            let name = words.collect::<Vec<_>>().join(" ");
+            if name.is_empty() {
+                return Err(Error::Wavefront(
+                    None,
+                    line_num,
+                    "Missing material name.".to_owned(),
+                ));
+            }
+            current = Some((name, Material::default()));
+            continue;
+        }
+
+        let mat = match current.as_mut() {
+            Some(&mut (_, ref mut mat)) => mat,
+            None => continue,
+        };
+
+        match keyword {
+            "Kd" => mat.diffuse = read_colour(words, line_num)?,
+            "Ks" => mat.specular = read_colour(words, line_num)?,
+            "Ns" => mat.spec_exp = read_value(&mut words, line_num, "specular exponent")?,
+            "d" => {
+                let mut value = words.next();
+                if value == Some("-halo") {
+                    value = words.next();
+                }
+                mat.opacity = parse_word(value, line_num, "opacity")?;
+            }
+            "Tr" => mat.opacity = 1.0 - read_value(&mut words, line_num, "transparency")?,
+            "Ni" => mat.ref_index = read_value(&mut words, line_num, "optical density")?,
+            "illum" => {
+                mat.illum = Some(
+                    words
+                        .next()
+                        .and_then(|word| word.parse::<u32>().ok())
+                        .ok_or_else(|| {
+                            Error::Wavefront(
+                                None,
+                                line_num,
+                                "Unable to parse illumination model.".to_owned(),
+                            )
+                        })?,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    if let Some((name, mat)) = current {
+        materials.insert(name, mat);
+    }
+
+    Ok(materials)
+}
+
+/// Read an RGB colour.
+/// A single value is interpreted as a grey.
+#[inline]
+fn read_colour(mut words: SplitWhitespace, line_num: usize) -> Result<[f64; 3], Error> {
+    let r = read_value(&mut words, line_num, "colour")?;
+    let g = words.next();
+    let b = words.next();
+
+    if g.is_none() {
+        return Ok([r; 3]);
+    }
+
+    Ok([
+        r,
+        parse_word(g, line_num, "colour")?,
+        parse_word(b, line_num, "colour")?,
+    ])
+}
+
+/// Read the next word as a value.
+#[inline]
+fn read_value(words: &mut SplitWhitespace, line_num: usize, name: &str) -> Result<f64, Error> {
+    parse_word(words.next(), line_num, name)
+}
+
+/// Parse an optional word as a value.
+#[inline]
+fn parse_word(word: Option<&str>, line_num: usize, name: &str) -> Result<f64, Error> {
+    let word =
+        word.ok_or_else(|| Error::Wavefront(None, line_num, format!("Missing {name} value.")))?;
+
+    word.parse::<f64>().map_err(|err| {
+        Error::Wavefront(
+            None,
+            line_num,
+            format!("Unable to parse {name} value from string: {word} ({err})"),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn material_properties_are_read() {
+        let s = "# Library\n\
+                 newmtl glass\n\
+                 Kd 0.1 0.2 0.3 # Diffuse\n\
+                 Ks 0.5\n\
+                 Ns 96.0\n\
+                 Ni 1.45\n\
+                 d -halo 0.25\n\
+                 illum 7\n\
+                 \n\
+                 newmtl red paint\n\
+                 Kd 1 0 0\n\
+                 Tr 0.75\n";
+
+        let mats = read(s).expect("Failed to read materials.");
+
+        assert_eq!(mats.len(), 2);
+        let glass = &mats["glass"];
+        assert_eq!(glass.diffuse, [0.1, 0.2, 0.3]);
+        assert_eq!(glass.specular, [0.5; 3]);
+        assert_eq!(glass.spec_exp, 96.0);
+        assert_eq!(glass.ref_index, 1.45);
+        assert_eq!(glass.opacity, 0.25);
+        assert_eq!(glass.illum, Some(7));
+
+        let paint = &mats["red paint"];
+        assert_eq!(paint.diffuse, [1.0, 0.0, 0.0]);
+        assert_eq!(paint.specular, Material::default().specular);
+        assert_eq!(paint.opacity, 0.25);
+        assert_eq!(paint.illum, None);
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        assert!(read("newmtl\n").is_err());
+        assert!(read("newmtl a\nKd 1 x 0\n").is_err());
+        assert!(read("newmtl a\nKd 1 1\n").is_err());
+        assert!(read("newmtl a\nNs\n").is_err());
+        assert!(read("newmtl a\nillum -1\n").is_err());
+        assert!(matches!(
+            read("newmtl a\n\nd one\n"),
+            Err(Error::Wavefront(None, 3, _))
+        ));
+    }
+}
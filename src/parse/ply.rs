@@ -0,0 +1,645 @@
+//! Polygon file format.
+
+use core::str::SplitWhitespace;
+use nalgebra::{Point3, Unit, Vector3};
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::{
+    geom::{Mesh, Triangle},
+    Error,
+};
+
+/// Names of the vertex properties read as positions and normals.
+const STANDARD_PROPERTIES: [&str; 6] = ["x", "y", "z", "nx", "ny", "nz"];
+
+/// PLY data encoding.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PlyFormat {
+    /// Human-readable text.
+    Ascii,
+    /// Little-endian binary.
+    BinaryLittleEndian,
+    /// Big-endian binary.
+    BinaryBigEndian,
+}
+
+/// Mesh and additional vertex data read from a PLY file.
+pub struct Ply {
+    /// Triangulated geometry.
+    pub mesh: Mesh,
+    /// Additional vertex properties, at each of the corners of each triangle.
+    pub properties: HashMap<String, Vec<[f64; 3]>>,
+}
+
+/// Load a mesh, and any additional vertex properties, from a PLY file.
+///
+/// # Errors
+///
+/// Returns an error if the file can not be read,
+/// or if the contents are not a valid PLY mesh.
+#[inline]
+pub fn load(path: &Path) -> Result<Ply, Error> {
+    read(&fs::read(path).map_err(|err| Error::Io(path.to_path_buf(), err))?)
+        .map_err(|err| err.with_path(path))
+}
+
+/// Read a mesh, and any additional vertex properties, from PLY data.
+/// Polygons are triangulated, and face normals are used if vertex normals are not given.
+///
+/// # Errors
+///
+/// Returns an error if the data is not a valid PLY mesh.
+#[inline]
+pub fn read(bytes: &[u8]) -> Result<Ply, Error> {
+    let (header, body) = Header::read(bytes)?;
+
+    let mut source = match header.format {
+        PlyFormat::Ascii => Source::Ascii(
+            core::str::from_utf8(body)
+                .map_err(|err| error(&format!("Invalid ASCII data: {err}")))?
+                .split_whitespace(),
+        ),
+        PlyFormat::BinaryLittleEndian => Source::Binary(body, false),
+        PlyFormat::BinaryBigEndian => Source::Binary(body, true),
+    };
+
+    let mut verts = Vec::new();
+    let mut norms = Vec::new();
+    let mut extras: Vec<(String, Vec<f64>)> = Vec::new();
+    let mut faces = Vec::new();
+
+    for element in &header.elements {
+        match element.name.as_str() {
+            "vertex" => {
+                let index_of = |name: &str| element.props.iter().position(|p| p.name == name);
+                let pos_indices = [index_of("x"), index_of("y"), index_of("z")];
+                let norm_indices = [index_of("nx"), index_of("ny"), index_of("nz")];
+                let has_norms = norm_indices.iter().all(Option::is_some);
+
+                extras = element
+                    .props
+                    .iter()
+                    .filter(|p| p.list.is_none() && !STANDARD_PROPERTIES.contains(&p.name.as_str()))
+                    .map(|p| (p.name.clone(), Vec::new()))
+                    .collect();
+
+                for _ in 0..element.count {
+                    let values = source.read_element(element)?;
+                    let value = |index: Option<usize>| index.map_or(0.0, |i| values[i]);
+
+                    verts.push(Point3::new(
+                        value(pos_indices[0]),
+                        value(pos_indices[1]),
+                        value(pos_indices[2]),
+                    ));
+                    if has_norms {
+                        norms.push(Unit::new_normalize(Vector3::new(
+                            value(norm_indices[0]),
+                            value(norm_indices[1]),
+                            value(norm_indices[2]),
+                        )));
+                    }
+                    for &mut (ref name, ref mut data) in &mut extras {
+                        let index = element.props.iter().position(|p| &p.name == name);
+                        data.push(value(index));
+                    }
+                }
+
+                if pos_indices.iter().any(Option::is_none) {
+                    return Err(error("Vertex element is missing a position property."));
+                }
+            }
+            "face" => {
+                let list_index = element
+                    .props
+                    .iter()
+                    .position(|p| {
+                        p.list.is_some() && (p.name == "vertex_indices" || p.name == "vertex_index")
+                    })
+                    .ok_or_else(|| error("Face element is missing a vertex index list."))?;
+
+                for _ in 0..element.count {
+                    let mut lists = source.read_element_lists(element)?;
+                    faces.push(lists.swap_remove(list_index));
+                }
+            }
+            _ => {
+                for _ in 0..element.count {
+                    source.read_element_lists(element)?;
+                }
+            }
+        }
+    }
+
+    // Triangulate.
+    let mut tris = Vec::with_capacity(faces.len());
+    let mut properties: HashMap<_, _> = extras
+        .iter()
+        .map(|&(ref name, _)| (name.clone(), Vec::with_capacity(faces.len())))
+        .collect();
+    for face in faces {
+        if face.len() < 3 {
+            return Err(error("Faces require at least three vertices."));
+        }
+
+        let indices = face
+            .iter()
+            .map(|&x| {
+                let index = x as usize;
+                if x < 0.0 || index >= verts.len() {
+                    return Err(error(&format!("Invalid vertex index: {x}")));
+                }
+                Ok(index)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for i in 1..(indices.len() - 1) {
+            let corners = [indices[0], indices[i], indices[i + 1]];
+            let tri_verts = corners.map(|vi| verts[vi]);
+            let plane_norm = (tri_verts[0] - tri_verts[2]).cross(&(tri_verts[1] - tri_verts[0]));
+            if plane_norm.norm_squared() <= 0.0 {
+                continue;
+            }
+
+            let tri_norms = if norms.is_empty() {
+                [Unit::new_normalize(plane_norm); 3]
+            } else {
+                corners.map(|vi| norms[vi])
+            };
+            tris.push(Triangle::new(tri_verts, tri_norms));
+
+            for &(ref name, ref data) in &extras {
+                properties
+                    .get_mut(name)
+                    .expect("Failed to initialise vertex property.")
+                    .push(corners.map(|vi| data[vi]));
+            }
+        }
+    }
+
+    if tris.is_empty() {
+        return Err(error("No valid faces found."));
+    }
+
+    Ok(Ply {
+        mesh: Mesh::new(tris),
+        properties,
+    })
+}
+
+/// Save a mesh, and any additional vertex properties, as a PLY file.
+/// Each property list must hold the values at the corners of each triangle of the mesh.
+///
+/// # Errors
+///
+/// Returns an error if the file can not be written.
+#[inline]
+pub fn save(
+    mesh: &Mesh,
+    properties: &HashMap<String, Vec<[f64; 3]>>,
+    format: PlyFormat,
+    path: &Path,
+) -> Result<(), Error> {
+    fs::write(path, write(mesh, properties, format))
+        .map_err(|err| Error::Io(path.to_path_buf(), err))
+}
+
+/// Write a mesh, and any additional vertex properties, as PLY data.
+/// Each property list must hold the values at the corners of each triangle of the mesh.
+/// Triangle corners sharing a position, normal and property values are merged into a single vertex.
+#[inline]
+#[must_use]
+pub fn write(
+    mesh: &Mesh,
+    properties: &HashMap<String, Vec<[f64; 3]>>,
+    format: PlyFormat,
+) -> Vec<u8> {
+    let mut names: Vec<_> = properties.keys().collect();
+    names.sort();
+    debug_assert!(names
+        .iter()
+        .all(|name| properties[*name].len() == mesh.tris.len()));
+
+    // Merge shared vertices.
+    let mut vertices: Vec<Vec<f64>> = Vec::new();
+    let mut vertex_indices: HashMap<Vec<u64>, usize> = HashMap::new();
+    let mut faces = Vec::with_capacity(mesh.tris.len());
+    for (ti, tri) in mesh.tris.iter().enumerate() {
+        let mut face = [0; 3];
+        for (corner, index) in face.iter_mut().enumerate() {
+            let mut vertex = Vec::with_capacity(6 + names.len());
+            vertex.extend(tri.verts[corner].iter());
+            vertex.extend(tri.norms[corner].iter());
+            vertex.extend(names.iter().map(|name| properties[*name][ti][corner]));
+
+            let key = vertex.iter().map(|x| x.to_bits()).collect();
+            *index = *vertex_indices.entry(key).or_insert_with(|| {
+                vertices.push(vertex);
+                vertices.len() - 1
+            });
+        }
+        faces.push(face);
+    }
+
+    // Header.
+    let format_name = match format {
+        PlyFormat::Ascii => "ascii",
+        PlyFormat::BinaryLittleEndian => "binary_little_endian",
+        PlyFormat::BinaryBigEndian => "binary_big_endian",
+    };
+    let mut header = format!(
+        "ply\nformat {format_name} 1.0\ncomment Written by arctk\nelement vertex {}\n",
+        vertices.len()
+    );
+    for name in STANDARD_PROPERTIES
+        .iter()
+        .copied()
+        .chain(names.iter().map(|n| n.as_str()))
+    {
+        header.push_str(&format!("property float {name}\n"));
+    }
+    header.push_str(&format!(
+        "element face {}\nproperty list uchar int vertex_indices\nend_header\n",
+        faces.len()
+    ));
+
+    // Body.
+    let mut data = header.into_bytes();
+    match format {
+        PlyFormat::Ascii => {
+            for vertex in &vertices {
+                let line: Vec<_> = vertex.iter().map(|x| format!("{}", *x as f32)).collect();
+                data.extend(line.join(" ").bytes());
+                data.push(b'\n');
+            }
+            for face in &faces {
+                data.extend(format!("3 {} {} {}\n", face[0], face[1], face[2]).bytes());
+            }
+        }
+        PlyFormat::BinaryLittleEndian | PlyFormat::BinaryBigEndian => {
+            let big_endian = format == PlyFormat::BinaryBigEndian;
+            for vertex in &vertices {
+                for x in vertex {
+                    let x = *x as f32;
+                    data.extend(if big_endian {
+                        x.to_be_bytes()
+                    } else {
+                        x.to_le_bytes()
+                    });
+                }
+            }
+            for face in &faces {
+                data.push(3);
+                for index in face {
+                    let index = *index as i32;
+                    data.extend(if big_endian {
+                        index.to_be_bytes()
+                    } else {
+                        index.to_le_bytes()
+                    });
+                }
+            }
+        }
+    }
+
+    data
+}
+
+/// Create a parsing error with the given description.
+#[inline]
+#[must_use]
+fn error(msg: &str) -> Error {
+    Error::Ply(None, msg.to_owned())
+}
+
+/// Scalar property types.
+#[derive(Clone, Copy)]
+enum Scalar {
+    /// Signed 8-bit integer.
+    I8,
+    /// Unsigned 8-bit integer.
+    U8,
+    /// Signed 16-bit integer.
+    I16,
+    /// Unsigned 16-bit integer.
+    U16,
+    /// Signed 32-bit integer.
+    I32,
+    /// Unsigned 32-bit integer.
+    U32,
+    /// 32-bit float.
+    F32,
+    /// 64-bit float.
+    F64,
+}
+
+impl Scalar {
+    /// Parse a type name.
+    #[inline]
+    fn new(name: &str) -> Result<Self, Error> {
+        Ok(match name {
+            "char" | "int8" => Self::I8,
+            "uchar" | "uint8" => Self::U8,
+            "short" | "int16" => Self::I16,
+            "ushort" | "uint16" => Self::U16,
+            "int" | "int32" => Self::I32,
+            "uint" | "uint32" => Self::U32,
+            "float" | "float32" => Self::F32,
+            "double" | "float64" => Self::F64,
+            _ => return Err(error(&format!("Unknown property type: {name}"))),
+        })
+    }
+
+    /// Size of the type in bytes.
+    #[inline]
+    #[must_use]
+    const fn size(self) -> usize {
+        match self {
+            Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+}
+
+/// Element property.
+struct Property {
+    /// Name.
+    name: String,
+    /// Optional list length type, if the property is a list.
+    list: Option<Scalar>,
+    /// Value type.
+    value: Scalar,
+}
+
+/// Element declaration.
+struct Element {
+    /// Name.
+    name: String,
+    /// Number of instances.
+    count: usize,
+    /// Properties.
+    props: Vec<Property>,
+}
+
+/// File header.
+struct Header {
+    /// Data encoding.
+    format: PlyFormat,
+    /// Element declarations, in order.
+    elements: Vec<Element>,
+}
+
+impl Header {
+    /// Read the header, and return it along with the remaining body data.
+    #[inline]
+    fn read(bytes: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let marker = b"end_header";
+        let end = bytes
+            .windows(marker.len())
+            .position(|w| w == marker)
+            .ok_or_else(|| error("Missing end of header."))?;
+        let mut body_start = end + marker.len();
+        while body_start < bytes.len() && bytes[body_start] != b'\n' {
+            body_start += 1;
+        }
+        body_start = (body_start + 1).min(bytes.len());
+
+        let text = core::str::from_utf8(&bytes[..end])
+            .map_err(|err| error(&format!("Invalid header: {err}")))?;
+        let mut lines = text.lines();
+        if lines.next().map(str::trim) != Some("ply") {
+            return Err(error("Missing PLY identifier."));
+        }
+
+        let mut format = None;
+        let mut elements: Vec<Element> = Vec::new();
+        for line in lines {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("format") => {
+                    format = Some(match words.next() {
+                        Some("ascii") => PlyFormat::Ascii,
+                        Some("binary_little_endian") => PlyFormat::BinaryLittleEndian,
+                        Some("binary_big_endian") => PlyFormat::BinaryBigEndian,
+                        _ => return Err(error(&format!("Unknown format: {line}"))),
+                    });
+                }
+                Some("element") => {
+                    let name = words.next().ok_or_else(|| error("Missing element name."))?;
+                    let count = words
+                        .next()
+                        .and_then(|w| w.parse().ok())
+                        .ok_or_else(|| error(&format!("Invalid element count: {line}")))?;
+                    elements.push(Element {
+                        name: name.to_owned(),
+                        count,
+                        props: Vec::new(),
+                    });
+                }
+                Some("property") => {
+                    let element = elements
+                        .last_mut()
+                        .ok_or_else(|| error("Property declared before any element."))?;
+                    let prop = match words.next() {
+                        Some("list") => {
+                            let list = Scalar::new(words.next().unwrap_or_default())?;
+                            let value = Scalar::new(words.next().unwrap_or_default())?;
+                            Property {
+                                name: read_name(words, line)?,
+                                list: Some(list),
+                                value,
+                            }
+                        }
+                        Some(ty) => Property {
+                            value: Scalar::new(ty)?,
+                            name: read_name(words, line)?,
+                            list: None,
+                        },
+                        None => return Err(error("Missing property type.")),
+                    };
+                    element.props.push(prop);
+                }
+                _ => {}
+            }
+        }
+
+        let format = format.ok_or_else(|| error("Missing format declaration."))?;
+
+        Ok((Self { format, elements }, &bytes[body_start..]))
+    }
+}
+
+/// Read a property name.
+#[inline]
+fn read_name(mut words: SplitWhitespace, line: &str) -> Result<String, Error> {
+    words
+        .next()
+        .map(str::to_owned)
+        .ok_or_else(|| error(&format!("Missing property name: {line}")))
+}
+
+/// Body data source.
+enum Source<'a> {
+    /// Whitespace separated text.
+    Ascii(SplitWhitespace<'a>),
+    /// Binary data, and whether it is big-endian.
+    Binary(&'a [u8], bool),
+}
+
+impl Source<'_> {
+    /// Read a single value of the given type.
+    #[inline]
+    fn read(&mut self, ty: Scalar) -> Result<f64, Error> {
+        match *self {
+            Self::Ascii(ref mut words) => {
+                let word = words
+                    .next()
+                    .ok_or_else(|| error("Unexpected end of data."))?;
+                word.parse::<f64>().map_err(|err| {
+                    error(&format!(
+                        "Unable to parse value from string: {word} ({err})"
+                    ))
+                })
+            }
+            Self::Binary(ref mut bytes, big_endian) => {
+                let size = ty.size();
+                if bytes.len() < size {
+                    return Err(error("Unexpected end of data."));
+                }
+                let (head, tail) = bytes.split_at(size);
+                *bytes = tail;
+
+                let mut buf = [0; 8];
+                buf[..size].copy_from_slice(head);
+                if big_endian {
+                    buf[..size].reverse();
+                }
+
+                Ok(match ty {
+                    Scalar::I8 => f64::from(buf[0] as i8),
+                    Scalar::U8 => f64::from(buf[0]),
+                    Scalar::I16 => f64::from(i16::from_le_bytes([buf[0], buf[1]])),
+                    Scalar::U16 => f64::from(u16::from_le_bytes([buf[0], buf[1]])),
+                    Scalar::I32 => f64::from(i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]])),
+                    Scalar::U32 => f64::from(u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]])),
+                    Scalar::F32 => f64::from(f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]])),
+                    Scalar::F64 => f64::from_le_bytes(buf),
+                })
+            }
+        }
+    }
+
+    /// Read a single element instance, returning the value of each scalar property.
+    /// List properties are read and discarded, and recorded as zero.
+    #[inline]
+    fn read_element(&mut self, element: &Element) -> Result<Vec<f64>, Error> {
+        element
+            .props
+            .iter()
+            .map(|prop| {
+                if let Some(len_ty) = prop.list {
+                    let len = self.read(len_ty)? as usize;
+                    for _ in 0..len {
+                        self.read(prop.value)?;
+                    }
+                    Ok(0.0)
+                } else {
+                    self.read(prop.value)
+                }
+            })
+            .collect()
+    }
+
+    /// Read a single element instance, returning the values of each property as a list.
+    #[inline]
+    fn read_element_lists(&mut self, element: &Element) -> Result<Vec<Vec<f64>>, Error> {
+        element
+            .props
+            .iter()
+            .map(|prop| {
+                if let Some(len_ty) = prop.list {
+                    let len = self.read(len_ty)? as usize;
+                    (0..len).map(|_| self.read(prop.value)).collect()
+                } else {
+                    Ok(vec![self.read(prop.value)?])
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geom::Cube;
+
+    #[test]
+    fn write_read_round_trip() {
+        let mesh = Mesh::new(
+            Cube::new(Point3::new(-1.0, -2.0, -3.0), Point3::new(0.5, 0.25, 0.125)).tris(),
+        );
+        let temps: Vec<_> = (0..mesh.tris.len())
+            .map(|ti| [ti as f64, 0.5, -0.25])
+            .collect();
+        let properties = HashMap::from([("temperature".to_owned(), temps)]);
+
+        for format in [
+            PlyFormat::Ascii,
+            PlyFormat::BinaryLittleEndian,
+            PlyFormat::BinaryBigEndian,
+        ] {
+            let ply =
+                read(&write(&mesh, &properties, format)).expect("Failed to read written data.");
+
+            assert_eq!(ply.mesh.tris.len(), mesh.tris.len());
+            for (a, b) in mesh.tris.iter().zip(&ply.mesh.tris) {
+                assert_eq!(a.verts, b.verts);
+                assert_eq!(a.norms, b.norms);
+            }
+            assert_eq!(ply.properties["temperature"], properties["temperature"]);
+        }
+    }
+
+    #[test]
+    fn polygons_are_triangulated() {
+        let s = "ply\nformat ascii 1.0\nelement vertex 4\n\
+                 property float x\nproperty float y\nproperty float z\n\
+                 element face 1\nproperty list uchar int vertex_indices\nend_header\n\
+                 0 0 0\n1 0 0\n1 1 0\n0 1 0\n4 0 1 2 3\n";
+
+        let ply = read(s.as_bytes()).expect("Failed to read data.");
+
+        assert_eq!(ply.mesh.tris.len(), 2);
+        for tri in &ply.mesh.tris {
+            assert_eq!(tri.norms, [Vector3::z_axis(); 3]);
+        }
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        let mesh = Mesh::new(Cube::new(Point3::origin(), Point3::new(1.0, 1.0, 1.0)).tris());
+        let bytes = write(&mesh, &HashMap::new(), PlyFormat::BinaryLittleEndian);
+
+        assert!(read(&bytes[..bytes.len() - 1]).is_err());
+        assert!(read(&bytes[3..]).is_err());
+        assert!(read(b"ply\nformat ascii 1.0\nelement vertex 1\n").is_err());
+        assert!(read(
+            b"ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float y\n\
+              property float z\nelement face 1\nproperty list uchar int vertex_indices\n\
+              end_header\n0 0 0\n1 0 0\n0 1 0\n3 0 1 3\n"
+        )
+        .is_err());
+
+        // Element counts are not trusted to size allocations.
+        assert!(read(
+            b"ply\nformat binary_little_endian 1.0\nelement vertex 4000000000000000000\n\
+              property float x\nproperty float y\nproperty float z\n\
+              element face 4000000000000000000\nproperty list uchar int vertex_indices\n\
+              end_header\n"
+        )
+        .is_err());
+    }
+}
@@ -6,9 +6,16 @@ use png::{BitDepth, ColorType, Encoder};
 use slice_of_array::SliceFlatExt;
 use std::{fs::File, io::BufWriter, path::Path};
 
+use crate::Error;
+
 /// Save an array as a PNG file.
+///
+/// # Errors
+///
+/// Returns an error if the file can not be created,
+/// or if the image data can not be encoded.
 #[inline]
-pub fn save(image: ArrayView2<LinSrgba>, path: &Path) {
+pub fn save(image: ArrayView2<LinSrgba>, path: &Path) -> Result<(), Error> {
     // Convert to png rgb space.
     let res = image.shape();
     let mut data: Array2<[u8; 4]> = Array2::from_elem((res[1], res[0]), [0; 4]);
@@ -19,15 +26,13 @@ pub fn save(image: ArrayView2<LinSrgba>, path: &Path) {
     }
 
     // Save data at path.
-    let file = File::create(path)
-        .unwrap_or_else(|_| panic!("Failed to create PNG file: {}", path.display()));
+    let file = File::create(path).map_err(|err| Error::Io(path.to_path_buf(), err))?;
     let w = BufWriter::new(file);
     let mut encoder = Encoder::new(w, res[0] as u32, res[1] as u32);
     encoder.set_color(ColorType::Rgba);
     encoder.set_depth(BitDepth::Eight);
     encoder
         .write_header()
-        .expect("Failed to write PNG header.")
-        .write_image_data(data.into_raw_vec().flat())
-        .expect("Failed to write PNG data.");
+        .and_then(|mut writer| writer.write_image_data(data.into_raw_vec().flat()))
+        .map_err(|err| Error::Png(path.to_path_buf(), err))
 }
@@ -0,0 +1,460 @@
+//! Stereolithography.
+
+use nalgebra::{Point3, Unit, Vector3};
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::{
+    geom::{Mesh, Triangle},
+    Error,
+};
+
+/// Size of the binary header in bytes.
+const HEADER_SIZE: usize = 80;
+/// Size of a binary facet record in bytes.
+const FACET_SIZE: usize = 50;
+
+/// Load a mesh from an ASCII or binary STL file.
+/// Triangle normals are set to the face normal.
+///
+/// # Errors
+///
+/// Returns an error if the file can not be read,
+/// or if the contents are not a valid STL mesh.
+#[inline]
+pub fn load(path: &Path) -> Result<Mesh, Error> {
+    read(&fs::read(path).map_err(|err| Error::Io(path.to_path_buf(), err))?)
+        .map_err(|err| err.with_path(path))
+}
+
+/// Load a mesh from an ASCII or binary STL file, with smoothed normals.
+/// Each vertex is merged with the nearest earlier vertex within the weld distance, if any,
+/// and the normals of neighbouring faces within the smoothing angle (rad) are averaged.
+///
+/// # Errors
+///
+/// Returns an error if the file can not be read,
+/// or if the contents are not a valid STL mesh.
+#[inline]
+pub fn load_smoothed(path: &Path, weld_dist: f64, smooth_angle: f64) -> Result<Mesh, Error> {
+    read_smoothed(
+        &fs::read(path).map_err(|err| Error::Io(path.to_path_buf(), err))?,
+        weld_dist,
+        smooth_angle,
+    )
+    .map_err(|err| err.with_path(path))
+}
+
+/// Read a mesh from ASCII or binary STL data.
+/// Triangle normals are set to the face normal.
+///
+/// # Errors
+///
+/// Returns an error if the data is not a valid STL mesh.
+#[inline]
+pub fn read(bytes: &[u8]) -> Result<Mesh, Error> {
+    let tris = read_facets(bytes)?
+        .into_iter()
+        .map(|verts| {
+            let norm = face_normal(&verts);
+            Triangle::new(verts, [norm; 3])
+        })
+        .collect();
+
+    Ok(Mesh::new(tris))
+}
+
+/// Read a mesh from ASCII or binary STL data, with smoothed normals.
+/// Each vertex is merged with the nearest earlier vertex within the weld distance, if any,
+/// and the normals of neighbouring faces within the smoothing angle (rad) are averaged.
+///
+/// # Errors
+///
+/// Returns an error if the data is not a valid STL mesh.
+#[inline]
+pub fn read_smoothed(bytes: &[u8], weld_dist: f64, smooth_angle: f64) -> Result<Mesh, Error> {
+    debug_assert!(weld_dist >= 0.0);
+    debug_assert!(smooth_angle >= 0.0);
+
+    let facets = read_facets(bytes)?;
+
+    // Weld vertices.
+    let mut verts = Vec::new();
+    let mut cells = HashMap::new();
+    let mut faces = Vec::with_capacity(facets.len());
+    for facet in &facets {
+        faces.push(facet.map(|pos| weld(&pos, weld_dist, &mut verts, &mut cells)));
+    }
+
+    // Area-weighted face normals and the faces adjacent to each vertex.
+    let mut area_norms = Vec::with_capacity(faces.len());
+    let mut adjacent = vec![Vec::new(); verts.len()];
+    for (fi, face) in faces.iter().enumerate() {
+        let [a, b, c] = face.map(|vi| verts[vi]);
+        area_norms.push((b - a).cross(&(c - a)));
+        for &vi in face {
+            adjacent[vi].push(fi);
+        }
+    }
+
+    let min_cos = smooth_angle.cos();
+    let mut tris = Vec::with_capacity(faces.len());
+    for (fi, face) in faces.iter().enumerate() {
+        let tri_verts = face.map(|vi| verts[vi]);
+        if area_norms[fi].norm_squared() <= 0.0 {
+            continue;
+        }
+        let face_norm = Unit::new_normalize(area_norms[fi]);
+
+        let norms = face.map(|vi| {
+            let sum = adjacent[vi]
+                .iter()
+                .map(|&fj| area_norms[fj])
+                .filter(|norm| {
+                    norm.norm_squared() > 0.0 && face_norm.dot(&norm.normalize()) >= min_cos
+                })
+                .fold(Vector3::zeros(), |acc, norm| acc + norm);
+            Unit::new_normalize(sum)
+        });
+
+        tris.push(Triangle::new(tri_verts, norms));
+    }
+
+    if tris.is_empty() {
+        return Err(Error::Stl(None, "No valid facets found.".to_owned()));
+    }
+
+    Ok(Mesh::new(tris))
+}
+
+/// Read the vertex positions of each non-degenerate facet from ASCII or binary STL data.
+#[inline]
+fn read_facets(bytes: &[u8]) -> Result<Vec<[Point3<f64>; 3]>, Error> {
+    let facets = if is_binary(bytes) {
+        read_binary(bytes)?
+    } else if bytes
+        .iter()
+        .skip_while(|b| b.is_ascii_whitespace())
+        .take(5)
+        .eq(b"solid")
+    {
+        read_ascii(
+            core::str::from_utf8(bytes)
+                .map_err(|err| Error::Stl(None, format!("Invalid ASCII data: {err}")))?,
+        )?
+    } else {
+        return Err(Error::Stl(None, "Unrecognised STL format.".to_owned()));
+    };
+
+    let facets: Vec<_> = facets
+        .into_iter()
+        .filter(|verts| face_normal_vec(verts).norm_squared() > 0.0)
+        .collect();
+
+    if facets.is_empty() {
+        return Err(Error::Stl(None, "No valid facets found.".to_owned()));
+    }
+
+    Ok(facets)
+}
+
+/// Determine if the data is in the binary format.
+/// Binary files may also begin with "solid", so the expected size is checked.
+/// Trailing bytes, such as padding, are allowed.
+#[inline]
+#[must_use]
+fn is_binary(bytes: &[u8]) -> bool {
+    if bytes.len() < HEADER_SIZE + 4 {
+        return false;
+    }
+
+    let num_facets = read_u32(bytes, HEADER_SIZE) as usize;
+    bytes.len() >= HEADER_SIZE + 4 + (num_facets * FACET_SIZE)
+}
+
+/// Read the facets of binary STL data.
+#[inline]
+fn read_binary(bytes: &[u8]) -> Result<Vec<[Point3<f64>; 3]>, Error> {
+    let num_facets = read_u32(bytes, HEADER_SIZE) as usize;
+    if bytes.len() < HEADER_SIZE + 4 + (num_facets * FACET_SIZE) {
+        return Err(Error::Stl(
+            None,
+            "Unexpected end of binary data.".to_owned(),
+        ));
+    }
+
+    let mut facets = Vec::with_capacity(num_facets);
+    for n in 0..num_facets {
+        // Skip the stored normal.
+        let start = HEADER_SIZE + 4 + (n * FACET_SIZE) + 12;
+
+        let mut verts = [Point3::origin(); 3];
+        for (i, vert) in verts.iter_mut().enumerate() {
+            let offset = start + (i * 12);
+            *vert = Point3::new(
+                f64::from(read_f32(bytes, offset)),
+                f64::from(read_f32(bytes, offset + 4)),
+                f64::from(read_f32(bytes, offset + 8)),
+            );
+        }
+        facets.push(verts);
+    }
+
+    Ok(facets)
+}
+
+/// Read the facets of ASCII STL data.
+/// Facets with more than three vertices are fan triangulated.
+#[inline]
+fn read_ascii(s: &str) -> Result<Vec<[Point3<f64>; 3]>, Error> {
+    let mut facets = Vec::new();
+    let mut verts = Vec::with_capacity(3);
+
+    for (index, line) in s.lines().enumerate() {
+        let line_num = index + 1;
+        let mut words = line.split_whitespace();
+
+        match words.next() {
+            Some("facet") => verts.clear(),
+            Some("vertex") => {
+                let mut pos = [0.0; 3];
+                for x in &mut pos {
+                    let word = words.next().ok_or_else(|| {
+                        Error::Stl(None, format!("Missing vertex value (line {line_num})."))
+                    })?;
+                    *x = word.parse::<f64>().map_err(|err| {
+                        Error::Stl(
+                            None,
+                            format!(
+                                "Unable to parse vertex value: {word} ({err}, line {line_num})."
+                            ),
+                        )
+                    })?;
+                }
+                verts.push(Point3::from(pos));
+            }
+            Some("endfacet") => {
+                if verts.len() < 3 {
+                    return Err(Error::Stl(
+                        None,
+                        format!("Facet has fewer than three vertices (line {line_num})."),
+                    ));
+                }
+                for i in 1..(verts.len() - 1) {
+                    facets.push([verts[0], verts[i], verts[i + 1]]);
+                }
+                verts.clear();
+            }
+            _ => {}
+        }
+    }
+
+    Ok(facets)
+}
+
+/// Read a little-endian `u32` at the given byte offset.
+#[inline]
+#[must_use]
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    let mut buf = [0; 4];
+    buf.copy_from_slice(&bytes[offset..offset + 4]);
+    u32::from_le_bytes(buf)
+}
+
+/// Read a little-endian `f32` at the given byte offset.
+#[inline]
+#[must_use]
+fn read_f32(bytes: &[u8], offset: usize) -> f32 {
+    let mut buf = [0; 4];
+    buf.copy_from_slice(&bytes[offset..offset + 4]);
+    f32::from_le_bytes(buf)
+}
+
+/// Calculate the un-normalised normal of a facet, following the right-hand rule.
+#[inline]
+#[must_use]
+fn face_normal_vec(verts: &[Point3<f64>; 3]) -> Vector3<f64> {
+    (verts[1] - verts[0]).cross(&(verts[2] - verts[0]))
+}
+
+/// Calculate the normal of a facet, following the right-hand rule.
+#[inline]
+#[must_use]
+fn face_normal(verts: &[Point3<f64>; 3]) -> Unit<Vector3<f64>> {
+    Unit::new_normalize(face_normal_vec(verts))
+}
+
+/// Determine the welding grid cell of a vertex position.
+/// Cells are as wide as the weld distance, so any vertex within the weld distance lies in a neighbouring cell.
+/// Without a weld distance, each distinct position has its own cell.
+#[inline]
+#[must_use]
+fn weld_cell(pos: &Point3<f64>, weld_dist: f64) -> [i64; 3] {
+    if weld_dist > 0.0 {
+        pos.coords.map(|x| (x / weld_dist).floor() as i64).into()
+    } else {
+        pos.coords.map(|x| x.to_bits() as i64).into()
+    }
+}
+
+/// Find the index of the nearest welded vertex within the weld distance of a position,
+/// adding the position as a new vertex if there is none.
+#[inline]
+fn weld(
+    pos: &Point3<f64>,
+    weld_dist: f64,
+    verts: &mut Vec<Point3<f64>>,
+    cells: &mut HashMap<[i64; 3], Vec<usize>>,
+) -> usize {
+    let cell = weld_cell(pos, weld_dist);
+
+    let nearest = if weld_dist > 0.0 {
+        (0..27)
+            .filter_map(|n: i64| {
+                cells.get(&[
+                    cell[0] + n % 3 - 1,
+                    cell[1] + (n / 3) % 3 - 1,
+                    cell[2] + n / 9 - 1,
+                ])
+            })
+            .flatten()
+            .map(|&vi| (vi, nalgebra::distance(&verts[vi], pos)))
+            .filter(|&(_, dist)| dist <= weld_dist)
+            .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)))
+            .map(|(vi, _)| vi)
+    } else {
+        cells.get(&cell).map(|indices| indices[0])
+    };
+
+    nearest.unwrap_or_else(|| {
+        verts.push(*pos);
+        cells
+            .entry(cell)
+            .or_insert_with(Vec::new)
+            .push(verts.len() - 1);
+        verts.len() - 1
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Facets of an octahedron with unit vertex distances, wound outwards.
+    fn octahedron() -> Vec<[[f32; 3]; 3]> {
+        let mut facets = Vec::new();
+        for &x in &[1.0, -1.0] {
+            for &y in &[1.0, -1.0] {
+                for &z in &[1.0, -1.0] {
+                    let facet = [[x, 0.0, 0.0], [0.0, y, 0.0], [0.0, 0.0, z]];
+                    if x * y * z > 0.0 {
+                        facets.push(facet);
+                    } else {
+                        facets.push([facet[0], facet[2], facet[1]]);
+                    }
+                }
+            }
+        }
+        facets
+    }
+
+    /// Write facets as ASCII STL data.
+    fn ascii(facets: &[[[f32; 3]; 3]]) -> Vec<u8> {
+        let mut s = "solid test\n".to_owned();
+        for facet in facets {
+            s.push_str("facet normal 0 0 0\nouter loop\n");
+            for v in facet {
+                s.push_str(&format!("vertex {} {} {}\n", v[0], v[1], v[2]));
+            }
+            s.push_str("endloop\nendfacet\n");
+        }
+        s.push_str("endsolid test\n");
+        s.into_bytes()
+    }
+
+    /// Write facets as binary STL data.
+    fn binary(facets: &[[[f32; 3]; 3]]) -> Vec<u8> {
+        let mut bytes = vec![0; HEADER_SIZE];
+        bytes.extend((facets.len() as u32).to_le_bytes());
+        for facet in facets {
+            bytes.extend([0; 12]);
+            for x in facet.iter().flatten() {
+                bytes.extend(x.to_le_bytes());
+            }
+            bytes.extend([0; 2]);
+        }
+        bytes
+    }
+
+    #[test]
+    fn ascii_and_binary_agree() {
+        let facets = octahedron();
+
+        let a = read(&ascii(&facets)).expect("Failed to read ASCII data.");
+        let b = read(&binary(&facets)).expect("Failed to read binary data.");
+
+        assert_eq!(a.tris.len(), facets.len());
+        for ((ta, tb), facet) in a.tris.iter().zip(&b.tris).zip(&facets) {
+            assert_eq!(ta.verts, tb.verts);
+            assert_eq!(ta.verts[0].x, f64::from(facet[0][0]));
+            assert!(ta.plane_norm.dot(&ta.centre().coords) > 0.0);
+        }
+    }
+
+    #[test]
+    fn smoothing_respects_the_angle() {
+        let bytes = binary(&octahedron());
+
+        let smooth =
+            read_smoothed(&bytes, 1.0e-6, 120.0_f64.to_radians()).expect("Failed to read.");
+        let sharp = read_smoothed(&bytes, 1.0e-6, 10.0_f64.to_radians()).expect("Failed to read.");
+
+        for (tri_smooth, tri_sharp) in smooth.tris.iter().zip(&sharp.tris) {
+            for ((vert, norm_smooth), norm_sharp) in tri_smooth
+                .verts
+                .iter()
+                .zip(&tri_smooth.norms)
+                .zip(&tri_sharp.norms)
+            {
+                assert!((norm_smooth.dot(&vert.coords) - 1.0).abs() < 1.0e-12);
+                assert!((norm_sharp.dot(&tri_sharp.plane_norm) - 1.0).abs() < 1.0e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn vertices_within_the_weld_distance_are_merged() {
+        // The copies of the shared vertex round to different points on a grid as wide as the weld distance.
+        let facets = [
+            [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.149_99, 1.0, 0.0]],
+            [[1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.150_01, 1.0, 0.0]],
+        ];
+        let bytes = binary(&facets);
+
+        let welded = read_smoothed(&bytes, 0.1, 0.1).expect("Failed to read.");
+        assert_eq!(welded.tris[1].verts[2], welded.tris[0].verts[2]);
+
+        let separate = read_smoothed(&bytes, 1.0e-6, 0.1).expect("Failed to read.");
+        assert_ne!(separate.tris[1].verts[2], separate.tris[0].verts[2]);
+    }
+
+    #[test]
+    fn binary_data_may_be_padded() {
+        let facets = octahedron();
+        let mut bytes = binary(&facets);
+        bytes.extend([0; 16]);
+
+        assert_eq!(
+            read(&bytes).expect("Failed to read.").tris.len(),
+            facets.len()
+        );
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        let bytes = binary(&octahedron());
+        assert!(read(&bytes[..bytes.len() - 1]).is_err());
+        assert!(read(b"solid test\nfacet normal 0 0 0\nouter loop\nvertex 0 0 x\n").is_err());
+        assert!(read(b"solid test\nendsolid test\n").is_err());
+        assert!(read(b"").is_err());
+    }
+}
@@ -1,165 +1,613 @@
 //! Wavefront.
 
-use nalgebra::{Point3, Unit, Vector3};
-use std::{fs, path::Path};
+use core::{
+    fmt::Display,
+    str::{FromStr, SplitWhitespace},
+};
+use nalgebra::{Point2, Point3, Unit, Vector3};
+use std::{collections::HashMap, fs, path::Path};
 
-use crate::geom::{Mesh, Triangle};
+use crate::{
+    geom::{Mesh, Triangle},
+    Error,
+};
+
+/// Name given to faces which are not part of a named object or group.
+const DEFAULT_GROUP_NAME: &str = "default";
+
+/// Named group of faces read from a wavefront file.
+pub struct Group {
+    /// Object or group name.
+    pub name: String,
+    /// Optional material name.
+    pub material: Option<String>,
+    /// Triangulated geometry.
+    pub mesh: Mesh,
+    /// Optional texture coordinates of each triangle's vertices.
+    pub tex_coords: Vec<Option<[Point2<f64>; 3]>>,
+}
 
 /// Load a mesh from a wavefront file.
+/// All groups are merged into a single mesh.
+///
+/// # Errors
+///
+/// Returns an error if the file can not be read,
+/// or if the contents are not a valid wavefront mesh.
 #[inline]
-#[must_use]
-pub fn load(path: &Path) -> Mesh {
-    read(
-        &fs::read_to_string(path)
-            .unwrap_or_else(|_| panic!("Failed to read file: {}.", path.display())),
-    )
+pub fn load(path: &Path) -> Result<Mesh, Error> {
+    read(&fs::read_to_string(path).map_err(|err| Error::Io(path.to_path_buf(), err))?)
+        .map_err(|err| err.with_path(path))
+}
+
+/// Load the named groups of a wavefront file as separate meshes.
+///
+/// # Errors
+///
+/// Returns an error if the file can not be read,
+/// or if the contents are not a valid wavefront mesh.
+#[inline]
+pub fn load_groups(path: &Path) -> Result<Vec<Group>, Error> {
+    read_groups(&fs::read_to_string(path).map_err(|err| Error::Io(path.to_path_buf(), err))?)
+        .map_err(|err| err.with_path(path))
 }
 
 /// Read a Mesh from a wavefront string.
+/// All groups are merged into a single mesh.
+///
+/// # Errors
+///
+/// Returns an error if the string is not a valid wavefront mesh.
 #[inline]
-#[must_use]
-pub fn read(s: &str) -> Mesh {
-    let verts = read_vertices(s);
-    let norms = read_normals(s);
-    let faces = read_faces(s);
+pub fn read(s: &str) -> Result<Mesh, Error> {
+    let tris = read_groups(s)?
+        .into_iter()
+        .flat_map(|group| group.mesh.tris)
+        .collect();
+
+    Ok(Mesh::new(tris))
+}
+
+/// Read the named groups of a wavefront string as separate meshes.
+/// Polygons are triangulated, and missing normals are calculated from the
+/// surrounding faces when smoothing is enabled, or from the face plane otherwise.
+/// Degenerate faces, which have no area, are dropped.
+///
+/// # Errors
+///
+/// Returns an error if the string is not a valid wavefront mesh.
+#[inline]
+pub fn read_groups(s: &str) -> Result<Vec<Group>, Error> {
+    let data = Data::read(s)?;
+    let smooth_norms = data.smooth_normals()?;
+
+    let mut groups = Vec::with_capacity(data.groups.len());
+    for &(ref name, ref material, ref faces) in &data.groups {
+        let mut tris = Vec::with_capacity(faces.len());
+        let mut tex_coords = Vec::with_capacity(faces.len());
 
-    let mut tris = Vec::with_capacity(faces.len());
-    for face in faces {
-        tris.push(Triangle::new(
-            [verts[(face.0).0], verts[(face.0).1], verts[(face.0).2]],
-            [norms[(face.1).0], norms[(face.1).1], norms[(face.1).2]],
-        ));
+        for face in faces {
+            let verts = data.face_verts(face)?;
+            let area_norm = (verts[0] - verts[2]).cross(&(verts[1] - verts[0]));
+            if area_norm.norm_squared() <= 0.0 {
+                continue;
+            }
+            let plane_norm = Unit::new_normalize(area_norm);
+
+            let mut norms = [plane_norm; 3];
+            for (norm, corner) in norms.iter_mut().zip(face.corners.iter()) {
+                if let Some(ni) = corner.norm {
+                    *norm = *lookup(&data.norms, ni, face.line_num, "normal")?;
+                } else if face.smooth {
+                    *norm = smooth_norms[corner.vert];
+                }
+            }
+
+            let coords = if face.corners.iter().all(|corner| corner.tex.is_some()) {
+                let mut coords = [Point2::origin(); 3];
+                for (coord, corner) in coords.iter_mut().zip(face.corners.iter()) {
+                    *coord = *lookup(
+                        &data.tex_coords,
+                        corner.tex.expect("Missing texture coordinate index."),
+                        face.line_num,
+                        "texture coordinate",
+                    )?;
+                }
+                Some(coords)
+            } else {
+                None
+            };
+
+            tris.push(Triangle::new(verts, norms));
+            tex_coords.push(coords);
+        }
+
+        // Groups containing only degenerate faces have no geometry.
+        if tris.is_empty() {
+            continue;
+        }
+
+        groups.push(Group {
+            name: name.clone(),
+            material: material.clone(),
+            mesh: Mesh::new(tris),
+            tex_coords,
+        });
+    }
+
+    if groups.is_empty() {
+        return Err(Error::Wavefront(None, 0, "No faces found.".to_owned()));
     }
-    Mesh::new(tris)
+
+    Ok(groups)
+}
+
+/// Read the names of the material libraries referenced by a wavefront string.
+#[inline]
+#[must_use]
+pub fn read_material_libs(s: &str) -> Vec<String> {
+    read_keyword_args(s, "mtllib")
+        .into_iter()
+        .flat_map(|args| {
+            args.split_whitespace()
+                .map(str::to_owned)
+                .collect::<Vec<_>>()
+        })
+        .collect()
 }
 
-/// Read the vertex list from wavefront string.
+/// Read the unique names of the materials used by a wavefront string.
 #[inline]
 #[must_use]
-fn read_vertices(s: &str) -> Vec<Point3<f64>> {
-    let vert_lines: Vec<_> = s
-        .split('\n')
-        .filter(|line| line.starts_with("v "))
+pub fn read_material_names(s: &str) -> Vec<String> {
+    let mut names: Vec<_> = read_keyword_args(s, "usemtl")
+        .into_iter()
+        .map(|args| read_name(args.split_whitespace()))
         .collect();
+    names.sort();
+    names.dedup();
+
+    names
+}
 
-    let mut verts = Vec::with_capacity(vert_lines.len());
-    for line in vert_lines {
-        let mut words = line.split_whitespace();
-        words.next();
+/// Save a mesh as a wavefront file.
+///
+/// # Errors
+///
+/// Returns an error if the file can not be written.
+#[inline]
+pub fn save(mesh: &Mesh, path: &Path) -> Result<(), Error> {
+    fs::write(path, write(mesh)).map_err(|err| Error::Io(path.to_path_buf(), err))
+}
 
-        let px = words
-            .next()
-            .expect("Missing vertex x value.")
-            .parse::<f64>()
-            .expect("Unable to parse f64 from string.");
-        let py = words
-            .next()
-            .expect("Missing vertex y value.")
-            .parse::<f64>()
-            .expect("Unable to parse f64 from string.");
-        let pz = words
-            .next()
-            .expect("Missing vertex z value.")
-            .parse::<f64>()
-            .expect("Unable to parse f64 from string.");
+/// Write a mesh as a wavefront string.
+/// Shared vertex positions and normals are written once.
+#[inline]
+#[must_use]
+pub fn write(mesh: &Mesh) -> String {
+    let mut verts = Vec::new();
+    let mut norms = Vec::new();
+    let mut vert_indices = HashMap::new();
+    let mut norm_indices = HashMap::new();
+    let mut faces = Vec::with_capacity(mesh.tris.len());
+
+    for tri in &mesh.tris {
+        let mut face = [(0, 0); 3];
+        for (corner, &mut (ref mut vi, ref mut ni)) in face.iter_mut().enumerate() {
+            let vert = tri.verts[corner];
+            *vi = *vert_indices
+                .entry(vert.coords.map(f64::to_bits))
+                .or_insert_with(|| {
+                    verts.push(vert);
+                    verts.len()
+                });
 
-        verts.push(Point3::new(px, py, pz));
+            let norm = tri.norms[corner];
+            *ni = *norm_indices
+                .entry(norm.map(f64::to_bits))
+                .or_insert_with(|| {
+                    norms.push(norm);
+                    norms.len()
+                });
+        }
+        faces.push(face);
     }
 
-    verts
+    let mut s = String::new();
+    for vert in &verts {
+        s.push_str(&format!("v {} {} {}\n", vert.x, vert.y, vert.z));
+    }
+    for norm in &norms {
+        s.push_str(&format!("vn {} {} {}\n", norm.x, norm.y, norm.z));
+    }
+    for [(va, na), (vb, nb), (vc, nc)] in faces {
+        s.push_str(&format!("f {va}//{na} {vb}//{nb} {vc}//{nc}\n"));
+    }
+
+    s
 }
 
-/// Read the normal list from wavefront string.
+/// Read the arguments of each line beginning with the given keyword.
 #[inline]
 #[must_use]
-fn read_normals(s: &str) -> Vec<Unit<Vector3<f64>>> {
-    let norm_lines: Vec<_> = s
-        .split('\n')
-        .filter(|line| line.starts_with("vn "))
-        .collect();
+fn read_keyword_args(s: &str, keyword: &str) -> Vec<String> {
+    s.lines()
+        .filter_map(|line| {
+            let mut words = line
+                .split('#')
+                .next()
+                .unwrap_or_default()
+                .split_whitespace();
+            (words.next() == Some(keyword)).then(|| words.collect::<Vec<_>>().join(" "))
+        })
+        .collect()
+}
 
-    let mut norms = Vec::with_capacity(norm_lines.len());
-    for line in norm_lines {
-        let mut words = line.split_whitespace();
-        words.next();
+/// Face vertex indices.
+#[derive(Clone, Copy)]
+struct Corner {
+    /// Vertex position index.
+    vert: usize,
+    /// Optional texture coordinate index.
+    tex: Option<usize>,
+    /// Optional normal index.
+    norm: Option<usize>,
+}
 
-        let nx = words
-            .next()
-            .expect("Missing normal x value.")
-            .parse::<f64>()
-            .expect("Unable to parse f64 from string.");
-        let ny = words
-            .next()
-            .expect("Missing normal y value.")
-            .parse::<f64>()
-            .expect("Unable to parse f64 from string.");
-        let nz = words
+/// Triangular face.
+struct Face {
+    /// Line number the face was declared on.
+    line_num: usize,
+    /// Whether missing normals should be smoothed.
+    smooth: bool,
+    /// Vertex indices.
+    corners: [Corner; 3],
+}
+
+/// Raw wavefront data.
+struct Data {
+    /// Vertex positions.
+    verts: Vec<Point3<f64>>,
+    /// Texture coordinates.
+    tex_coords: Vec<Point2<f64>>,
+    /// Vertex normals.
+    norms: Vec<Unit<Vector3<f64>>>,
+    /// Groups of faces, and their name and optional material.
+    groups: Vec<(String, Option<String>, Vec<Face>)>,
+}
+
+impl Data {
+    /// Read the raw data from a wavefront string.
+    #[inline]
+    fn read(s: &str) -> Result<Self, Error> {
+        let mut data = Self {
+            verts: Vec::new(),
+            tex_coords: Vec::new(),
+            norms: Vec::new(),
+            groups: Vec::new(),
+        };
+
+        let mut object_name: Option<String> = None;
+        let mut group_name: Option<String> = None;
+        let mut material: Option<String> = None;
+        let mut smooth = false;
+        let mut group_changed = true;
+
+        let mut continued = String::new();
+        let mut line_num = 0;
+        for (index, raw_line) in s.lines().enumerate() {
+            // Remove comments.
+            let line = raw_line.split('#').next().unwrap_or_default().trim_end();
+
+            // Join continued lines.
+            if continued.is_empty() {
+                line_num = index + 1;
+            }
+            if let Some(head) = line.strip_suffix('\\') {
+                continued.push_str(head);
+                continued.push(' ');
+                continue;
+            }
+            continued.push_str(line);
+            let line = core::mem::take(&mut continued);
+
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("v") => {
+                    let [px, py, pz] = read_triplet(&mut words, line_num, "vertex")?;
+                    data.verts.push(Point3::new(px, py, pz));
+                }
+                Some("vt") => {
+                    let u = read_value(&mut words, line_num, "texture coordinate")?;
+                    let v = words.next().map_or(Ok(0.0), |word| {
+                        parse_word(word, line_num, "texture coordinate")
+                    })?;
+                    data.tex_coords.push(Point2::new(u, v));
+                }
+                Some("vn") => {
+                    let [nx, ny, nz] = read_triplet(&mut words, line_num, "normal")?;
+                    data.norms
+                        .push(Unit::new_normalize(Vector3::new(nx, ny, nz)));
+                }
+                Some("f") => {
+                    let corners = words
+                        .map(|word| data.read_corner(word, line_num))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if corners.len() < 3 {
+                        return Err(Error::Wavefront(
+                            None,
+                            line_num,
+                            "Faces require at least three vertices.".to_owned(),
+                        ));
+                    }
+
+                    if group_changed {
+                        let name = group_name
+                            .clone()
+                            .or_else(|| object_name.clone())
+                            .unwrap_or_else(|| DEFAULT_GROUP_NAME.to_owned());
+                        data.groups.push((name, material.clone(), Vec::new()));
+                        group_changed = false;
+                    }
+                    let faces = &mut data
+                        .groups
+                        .last_mut()
+                        .expect("Failed to initialise face group.")
+                        .2;
+
+                    // Fan triangulation.
+                    for i in 1..(corners.len() - 1) {
+                        faces.push(Face {
+                            line_num,
+                            smooth,
+                            corners: [corners[0], corners[i], corners[i + 1]],
+                        });
+                    }
+                }
+                Some("o") => {
+                    object_name = Some(read_name(words));
+                    group_name = None;
+                    group_changed = true;
+                }
+                Some("g") => {
+                    group_name = Some(read_name(words));
+                    group_changed = true;
+                }
+                Some("usemtl") => {
+                    material = Some(read_name(words));
+                    group_changed = true;
+                }
+                Some("s") => {
+                    smooth = !matches!(words.next(), None | Some("off" | "0"));
+                }
+                _ => {}
+            }
+        }
+
+        // Merge groups which share a name and material.
+        let mut groups: Vec<(String, Option<String>, Vec<Face>)> = Vec::new();
+        for (name, mat, faces) in data.groups {
+            if let Some(group) = groups.iter_mut().find(|g| g.0 == name && g.1 == mat) {
+                group.2.extend(faces);
+            } else {
+                groups.push((name, mat, faces));
+            }
+        }
+        data.groups = groups;
+
+        Ok(data)
+    }
+
+    /// Read the vertex indices of a face corner.
+    /// Negative indices are resolved relative to the current end of each list.
+    #[inline]
+    fn read_corner(&self, word: &str, line_num: usize) -> Result<Corner, Error> {
+        let mut parts = word.split('/');
+
+        let vert = parts
             .next()
-            .expect("Missing normal z value.")
-            .parse::<f64>()
-            .expect("Unable to parse f64 from string.");
+            .filter(|part| !part.is_empty())
+            .ok_or_else(|| {
+                Error::Wavefront(None, line_num, format!("Missing vertex index: {word}"))
+            })?;
+        let vert = resolve_index(vert, self.verts.len(), line_num)?;
+
+        let tex = match parts.next() {
+            Some(part) if !part.is_empty() => {
+                Some(resolve_index(part, self.tex_coords.len(), line_num)?)
+            }
+            _ => None,
+        };
 
-        norms.push(Unit::new_normalize(Vector3::new(nx, ny, nz)));
+        let norm = match parts.next() {
+            Some(part) if !part.is_empty() => {
+                Some(resolve_index(part, self.norms.len(), line_num)?)
+            }
+            _ => None,
+        };
+
+        Ok(Corner { vert, tex, norm })
+    }
+
+    /// Reference the vertex positions of a face.
+    #[inline]
+    fn face_verts(&self, face: &Face) -> Result<[Point3<f64>; 3], Error> {
+        let mut verts = [Point3::origin(); 3];
+        for (vert, corner) in verts.iter_mut().zip(face.corners.iter()) {
+            *vert = *lookup(&self.verts, corner.vert, face.line_num, "vertex")?;
+        }
+
+        Ok(verts)
     }
 
-    norms
+    /// Calculate the area-weighted average normal of each vertex, over the smoothed faces.
+    #[inline]
+    fn smooth_normals(&self) -> Result<Vec<Unit<Vector3<f64>>>, Error> {
+        let mut sums = vec![Vector3::zeros(); self.verts.len()];
+
+        for face in self.groups.iter().flat_map(|group| group.2.iter()) {
+            if !face.smooth {
+                continue;
+            }
+
+            let verts = self.face_verts(face)?;
+            let area_norm = (verts[1] - verts[0]).cross(&(verts[2] - verts[0]));
+            for corner in &face.corners {
+                sums[corner.vert] += area_norm;
+            }
+        }
+
+        Ok(sums
+            .into_iter()
+            .map(|sum| {
+                if sum.norm_squared() > 0.0 {
+                    Unit::new_normalize(sum)
+                } else {
+                    Vector3::z_axis()
+                }
+            })
+            .collect())
+    }
 }
 
-/// Read the face list from wavefront string.
-#[allow(clippy::type_complexity)]
+/// Resolve a one-based, or negative relative, index into a zero-based index.
+#[inline]
+fn resolve_index(word: &str, len: usize, line_num: usize) -> Result<usize, Error> {
+    let index: i64 = parse_word(word, line_num, "index")?;
+
+    let resolved = match index {
+        i if i > 0 => usize::try_from(i - 1).ok(),
+        i if i < 0 => usize::try_from(-i).ok().and_then(|i| len.checked_sub(i)),
+        _ => None,
+    };
+
+    resolved.ok_or_else(|| Error::Wavefront(None, line_num, format!("Invalid index: {word}")))
+}
+
+/// Read the remaining words of a line as a name.
 #[inline]
 #[must_use]
-fn read_faces(s: &str) -> Vec<((usize, usize, usize), (usize, usize, usize))> {
-    let face_lines: Vec<_> = s
-        .split('\n')
-        .filter(|line| line.starts_with("f "))
-        .collect();
+fn read_name(words: SplitWhitespace) -> String {
+    let name = words.collect::<Vec<_>>().join(" ");
+    if name.is_empty() {
+        DEFAULT_GROUP_NAME.to_owned()
+    } else {
+        name
+    }
+}
 
-    let mut faces = Vec::with_capacity(face_lines.len());
-    for line in face_lines {
-        let split_line = line.replace("//", " ");
-        let mut words = split_line.split_whitespace();
-        words.next();
+/// Parse a word as a value of the given type.
+#[inline]
+fn parse_word<T: FromStr>(word: &str, line_num: usize, name: &str) -> Result<T, Error>
+where
+    T::Err: Display,
+{
+    word.parse::<T>().map_err(|err| {
+        Error::Wavefront(
+            None,
+            line_num,
+            format!("Unable to parse {name} value from string: {word} ({err})"),
+        )
+    })
+}
 
-        let fx = words
-            .next()
-            .expect("Missing face vertex x value.")
-            .parse::<usize>()
-            .expect("Unable to parse f64 from string.")
-            - 1;
-        let nx = words
-            .next()
-            .expect("Missing face normal x value.")
-            .parse::<usize>()
-            .expect("Unable to parse f64 from string.")
-            - 1;
-        let fy = words
-            .next()
-            .expect("Missing face vertex y value.")
-            .parse::<usize>()
-            .expect("Unable to parse f64 from string.")
-            - 1;
-        let ny = words
-            .next()
-            .expect("Missing face normal y value.")
-            .parse::<usize>()
-            .expect("Unable to parse f64 from string.")
-            - 1;
-        let fz = words
-            .next()
-            .expect("Missing face vertex z value.")
-            .parse::<usize>()
-            .expect("Unable to parse f64 from string.")
-            - 1;
-        let nz = words
-            .next()
-            .expect("Missing face normal z value.")
-            .parse::<usize>()
-            .expect("Unable to parse f64 from string.")
-            - 1;
+/// Read the next word as a value of the given type.
+#[inline]
+fn read_value<T: FromStr>(
+    words: &mut SplitWhitespace,
+    line_num: usize,
+    name: &str,
+) -> Result<T, Error>
+where
+    T::Err: Display,
+{
+    let word = words
+        .next()
+        .ok_or_else(|| Error::Wavefront(None, line_num, format!("Missing {name} value.")))?;
+
+    parse_word(word, line_num, name)
+}
 
-        faces.push(((fx, fy, fz), (nx, ny, nz)));
+/// Read the next three words as values of the given type.
+#[inline]
+fn read_triplet<T: FromStr + Copy + Default>(
+    words: &mut SplitWhitespace,
+    line_num: usize,
+    name: &str,
+) -> Result<[T; 3], Error>
+where
+    T::Err: Display,
+{
+    let mut values = [T::default(); 3];
+    for value in &mut values {
+        *value = read_value(words, line_num, name)?;
     }
 
-    faces
+    Ok(values)
+}
+
+/// Reference the element of a list with the given zero-based index.
+#[inline]
+fn lookup<'a, T>(list: &'a [T], index: usize, line_num: usize, name: &str) -> Result<&'a T, Error> {
+    list.get(index).ok_or_else(|| {
+        Error::Wavefront(
+            None,
+            line_num,
+            format!("Invalid {name} index: {}", index + 1),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geom::Cube;
+
+    #[test]
+    fn write_read_round_trip() {
+        let cube = Cube::new(Point3::new(-1.0, -2.0, -3.0), Point3::new(0.1, 0.2, 0.3));
+        let mesh = Mesh::new(cube.tris());
+
+        let read_mesh = read(&write(&mesh)).expect("Failed to read written mesh.");
+
+        assert_eq!(read_mesh.tris.len(), mesh.tris.len());
+        for (a, b) in mesh.tris.iter().zip(&read_mesh.tris) {
+            assert_eq!(a.verts, b.verts);
+            assert_eq!(a.norms, b.norms);
+        }
+    }
+
+    #[test]
+    fn polygons_are_triangulated_into_groups() {
+        let s = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+                 o quad\nusemtl red\nf 1 2 3 4\n\
+                 o tri\nf -4 -3 -2\n";
+
+        let groups = read_groups(s).expect("Failed to read groups.");
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].name, "quad");
+        assert_eq!(groups[0].material.as_deref(), Some("red"));
+        assert_eq!(groups[0].mesh.tris.len(), 2);
+        assert_eq!(groups[1].name, "tri");
+        assert_eq!(groups[1].mesh.tris.len(), 1);
+    }
+
+    #[test]
+    fn degenerate_faces_are_dropped() {
+        let s = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 2 0 0\nf 1 2 3\nf 1 2 4\n";
+
+        let mesh = read(s).expect("Failed to read mesh.");
+
+        assert_eq!(mesh.tris.len(), 1);
+        assert!(mesh.tris[0].plane_norm.iter().all(|x| x.is_finite()));
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        assert!(read("").is_err());
+        assert!(read("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n").is_err());
+        assert!(read("v 0 0 zero\nv 1 0 0\nv 0 1 0\nf 1 2 3\n").is_err());
+        assert!(read("v 0 0 0\nv 1 0 0\nf 1 2\n").is_err());
+    }
 }
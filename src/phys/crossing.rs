@@ -1,16 +1,40 @@
 //! Electro-magnetic boundary crossing calculator.
 
+use core::f64::consts::PI;
 use nalgebra::{Unit, Vector3};
 
+use crate::phys::Mueller;
+
 /// Optical interface crossing information.
 /// Calculates trajectory information.
+/// Amplitude coefficients follow the convention in which the s- and p-polarised reflection coefficients
+/// have opposite signs at normal incidence.
+/// Phase shifts follow the `exp(i(k.r - wt))` time convention,
+/// under which the phases retarded by total internal reflection are negative.
 pub struct Crossing {
-    /// Probability of reflection.
+    /// Probability of reflection, averaged over polarisations.
     pub ref_prob: f64,
     /// Reflection direction.
     pub ref_dir: Unit<Vector3<f64>>,
     /// Transmission (refraction) direction.
     pub trans_dir: Option<Unit<Vector3<f64>>>,
+    /// Magnitude of the s-polarised reflection amplitude coefficient.
+    pub r_s: f64,
+    /// Magnitude of the p-polarised reflection amplitude coefficient.
+    pub r_p: f64,
+    /// Phase shift of the s-polarised reflection (rad).
+    pub phase_s: f64,
+    /// Phase shift of the p-polarised reflection (rad).
+    pub phase_p: f64,
+    /// The s-polarised transmission amplitude coefficient.
+    /// This is the raw field ratio, and its square is not the transmitted flux fraction.
+    pub t_s: f64,
+    /// The p-polarised transmission amplitude coefficient.
+    /// This is the raw field ratio, and its square is not the transmitted flux fraction.
+    pub t_p: f64,
+    /// Factor converting squared transmission amplitudes to transmitted flux fractions,
+    /// `n_next cos(theta_t) / (n_curr cos(theta_i))`.
+    pub flux_factor: f64,
 }
 
 impl Crossing {
@@ -36,45 +60,48 @@ impl Crossing {
             Some((n_next / n_curr).asin())
         };
 
-        let (ref_prob, trans_dir) = if crit_ang.is_some()
+        let ref_dir = Self::init_ref_dir(inc, norm, ci);
+        if crit_ang.is_some()
             && (ci.acos() >= crit_ang.expect("Failed to determine critical angle."))
         {
-            (1.0, None)
-        } else {
-            let s2t = (n * n) * ci.mul_add(-ci, 1.0);
-            let ct = (1.0 - s2t).sqrt();
-
-            (
-                Self::init_ref_prob(n_curr, n_next, ci, ct),
-                Some(Self::init_trans_dir(inc, norm, n, ci, ct)),
-            )
-        };
+            // Total internal reflection retards each polarisation by a different phase.
+            let kappa = (n * n).mul_add(ci.mul_add(-ci, 1.0), -1.0).max(0.0).sqrt();
 
-        Self {
-            ref_prob,
-            ref_dir: Self::init_ref_dir(inc, norm, ci),
-            trans_dir,
+            return Self {
+                ref_prob: 1.0,
+                ref_dir,
+                trans_dir: None,
+                r_s: 1.0,
+                r_p: 1.0,
+                phase_s: -2.0 * (kappa / (n * ci)).atan(),
+                phase_p: -2.0 * (n * kappa / ci).atan(),
+                t_s: 0.0,
+                t_p: 0.0,
+                flux_factor: 0.0,
+            };
         }
-    }
-
-    /// Calculate the reflection probability.
-    #[inline]
-    #[must_use]
-    fn init_ref_prob(n1: f64, n2: f64, ci: f64, ct: f64) -> f64 {
-        debug_assert!(n1 >= 0.0);
-        debug_assert!(n2 >= 0.0);
 
-        let n1_c_i = n1 * ci;
-        let n2_c_t = n2 * ct;
-        let r_norm_sqrt = (n1_c_i - n2_c_t) / (n1_c_i + n2_c_t);
-        let r_norm = r_norm_sqrt * r_norm_sqrt;
+        let s2t = (n * n) * ci.mul_add(-ci, 1.0);
+        let ct = (1.0 - s2t).sqrt();
 
-        let n2_c_i = n2 * ci;
-        let n1_c_t = n1 * ct;
-        let r_tran_sqrt = (n2_c_i - n1_c_t) / (n2_c_i + n1_c_t);
-        let r_tran = r_tran_sqrt * r_tran_sqrt;
+        let r_s = n_curr.mul_add(ci, -n_next * ct) / n_curr.mul_add(ci, n_next * ct);
+        let r_p = n_next.mul_add(ci, -n_curr * ct) / n_next.mul_add(ci, n_curr * ct);
+        let t_s = 2.0 * n_curr * ci / n_curr.mul_add(ci, n_next * ct);
+        let t_p = 2.0 * n_curr * ci / n_next.mul_add(ci, n_curr * ct);
+        let phase = |r: f64| if r < 0.0 { PI } else { 0.0 };
 
-        (r_norm + r_tran) / 2.0
+        Self {
+            ref_prob: r_s.mul_add(r_s, r_p * r_p) / 2.0,
+            ref_dir,
+            trans_dir: Some(Self::init_trans_dir(inc, norm, n, ci, ct)),
+            r_s: r_s.abs(),
+            r_p: r_p.abs(),
+            phase_s: phase(r_s),
+            phase_p: phase(r_p),
+            t_s,
+            t_p,
+            flux_factor: (n_next * ct) / (n_curr * ci),
+        }
     }
 
     /// Calculate the reflection direction.
@@ -114,4 +141,116 @@ impl Crossing {
     pub fn trans_prob(&self) -> f64 {
         1.0 - self.ref_prob
     }
+
+    /// Get the s-polarised reflection probability.
+    #[inline]
+    #[must_use]
+    pub fn ref_prob_s(&self) -> f64 {
+        self.r_s * self.r_s
+    }
+
+    /// Get the p-polarised reflection probability.
+    #[inline]
+    #[must_use]
+    pub fn ref_prob_p(&self) -> f64 {
+        self.r_p * self.r_p
+    }
+
+    /// Construct the Mueller matrix of the reflection.
+    /// Stokes parameters must be referenced to the s-polarisation axis, perpendicular to the plane of incidence.
+    #[inline]
+    #[must_use]
+    pub fn ref_mueller(&self) -> Mueller {
+        Mueller::from_amplitudes([self.r_s, self.r_p], [self.phase_s, self.phase_p])
+    }
+
+    /// Get the s-polarised transmission probability.
+    #[inline]
+    #[must_use]
+    pub fn trans_prob_s(&self) -> f64 {
+        self.flux_factor * self.t_s * self.t_s
+    }
+
+    /// Get the p-polarised transmission probability.
+    #[inline]
+    #[must_use]
+    pub fn trans_prob_p(&self) -> f64 {
+        self.flux_factor * self.t_p * self.t_p
+    }
+
+    /// Construct the Mueller matrix of the transmission.
+    /// The amplitudes are scaled by the flux factor, so that transmission and reflection conserve flux.
+    /// Stokes parameters must be referenced to the s-polarisation axis, perpendicular to the plane of incidence.
+    #[inline]
+    #[must_use]
+    pub fn trans_mueller(&self) -> Mueller {
+        let scale = self.flux_factor.sqrt();
+        Mueller::from_amplitudes([scale * self.t_s, scale * self.t_p], [0.0, 0.0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Construct the incident direction at the given angle (rad) to the downward normal.
+    fn incident(angle: f64) -> Unit<Vector3<f64>> {
+        Unit::new_normalize(Vector3::new(angle.sin(), 0.0, -angle.cos()))
+    }
+
+    #[test]
+    fn reflection_and_transmission_conserve_flux() {
+        for &(n_curr, n_next) in &[(1.0, 1.5), (1.5, 1.0), (1.0, 2.4), (1.33, 1.5)] {
+            for deg in [0.0, 15.0, 30.0, 45.0, 60.0, 75.0, 89.0] {
+                let crossing = Crossing::new(
+                    &incident(f64::to_radians(deg)),
+                    &Vector3::z_axis(),
+                    n_curr,
+                    n_next,
+                );
+
+                assert!((crossing.ref_prob_s() + crossing.trans_prob_s() - 1.0).abs() < 1.0e-12);
+                assert!((crossing.ref_prob_p() + crossing.trans_prob_p() - 1.0).abs() < 1.0e-12);
+                let trans = crossing.trans_mueller().0[(0, 0)];
+                assert!((crossing.ref_prob + trans - 1.0).abs() < 1.0e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn normal_incidence_reflectance() {
+        let crossing = Crossing::new(&incident(0.0), &Vector3::z_axis(), 1.0, 1.5);
+
+        assert!((crossing.ref_prob - 0.04).abs() < 1.0e-12);
+        assert_eq!(crossing.phase_s, PI);
+        assert_eq!(crossing.phase_p, 0.0);
+    }
+
+    #[test]
+    fn brewster_angle_extinguishes_p_reflection() {
+        let crossing = Crossing::new(&incident(1.5_f64.atan()), &Vector3::z_axis(), 1.0, 1.5);
+
+        assert!(crossing.ref_prob_p() < 1.0e-24);
+        assert!(crossing.ref_prob_s() > 0.0);
+    }
+
+    #[test]
+    fn total_internal_reflection_phase_shifts() {
+        let (n_curr, n_next) = (1.5, 1.0);
+        for deg in [45.0, 60.0, 80.0] {
+            let angle = f64::to_radians(deg);
+            let crossing = Crossing::new(&incident(angle), &Vector3::z_axis(), n_curr, n_next);
+
+            assert!(crossing.trans_dir.is_none());
+            assert_eq!(crossing.ref_prob, 1.0);
+            assert_eq!([crossing.r_s, crossing.r_p], [1.0, 1.0]);
+
+            // Standard results, e.g. Born and Wolf section 1.5.4.
+            let (sin, cos) = angle.sin_cos();
+            let n = n_next / n_curr;
+            let root = sin.mul_add(sin, -n * n).sqrt();
+            assert!((crossing.phase_s + 2.0 * (root / cos).atan()).abs() < 1.0e-12);
+            assert!((crossing.phase_p + 2.0 * (root / (n * n * cos)).atan()).abs() < 1.0e-12);
+        }
+    }
 }
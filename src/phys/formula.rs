@@ -0,0 +1,108 @@
+//! Mathematical formulae.
+
+/// Function of a single variable.
+#[non_exhaustive]
+#[derive(Clone)]
+pub enum Formula {
+    /// Constant value.
+    Constant(f64),
+    /// Polynomial, coefficients in increasing order of power.
+    Polynomial(Vec<f64>),
+    /// Linear interpolation between tabulated points, ordered by increasing abscissa.
+    /// Values beyond the ends of the table are clamped.
+    Tabulated(Vec<f64>, Vec<f64>),
+    /// Sellmeier dispersion relation, `B` and `C` coefficient pairs.
+    /// Each `C` coefficient has the squared units of the variable.
+    Sellmeier(Vec<[f64; 2]>),
+    /// Cauchy dispersion relation, coefficients in increasing order of inverse even power.
+    Cauchy(Vec<f64>),
+}
+
+impl Formula {
+    /// Evaluate the formula at the given value.
+    #[inline]
+    #[must_use]
+    pub fn eval(&self, x: f64) -> f64 {
+        match *self {
+            Self::Constant(c) => c,
+            Self::Polynomial(ref coeffs) => coeffs
+                .iter()
+                .rev()
+                .fold(0.0, |acc, &coeff| acc.mul_add(x, coeff)),
+            Self::Tabulated(ref xs, ref ys) => {
+                debug_assert!(!xs.is_empty());
+                debug_assert!(xs.len() == ys.len());
+
+                let index = xs.partition_point(|&x_i| x_i <= x);
+                if index == 0 {
+                    return ys[0];
+                }
+                if index == xs.len() {
+                    return ys[xs.len() - 1];
+                }
+
+                let frac = (x - xs[index - 1]) / (xs[index] - xs[index - 1]);
+                (ys[index] - ys[index - 1]).mul_add(frac, ys[index - 1])
+            }
+            Self::Sellmeier(ref terms) => {
+                let x_sq = x * x;
+                terms
+                    .iter()
+                    .fold(1.0, |acc, &[b, c]| acc + (b * x_sq / (x_sq - c)))
+                    .sqrt()
+            }
+            Self::Cauchy(ref coeffs) => {
+                let inv_x_sq = 1.0 / (x * x);
+                coeffs
+                    .iter()
+                    .rev()
+                    .fold(0.0, |acc, &coeff| acc.mul_add(inv_x_sq, coeff))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phys::FormulaBuilder;
+
+    /// Fraunhofer F, d and C line wavelengths (m).
+    const LINES: [f64; 3] = [486.13e-9, 587.56e-9, 656.27e-9];
+
+    /// Catalogue refractive indices of BK7 glass at the Fraunhofer F, d and C lines.
+    const BK7_INDICES: [f64; 3] = [1.52238, 1.51680, 1.51432];
+
+    #[test]
+    fn sellmeier_matches_bk7() {
+        let builder: FormulaBuilder = json5::from_str(
+            "{ Sellmeier: [[1.03961212, 6.00069867e-15], [0.231792344, 2.00179144e-14], [1.01046945, 1.03560653e-10]] }",
+        )
+        .expect("Failed to parse formula.");
+        let formula = builder.build().expect("Failed to build formula.");
+
+        for (wavelength, index) in LINES.iter().zip(BK7_INDICES) {
+            assert!((formula.eval(*wavelength) - index).abs() < 2.0e-5);
+        }
+    }
+
+    #[test]
+    fn cauchy_approximates_bk7() {
+        let formula = Formula::Cauchy(vec![1.5046, 4.2e-15]);
+
+        for (wavelength, index) in LINES.iter().zip(BK7_INDICES) {
+            assert!((formula.eval(*wavelength) - index).abs() < 1.0e-3);
+        }
+        assert!(formula.eval(LINES[0]) > formula.eval(LINES[2]));
+    }
+
+    #[test]
+    fn tabulated_values_are_interpolated_and_clamped() {
+        let formula = Formula::Tabulated(vec![1.0, 2.0, 4.0], vec![10.0, 20.0, 0.0]);
+
+        assert_eq!(formula.eval(0.0), 10.0);
+        assert_eq!(formula.eval(1.5), 15.0);
+        assert_eq!(formula.eval(3.0), 10.0);
+        assert_eq!(formula.eval(5.0), 0.0);
+    }
+}
@@ -0,0 +1,82 @@
+//! Formula settings.
+
+use serde::Deserialize;
+
+use crate::{phys::Formula, Error};
+
+/// Formula settings.
+#[derive(Clone, Deserialize)]
+pub enum FormulaBuilder {
+    /// Constant value.
+    Constant(f64),
+    /// Polynomial coefficients, in increasing order of power.
+    Polynomial(Vec<f64>),
+    /// Tabulated points.
+    Tabulated(Vec<[f64; 2]>),
+    /// Sellmeier `B` and `C` coefficient pairs.
+    /// Material properties are evaluated at the wavelength in metres, so each `C` must be given in m^2.
+    /// Tabulated values in um^2 are scaled by 1e-12; for example, BK7 glass is:
+    ///
+    /// ```json5
+    /// { Sellmeier: [[1.03961212, 6.00069867e-15], [0.231792344, 2.00179144e-14], [1.01046945, 1.03560653e-10]] }
+    /// ```
+    Sellmeier(Vec<[f64; 2]>),
+    /// Cauchy coefficients, in increasing order of inverse even power.
+    /// As with Sellmeier `C` coefficients, the nth coefficient must be given in m^(2n).
+    Cauchy(Vec<f64>),
+}
+
+impl FormulaBuilder {
+    /// Build the Formula.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a polynomial or dispersion relation has no coefficients,
+    /// or if a table is empty or not in strictly increasing order.
+    #[inline]
+    pub fn build(&self) -> Result<Formula, Error> {
+        match *self {
+            Self::Constant(c) => Ok(Formula::Constant(c)),
+            Self::Polynomial(ref coeffs) => {
+                if coeffs.is_empty() {
+                    return Err(Error::InvalidParameter(
+                        "Polynomial requires at least one coefficient.".to_owned(),
+                    ));
+                }
+                Ok(Formula::Polynomial(coeffs.clone()))
+            }
+            Self::Tabulated(ref points) => {
+                if points.is_empty() {
+                    return Err(Error::InvalidParameter(
+                        "Table requires at least one point.".to_owned(),
+                    ));
+                }
+                if points.windows(2).any(|pair| pair[0][0] >= pair[1][0]) {
+                    return Err(Error::InvalidParameter(
+                        "Table points must be in strictly increasing order.".to_owned(),
+                    ));
+                }
+                Ok(Formula::Tabulated(
+                    points.iter().map(|point| point[0]).collect(),
+                    points.iter().map(|point| point[1]).collect(),
+                ))
+            }
+            Self::Sellmeier(ref terms) => {
+                if terms.is_empty() {
+                    return Err(Error::InvalidParameter(
+                        "Sellmeier relation requires at least one term.".to_owned(),
+                    ));
+                }
+                Ok(Formula::Sellmeier(terms.clone()))
+            }
+            Self::Cauchy(ref coeffs) => {
+                if coeffs.is_empty() {
+                    return Err(Error::InvalidParameter(
+                        "Cauchy relation requires at least one coefficient.".to_owned(),
+                    ));
+                }
+                Ok(Formula::Cauchy(coeffs.clone()))
+            }
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! Optical material.
+
+use std::{f64::INFINITY, path::Path};
+
+use crate::{
+    parse::json,
+    phys::{phase::Phase, Formula, MaterialBuilder, Optics},
+    Error,
+};
+
+/// Number of wavelengths at which each property is sampled when checking a material.
+const NUM_CHECK_SAMPLES: usize = 101;
+
+/// Wavelength dependent optical properties of a medium.
+/// Each property is a function of the wavelength (m).
+#[derive(Clone)]
+pub struct Material {
+    /// Refractive index.
+    pub ref_index: Formula,
+    /// Absorption coefficient (m^-1).
+    pub abs_coeff: Formula,
+    /// Scattering coefficient (m^-1).
+    pub scat_coeff: Formula,
+    /// Scattering anisotropy factor.
+    pub asym: Formula,
+    /// Optional phase function.
+    /// Henyey-Greenstein scattering with the anisotropy factor is used if not given.
+    pub phase: Option<Phase>,
+}
+
+impl Material {
+    /// Load a material from the `materials` directory of the given resource directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can not be read, or if the material is invalid.
+    #[inline]
+    pub fn load(resources_dir: &Path, name: &str) -> Result<Self, Error> {
+        json::load::<MaterialBuilder>(
+            &resources_dir
+                .join("materials")
+                .join(name)
+                .with_extension("json"),
+        )?
+        .build()
+    }
+
+    /// Check that the optical properties are valid across the given wavelength range (m).
+    /// Each property is sampled at evenly spaced wavelengths, including both ends of the range,
+    /// and Sellmeier relations are also checked for poles within the range.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a Sellmeier relation has a pole within the range,
+    /// or if a property is not finite or lies out of range at any sampled wavelength:
+    /// refractive indices must be at least one, coefficients must be non-negative,
+    /// and the anisotropy factor must lie within [-1, 1].
+    #[inline]
+    pub fn check(&self, wavelengths: [f64; 2]) -> Result<(), Error> {
+        debug_assert!(wavelengths[0] > 0.0);
+        debug_assert!(wavelengths[0] <= wavelengths[1]);
+
+        check_formula(
+            &self.ref_index,
+            "Refractive index",
+            1.0,
+            INFINITY,
+            wavelengths,
+        )?;
+        check_formula(
+            &self.abs_coeff,
+            "Absorption coefficient",
+            0.0,
+            INFINITY,
+            wavelengths,
+        )?;
+        check_formula(
+            &self.scat_coeff,
+            "Scattering coefficient",
+            0.0,
+            INFINITY,
+            wavelengths,
+        )?;
+        check_formula(
+            &self.asym,
+            "Scattering anisotropy factor",
+            -1.0,
+            1.0,
+            wavelengths,
+        )
+    }
+
+    /// Evaluate the optical properties at the given wavelength (m).
+    #[inline]
+    #[must_use]
+    pub fn optics(&self, wavelength: f64) -> Optics {
+        debug_assert!(wavelength > 0.0);
+
+        Optics::new(
+            self.ref_index.eval(wavelength),
+            self.abs_coeff.eval(wavelength),
+            self.scat_coeff.eval(wavelength),
+            self.asym.eval(wavelength),
+        )
+    }
+}
+
+/// Check that a formula lies within the given range across a wavelength range.
+#[inline]
+fn check_formula(
+    formula: &Formula,
+    name: &str,
+    min: f64,
+    max: f64,
+    wavelengths: [f64; 2],
+) -> Result<(), Error> {
+    let [lower, upper] = wavelengths;
+
+    if let Formula::Sellmeier(ref terms) = *formula {
+        if let Some(pole) = terms
+            .iter()
+            .filter(|&&[_, c]| c > 0.0)
+            .map(|&[_, c]| c.sqrt())
+            .find(|pole| (lower..=upper).contains(pole))
+        {
+            return Err(Error::InvalidParameter(format!(
+                "{name} has a pole at {pole} m, within the wavelength range [{lower}, {upper}] m."
+            )));
+        }
+    }
+
+    for n in 0..NUM_CHECK_SAMPLES {
+        let frac = n as f64 / (NUM_CHECK_SAMPLES - 1) as f64;
+        let wavelength = (upper - lower).mul_add(frac, lower);
+        let x = formula.eval(wavelength);
+        if !x.is_finite() || !(min..=max).contains(&x) {
+            return Err(Error::InvalidParameter(format!(
+                "{name} must lie within [{min}, {max}]: {x} at {wavelength} m"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Construct a material of BK7 glass with the given absorption coefficient.
+    fn bk7(abs_coeff: Formula) -> Material {
+        Material {
+            ref_index: Formula::Sellmeier(vec![
+                [1.039_612_12, 6.000_698_67e-15],
+                [0.231_792_344, 2.001_791_44e-14],
+                [1.010_469_45, 1.035_606_53e-10],
+            ]),
+            abs_coeff,
+            scat_coeff: Formula::Constant(0.0),
+            asym: Formula::Constant(0.0),
+            phase: None,
+        }
+    }
+
+    #[test]
+    fn materials_are_checked_over_the_wavelength_range() {
+        let mat = bk7(Formula::Constant(1.0));
+        assert!(mat.check([400e-9, 700e-9]).is_ok());
+
+        // The pole of the second term lies at 141 nm.
+        assert!(matches!(
+            mat.check([100e-9, 200e-9]),
+            Err(Error::InvalidParameter(_))
+        ));
+        // Below the poles the relation is not a valid refractive index.
+        assert!(mat.ref_index.eval(50e-9).is_nan() || mat.ref_index.eval(50e-9) < 1.0);
+        assert!(matches!(
+            mat.check([40e-9, 60e-9]),
+            Err(Error::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn coefficient_formulae_are_checked() {
+        let mat = bk7(Formula::Polynomial(vec![1.0, -2.0e6]));
+        assert!(mat.check([400e-9, 450e-9]).is_ok());
+        assert!(matches!(
+            mat.check([400e-9, 700e-9]),
+            Err(Error::InvalidParameter(_))
+        ));
+    }
+}
@@ -0,0 +1,137 @@
+//! Optical material settings.
+
+use serde::Deserialize;
+
+use crate::{
+    phys::{phase::PhaseBuilder, FormulaBuilder, Material},
+    Error,
+};
+
+/// Optical material settings.
+#[derive(Clone, Deserialize)]
+pub struct MaterialBuilder {
+    /// Refractive index.
+    pub ref_index: FormulaBuilder,
+    /// Absorption coefficient (m^-1).
+    pub abs_coeff: FormulaBuilder,
+    /// Scattering coefficient (m^-1).
+    pub scat_coeff: FormulaBuilder,
+    /// Scattering anisotropy factor.
+    pub asym: FormulaBuilder,
+    /// Optional phase function.
+    /// Henyey-Greenstein scattering with the anisotropy factor is used if not given.
+    pub phase: Option<PhaseBuilder>,
+}
+
+impl MaterialBuilder {
+    /// Build the Material.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the property formulae are invalid,
+    /// or if a constant or tabulated property value is out of range:
+    /// refractive indices must be at least one, coefficients must be non-negative,
+    /// and the anisotropy factor must lie within [-1, 1].
+    /// Also returns an error if the phase function settings are invalid.
+    /// Formulae defined by coefficients are not range checked until `Material::check` is called.
+    #[inline]
+    pub fn build(&self) -> Result<Material, Error> {
+        check_range(&self.ref_index, "Refractive index", 1.0, f64::INFINITY)?;
+        check_range(
+            &self.abs_coeff,
+            "Absorption coefficient",
+            0.0,
+            f64::INFINITY,
+        )?;
+        check_range(
+            &self.scat_coeff,
+            "Scattering coefficient",
+            0.0,
+            f64::INFINITY,
+        )?;
+        check_range(&self.asym, "Scattering anisotropy factor", -1.0, 1.0)?;
+
+        Ok(Material {
+            ref_index: self.ref_index.build()?,
+            abs_coeff: self.abs_coeff.build()?,
+            scat_coeff: self.scat_coeff.build()?,
+            asym: self.asym.build()?,
+            phase: self.phase.as_ref().map(PhaseBuilder::build).transpose()?,
+        })
+    }
+}
+
+/// Check that the constant or tabulated values of a formula lie within the given range.
+/// Formulae defined by coefficients depend on the wavelengths used,
+/// so are checked by `Material::check` once the wavelength range is known.
+#[inline]
+fn check_range(formula: &FormulaBuilder, name: &str, min: f64, max: f64) -> Result<(), Error> {
+    let values: Vec<f64> = match *formula {
+        FormulaBuilder::Constant(c) => vec![c],
+        FormulaBuilder::Tabulated(ref points) => points.iter().map(|point| point[1]).collect(),
+        FormulaBuilder::Polynomial(_)
+        | FormulaBuilder::Sellmeier(_)
+        | FormulaBuilder::Cauchy(_) => {
+            vec![]
+        }
+    };
+
+    if let Some(x) = values.into_iter().find(|x| !(min..=max).contains(x)) {
+        return Err(Error::InvalidParameter(format!(
+            "{name} must lie within [{min}, {max}]: {x}"
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse material settings with the given properties.
+    fn builder(ref_index: &str, abs_coeff: &str, asym: &str) -> MaterialBuilder {
+        json5::from_str(&format!(
+            "{{ ref_index: {ref_index}, abs_coeff: {abs_coeff}, scat_coeff: {{ Constant: 1.0 }}, asym: {asym} }}"
+        ))
+        .expect("Failed to parse material.")
+    }
+
+    #[test]
+    fn valid_materials_are_built() {
+        let mat = builder(
+            "{ Constant: 1.5 }",
+            "{ Tabulated: [[400e-9, 0.0], [700e-9, 2.0]] }",
+            "{ Constant: 0.9 }",
+        );
+        assert!(mat.build().is_ok());
+    }
+
+    #[test]
+    fn out_of_range_values_are_rejected() {
+        for mat in [
+            builder(
+                "{ Constant: 0.9 }",
+                "{ Constant: 0.0 }",
+                "{ Constant: 0.0 }",
+            ),
+            builder(
+                "{ Constant: 1.5 }",
+                "{ Tabulated: [[400e-9, 1.0], [700e-9, -1.0]] }",
+                "{ Constant: 0.0 }",
+            ),
+            builder(
+                "{ Constant: 1.5 }",
+                "{ Constant: 0.0 }",
+                "{ Constant: 1.1 }",
+            ),
+            builder(
+                "{ Constant: 1.5 }",
+                "{ Constant: 0.0 }",
+                "{ Constant: -1.1 }",
+            ),
+        ] {
+            assert!(matches!(mat.build(), Err(Error::InvalidParameter(_))));
+        }
+    }
+}
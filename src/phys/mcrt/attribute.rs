@@ -0,0 +1,14 @@
+//! Surface attributes.
+
+use crate::phys::Material;
+
+/// Photon interactions at a surface.
+#[non_exhaustive]
+pub enum Attribute<'a> {
+    /// Refractive interface, inside and outside media.
+    Interface(&'a Material, &'a Material),
+    /// Partially reflective mirror, absorption fraction.
+    Mirror(f64),
+    /// Perfect absorber.
+    Absorber,
+}
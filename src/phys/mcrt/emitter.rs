@@ -0,0 +1,83 @@
+//! Photon emission geometry.
+
+use core::f64::consts::TAU;
+use nalgebra::{Point3, Unit, Vector3};
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::{geom::Mesh, rt::Ray};
+
+/// Emission geometry of a light source.
+#[non_exhaustive]
+pub enum Emitter {
+    /// Isotropic point source.
+    Point(Point3<f64>),
+    /// Collimated beam.
+    Beam(Ray),
+    /// Lambertian emitting surface.
+    Surface {
+        /// Emitting triangles.
+        mesh: Mesh,
+        /// Cumulative area of the triangles.
+        areas: Vec<f64>,
+    },
+}
+
+impl Emitter {
+    /// Construct a new emitting surface.
+    #[inline]
+    #[must_use]
+    pub fn new_surface(mesh: Mesh) -> Self {
+        debug_assert!(!mesh.tris.is_empty());
+
+        let mut total = 0.0;
+        let areas = mesh
+            .tris
+            .iter()
+            .map(|tri| {
+                total += tri.area();
+                total
+            })
+            .collect();
+
+        Self::Surface { mesh, areas }
+    }
+
+    /// Sample an emission position and direction.
+    #[inline]
+    #[must_use]
+    pub fn emit(&self, rng: &mut ThreadRng) -> Ray {
+        match *self {
+            Self::Point(pos) => {
+                let theta = rng.gen::<f64>().mul_add(-2.0, 1.0).acos();
+                let phi = rng.gen_range(0.0..TAU);
+                let dir = Vector3::new(
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                );
+                Ray::new(pos, Unit::new_normalize(dir))
+            }
+            Self::Beam(ref ray) => ray.clone(),
+            Self::Surface {
+                ref mesh,
+                ref areas,
+            } => {
+                let total = areas[areas.len() - 1];
+                let target = rng.gen_range(0.0..total);
+                let index = areas.partition_point(|&area| area <= target);
+                let tri = &mesh.tris[index.min(areas.len() - 1)];
+
+                // Uniform position within the triangle.
+                let r = rng.gen::<f64>().sqrt();
+                let s = rng.gen::<f64>();
+                let [a, b, c] = tri.verts;
+                let pos = a + ((b - a) * (r * (1.0 - s))) + ((c - a) * (r * s));
+
+                // Cosine-weighted direction about the plane normal.
+                let mut ray = Ray::new(pos, tri.plane_norm);
+                ray.rotate(rng.gen::<f64>().sqrt().asin(), rng.gen_range(0.0..TAU));
+                ray
+            }
+        }
+    }
+}
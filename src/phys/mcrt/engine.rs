@@ -0,0 +1,198 @@
+//! Photon transport engine.
+
+use core::f64::consts::PI;
+use nalgebra::Unit;
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::{
+    geom::Trace,
+    phys::{
+        mcrt::{Attribute, Input, Output, Photon, Settings},
+        phase::{HenyeyGreenstein, PhaseFunction},
+        Crossing, Material, Mueller, Optics,
+    },
+    rt::Hit,
+};
+
+/// Speed of light in a vacuum (m s^-1).
+const SPEED_OF_LIGHT_IN_VACUUM: f64 = 299_792_458.0;
+
+/// Simulate the life of a photon packet, accumulating its contributions into the output data.
+/// The packet is followed until it is absorbed, leaves the boundary of the tree,
+/// loses at Russian roulette, or reaches the loop limit.
+#[inline]
+pub fn sample(input: &Input<'_>, rng: &mut ThreadRng, mut phot: Photon, data: &mut Output) {
+    let bump_dist = input.settings.bump_dist;
+    let voxel_size = input.grid.voxel_size;
+    let voxel_vol = voxel_size.x * voxel_size.y * voxel_size.z;
+
+    if let Some(index) = input.grid.voxel_index(&phot.ray.pos) {
+        data.emission[index] += phot.power / voxel_vol;
+    }
+
+    let mut medium = &input.world;
+    let mut optics = medium.optics(phot.wavelength);
+    let mut num_loops = 0;
+    while num_loops < input.settings.loop_limit {
+        num_loops += 1;
+
+        let exit_dist = match input.tree.boundary().dist_range(&phot.ray) {
+            Some((_, exit_dist)) => exit_dist,
+            None => return,
+        };
+
+        // Voxel indices are clamped to the grid, so a photon on, or rounded just beyond,
+        // the far face of its voxel has no distance left within it.
+        // It is then stepped across the face by the bump distance.
+        let index = input.grid.voxel_index(&phot.ray.pos);
+        let voxel_dist = index.map_or_else(
+            || input.grid.boundary.dist(&phot.ray).unwrap_or(f64::INFINITY),
+            |index| {
+                input
+                    .grid
+                    .generate_voxel(index)
+                    .dist_range(&phot.ray)
+                    .map_or(0.0, |(_, exit_dist)| exit_dist)
+            },
+        );
+
+        let interaction_coeff = optics.interaction_coeff();
+        let scat_dist = if interaction_coeff > 0.0 {
+            -rng.gen::<f64>().ln() / interaction_coeff
+        } else {
+            f64::INFINITY
+        };
+
+        let max_dist = scat_dist.min(voxel_dist).min(exit_dist).max(bump_dist);
+        let mut tally = |phot: &mut Photon, optics: &Optics, dist: f64| {
+            if let Some(index) = index {
+                let fluence = phot.weight * phot.power * dist / voxel_vol;
+                data.energy[index] += fluence * optics.ref_index / SPEED_OF_LIGHT_IN_VACUUM;
+                data.absorptions[index] += fluence * optics.abs_coeff;
+                data.scatters[index] += fluence * optics.scat_coeff;
+            }
+            phot.ray.travel(dist);
+        };
+
+        match input.tree.scan(phot.ray.clone(), bump_dist, max_dist) {
+            Some(hit) if hit.dist <= scat_dist.min(voxel_dist) => {
+                tally(&mut phot, &optics, hit.dist);
+                if !surface(&input.settings, rng, &hit, &mut phot, &mut medium) {
+                    return;
+                }
+                optics = medium.optics(phot.wavelength);
+            }
+            _ if scat_dist < voxel_dist.min(exit_dist) => {
+                tally(&mut phot, &optics, scat_dist);
+                phot.weight *= optics.albedo();
+                match medium.phase {
+                    Some(ref phase) => scatter(rng, phase, &mut phot),
+                    None => scatter(rng, &HenyeyGreenstein::new(optics.asym), &mut phot),
+                }
+                if !roulette(&input.settings, rng, &mut phot) {
+                    return;
+                }
+            }
+            _ if voxel_dist < exit_dist => {
+                tally(&mut phot, &optics, voxel_dist + bump_dist);
+            }
+            _ => return,
+        }
+    }
+}
+
+/// Perform a surface interaction.
+/// The current medium is updated if the photon packet is transmitted across an interface.
+/// Returns false if the photon packet is terminated.
+#[inline]
+fn surface<'a>(
+    settings: &Settings,
+    rng: &mut ThreadRng,
+    hit: &Hit<'_, Attribute<'a>>,
+    phot: &mut Photon,
+    medium: &mut &'a Material,
+) -> bool {
+    match *hit.tag {
+        Attribute::Interface(inside, outside) => {
+            let (curr, next) = if hit.side.is_inside() {
+                (inside, outside)
+            } else {
+                (outside, inside)
+            };
+            let crossing = Crossing::new(
+                &phot.ray.dir,
+                hit.side.norm(),
+                curr.ref_index.eval(phot.wavelength),
+                next.ref_index.eval(phot.wavelength),
+            );
+
+            align_to_plane_of_incidence(hit, phot);
+            let ref_mueller = crossing.ref_mueller();
+            if rng.gen::<f64>() < phot.stokes.ratio(&ref_mueller) {
+                phot.ray.dir = crossing.ref_dir;
+                phot.stokes.apply(&ref_mueller);
+                *medium = curr;
+            } else {
+                phot.ray.dir = crossing
+                    .trans_dir
+                    .expect("Failed to determine transmission direction.");
+                phot.stokes.apply(&crossing.trans_mueller());
+                *medium = next;
+            }
+            phot.ray.travel(settings.bump_dist);
+
+            true
+        }
+        Attribute::Mirror(abs_frac) => {
+            phot.weight *= 1.0 - abs_frac;
+            align_to_plane_of_incidence(hit, phot);
+            phot.ray.dir = Crossing::calc_ref_dir(&phot.ray.dir, hit.side.norm());
+            phot.stokes
+                .apply(&Mueller::from_amplitudes([1.0, 1.0], [PI, 0.0]));
+            phot.ray.travel(settings.bump_dist);
+
+            roulette(settings, rng, phot)
+        }
+        Attribute::Absorber => false,
+    }
+}
+
+/// Scatter a photon packet into a direction sampled from the phase function.
+/// The polarisation state is re-referenced to the scattering plane before it is transformed,
+/// and the statistical weight is corrected for the polarisation dependence of the scattered intensity.
+#[inline]
+fn scatter<P: PhaseFunction>(rng: &mut ThreadRng, phase: &P, phot: &mut Photon) {
+    let (pitch, roll) = phase.sample(rng);
+    let old_dir = phot.ray.dir;
+    phot.ray.rotate(pitch, roll);
+
+    if let Some(axis) = Unit::try_new(old_dir.cross(&phot.ray.dir), 1.0e-9) {
+        phot.stokes.rotate_frame(&old_dir, axis);
+    }
+    phot.weight *= phot.stokes.apply(&phase.mueller(pitch.cos()));
+}
+
+/// Re-reference the polarisation state of a photon packet to the s-polarisation axis of a surface hit.
+/// The current reference axis is kept at normal incidence, where the plane of incidence is undefined.
+#[inline]
+fn align_to_plane_of_incidence<T>(hit: &Hit<'_, T>, phot: &mut Photon) {
+    if let Some(s_axis) = Unit::try_new(phot.ray.dir.cross(hit.side.norm()), 1.0e-9) {
+        phot.stokes.rotate_frame(&phot.ray.dir, s_axis);
+    }
+}
+
+/// Play Russian roulette with a photon packet whose weight has fallen below the minimum.
+/// Returns false if the photon packet is terminated.
+#[inline]
+fn roulette(settings: &Settings, rng: &mut ThreadRng, phot: &mut Photon) -> bool {
+    if phot.weight >= settings.min_weight {
+        return true;
+    }
+
+    if rng.gen::<f64>() * settings.roulette_barrels < 1.0 {
+        phot.weight *= settings.roulette_barrels;
+        return true;
+    }
+
+    false
+}
@@ -0,0 +1,45 @@
+//! Transport runtime.
+
+use crate::{
+    dom::Tree,
+    geom::Grid,
+    phys::{
+        mcrt::{Attribute, Light, Settings},
+        Material,
+    },
+};
+
+/// Photon transport runtime data.
+pub struct Input<'a> {
+    /// Simulation settings.
+    pub settings: Settings,
+    /// Light source.
+    pub light: Light,
+    /// Surface hierarchy.
+    pub tree: Tree<'a, Attribute<'a>>,
+    /// Tally grid.
+    pub grid: Grid,
+    /// Medium surrounding the surfaces, in which photon packets are emitted.
+    pub world: Material,
+}
+
+impl<'a> Input<'a> {
+    /// Construct a new instance.
+    #[inline]
+    #[must_use]
+    pub const fn new(
+        settings: Settings,
+        light: Light,
+        tree: Tree<'a, Attribute<'a>>,
+        grid: Grid,
+        world: Material,
+    ) -> Self {
+        Self {
+            settings,
+            light,
+            tree,
+            grid,
+            world,
+        }
+    }
+}
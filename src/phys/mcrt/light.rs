@@ -0,0 +1,48 @@
+//! Light source.
+
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::phys::mcrt::{Emitter, Photon};
+
+/// Photon packet source.
+pub struct Light {
+    /// Emission geometry.
+    pub emitter: Emitter,
+    /// Minimum and maximum emission wavelengths (m), sampled uniformly.
+    pub wavelengths: [f64; 2],
+    /// Total power (W).
+    pub power: f64,
+}
+
+impl Light {
+    /// Construct a new instance.
+    #[inline]
+    #[must_use]
+    pub fn new(emitter: Emitter, wavelengths: [f64; 2], power: f64) -> Self {
+        debug_assert!(wavelengths[0] > 0.0);
+        debug_assert!(wavelengths[0] <= wavelengths[1]);
+        debug_assert!(power > 0.0);
+
+        Self {
+            emitter,
+            wavelengths,
+            power,
+        }
+    }
+
+    /// Emit a photon packet carrying its share of the total power.
+    #[inline]
+    #[must_use]
+    pub fn emit(&self, rng: &mut ThreadRng, num_photons: u64) -> Photon {
+        debug_assert!(num_photons > 0);
+
+        let [min, max] = self.wavelengths;
+        let wavelength = (max - min).mul_add(rng.gen(), min);
+
+        Photon::new(
+            self.emitter.emit(rng),
+            wavelength,
+            self.power / num_photons as f64,
+        )
+    }
+}
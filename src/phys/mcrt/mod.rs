@@ -0,0 +1,16 @@
+//! Monte Carlo radiative transfer.
+
+pub mod attribute;
+pub mod emitter;
+pub mod engine;
+pub mod input;
+pub mod light;
+pub mod output;
+pub mod photon;
+pub mod run;
+pub mod settings;
+
+pub use self::{
+    attribute::*, emitter::*, engine::*, input::*, light::*, output::*, photon::*, run::*,
+    settings::*,
+};
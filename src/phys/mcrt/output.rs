@@ -0,0 +1,40 @@
+//! Transport tallies.
+
+use core::ops::AddAssign;
+use ndarray::Array3;
+
+/// Volumetric tallies accumulated over a grid.
+pub struct Output {
+    /// Emitted power density (W m^-3).
+    pub emission: Array3<f64>,
+    /// Photon energy density (J m^-3).
+    pub energy: Array3<f64>,
+    /// Absorbed power density (W m^-3).
+    pub absorptions: Array3<f64>,
+    /// Scattered power density (W m^-3).
+    pub scatters: Array3<f64>,
+}
+
+impl Output {
+    /// Construct a new instance.
+    #[inline]
+    #[must_use]
+    pub fn new(res: [usize; 3]) -> Self {
+        Self {
+            emission: Array3::zeros(res),
+            energy: Array3::zeros(res),
+            absorptions: Array3::zeros(res),
+            scatters: Array3::zeros(res),
+        }
+    }
+}
+
+impl AddAssign<&Self> for Output {
+    #[inline]
+    fn add_assign(&mut self, rhs: &Self) {
+        self.emission += &rhs.emission;
+        self.energy += &rhs.energy;
+        self.absorptions += &rhs.absorptions;
+        self.scatters += &rhs.scatters;
+    }
+}
@@ -0,0 +1,38 @@
+//! Photon packet.
+
+use crate::{phys::Stokes, rt::Ray};
+
+/// Packet of photons travelling together.
+#[derive(Clone)]
+pub struct Photon {
+    /// Position and direction.
+    pub ray: Ray,
+    /// Wavelength (m).
+    pub wavelength: f64,
+    /// Statistical weight.
+    pub weight: f64,
+    /// Power (W).
+    pub power: f64,
+    /// Polarisation state.
+    pub stokes: Stokes,
+}
+
+impl Photon {
+    /// Construct a new unpolarised instance with unit weight.
+    #[inline]
+    #[must_use]
+    pub fn new(ray: Ray, wavelength: f64, power: f64) -> Self {
+        debug_assert!(wavelength > 0.0);
+        debug_assert!(power > 0.0);
+
+        let stokes = Stokes::new_unpolarised(&ray.dir);
+
+        Self {
+            ray,
+            wavelength,
+            weight: 1.0,
+            power,
+            stokes,
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! Transport control.
+
+use rand::thread_rng;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::sync::Mutex;
+
+use crate::{
+    dom::TreeCell,
+    phys::mcrt::{sample, Attribute, Input, Output, Settings},
+    util::ProgressBar,
+    Error,
+};
+
+/// Run the photon transport simulation.
+/// Photon packets are simulated in parallel blocks, and their tallies summed.
+///
+/// # Errors
+///
+/// Returns an error if the settings are invalid,
+/// if any medium is invalid across the emission wavelengths of the light,
+/// or if the measurement grid has no voxels along an axis.
+#[inline]
+pub fn run(input: &Input) -> Result<Output, Error> {
+    check_settings(&input.settings)?;
+    check_media(input)?;
+    if input.grid.num_voxels.contains(&0) {
+        return Err(Error::InvalidParameter(format!(
+            "Grid voxel counts {:?} must be greater than zero.",
+            input.grid.num_voxels
+        )));
+    }
+
+    let num_photons = input.settings.num_photons;
+    let block_size = input.settings.block_size;
+    let num_blocks = (num_photons + block_size - 1) / block_size;
+
+    let data = Mutex::new(Output::new(input.grid.num_voxels));
+    let pb = ProgressBar::new("Photon transport", num_blocks as usize);
+    (0..num_blocks).into_par_iter().for_each(|block| {
+        let start = block * block_size;
+        let end = (start + block_size).min(num_photons);
+
+        let mut rng = thread_rng();
+        let mut block_data = Output::new(input.grid.num_voxels);
+        for _ in start..end {
+            let phot = input.light.emit(&mut rng, num_photons);
+            sample(input, &mut rng, phot, &mut block_data);
+        }
+
+        *data.lock().expect("Could not lock output data.") += &block_data;
+        pb.tick();
+    });
+    pb.finish_with_message("Transport complete");
+
+    Ok(data.into_inner().expect("Could not unlock output data."))
+}
+
+/// Check that the transport settings are usable.
+#[inline]
+fn check_settings(settings: &Settings) -> Result<(), Error> {
+    if settings.num_photons == 0 || settings.block_size == 0 {
+        return Err(Error::InvalidParameter(
+            "Photon and block counts must be greater than zero.".to_owned(),
+        ));
+    }
+    if settings.bump_dist <= 0.0 {
+        return Err(Error::InvalidParameter(format!(
+            "Bump distance {} must be positive.",
+            settings.bump_dist
+        )));
+    }
+    if settings.roulette_barrels < 1.0 {
+        return Err(Error::InvalidParameter(format!(
+            "Roulette barrel count {} must be at least one.",
+            settings.roulette_barrels
+        )));
+    }
+
+    Ok(())
+}
+
+/// Check that the world and interface materials are valid across the emission wavelengths.
+#[inline]
+fn check_media(input: &Input) -> Result<(), Error> {
+    let wavelengths = input.light.wavelengths;
+    input.world.check(wavelengths)?;
+
+    let mut attrs: Vec<&Attribute> = input.tree.unbounded.iter().map(|&(_, attr)| attr).collect();
+    let mut cells = vec![&input.tree.root];
+    while let Some(cell) = cells.pop() {
+        match *cell {
+            TreeCell::Branch { ref children, .. } => cells.extend(children.iter()),
+            TreeCell::Leaf { ref shapes, .. } => {
+                attrs.extend(shapes.iter().map(|&(_, attr)| attr));
+            }
+        }
+    }
+    attrs.sort_unstable_by_key(|&attr| attr as *const Attribute);
+    attrs.dedup_by_key(|attr| *attr as *const Attribute);
+
+    for attr in attrs {
+        if let Attribute::Interface(inside, outside) = *attr {
+            inside.check(wavelengths)?;
+            outside.check(wavelengths)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dom::{Surface, TreeBuilder},
+        geom::{Cube, Grid, Mesh, Shape, Sphere},
+        phys::{
+            mcrt::{Attribute, Emitter, Light},
+            Formula, Material,
+        },
+        rt::Ray,
+    };
+    use nalgebra::{Point3, Vector3};
+
+    /// Construct the settings used by the tests.
+    fn settings(num_photons: u64) -> Settings {
+        Settings {
+            num_photons,
+            block_size: 1000,
+            bump_dist: 1.0e-9,
+            loop_limit: 1_000_000,
+            min_weight: 0.01,
+            roulette_barrels: 8.0,
+        }
+    }
+
+    /// Construct the tree settings used by the tests.
+    fn tree_settings() -> TreeBuilder {
+        TreeBuilder {
+            tar_tris: 4,
+            max_depth: 4,
+            padding: 0.01,
+            par_depth: None,
+            split_bins: None,
+            sah: None,
+        }
+    }
+
+    /// Construct a material with constant properties.
+    fn material(ref_index: f64, abs_coeff: f64, scat_coeff: f64) -> Material {
+        Material {
+            ref_index: Formula::Constant(ref_index),
+            abs_coeff: Formula::Constant(abs_coeff),
+            scat_coeff: Formula::Constant(scat_coeff),
+            asym: Formula::Constant(0.0),
+            phase: None,
+        }
+    }
+
+    #[test]
+    fn absorption_matches_beer_lambert() {
+        let attr = Attribute::Absorber;
+        let surfs = vec![Surface::from_shapes(
+            vec![Shape::Sphere(Sphere::new(Point3::origin(), 1.0))],
+            &attr,
+        )];
+        let tree = tree_settings()
+            .build(&surfs)
+            .expect("Failed to build tree.");
+        let grid = Grid::new(
+            Cube::new(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0)),
+            [8, 8, 8],
+        );
+        let light = Light::new(Emitter::Point(Point3::origin()), [500e-9, 500e-9], 1.0);
+        let input = Input::new(settings(20_000), light, tree, grid, material(1.0, 1.0, 0.0));
+
+        let output = run(&input).expect("Failed to run.");
+
+        let size = input.grid.voxel_size;
+        let absorbed = output.absorptions.sum() * size.x * size.y * size.z;
+        let expected = 1.0 - (-1.0_f64).exp();
+        assert!(
+            (absorbed - expected).abs() < 0.02,
+            "Absorbed {absorbed}, expected {expected}."
+        );
+    }
+
+    #[test]
+    fn normal_incidence_transmission_matches_fresnel() {
+        let world = material(1.0, 0.0, 0.0);
+        let inside = material(1.5, 1000.0, 0.0);
+        let attr = Attribute::Interface(&inside, &world);
+        let cube = Cube::new(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0));
+        let surfs = vec![Surface::new(Mesh::new(cube.tris()), &attr)];
+        let tree = tree_settings()
+            .build(&surfs)
+            .expect("Failed to build tree.");
+        let grid = Grid::new(cube, [4, 4, 40]);
+        let beam = Ray::new(Point3::new(0.3, 0.2, 1.1), -Vector3::z_axis());
+        let light = Light::new(Emitter::Beam(beam), [500e-9, 500e-9], 1.0);
+        let input = Input::new(settings(100_000), light, tree, grid, world.clone());
+
+        let output = run(&input).expect("Failed to run.");
+
+        // Absorption is estimated from the path length, so has a unit variance per photon.
+        let size = input.grid.voxel_size;
+        let absorbed = output.absorptions.sum() * size.x * size.y * size.z;
+        assert!(
+            (absorbed - 0.96).abs() < 0.01,
+            "Absorbed {absorbed}, expected 0.96."
+        );
+    }
+
+    #[test]
+    fn empty_grids_are_rejected() {
+        let attr = Attribute::Absorber;
+        let surfs = vec![Surface::from_shapes(
+            vec![Shape::Sphere(Sphere::new(Point3::origin(), 1.0))],
+            &attr,
+        )];
+        let tree = tree_settings()
+            .build(&surfs)
+            .expect("Failed to build tree.");
+        let grid = Grid::new(
+            Cube::new(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0)),
+            [8, 0, 8],
+        );
+        let light = Light::new(Emitter::Point(Point3::origin()), [500e-9, 500e-9], 1.0);
+        let input = Input::new(settings(100), light, tree, grid, material(1.0, 1.0, 0.0));
+
+        assert!(run(&input).is_err());
+    }
+
+    #[test]
+    fn invalid_media_are_rejected() {
+        let world = material(1.0, 0.0, 0.0);
+        let inside = material(0.5, 0.0, 0.0);
+        let attr = Attribute::Interface(&inside, &world);
+        let cube = Cube::new(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0));
+        let surfs = vec![Surface::new(Mesh::new(cube.tris()), &attr)];
+        let tree = tree_settings()
+            .build(&surfs)
+            .expect("Failed to build tree.");
+        let grid = Grid::new(cube, [4, 4, 4]);
+        let light = Light::new(Emitter::Point(Point3::origin()), [500e-9, 500e-9], 1.0);
+        let input = Input::new(settings(100), light, tree, grid, world.clone());
+
+        assert!(matches!(run(&input), Err(Error::InvalidParameter(_))));
+    }
+}
@@ -0,0 +1,20 @@
+//! Transport configuration.
+
+use serde::Deserialize;
+
+/// Photon transport settings.
+#[derive(Clone, Deserialize)]
+pub struct Settings {
+    /// Total number of photon packets to emit.
+    pub num_photons: u64,
+    /// Number of photon packets simulated by a thread between reports.
+    pub block_size: u64,
+    /// Bump distance (m).
+    pub bump_dist: f64,
+    /// Loop limit for each photon packet.
+    pub loop_limit: u64,
+    /// Weight below which photon packets play Russian roulette.
+    pub min_weight: f64,
+    /// Number of roulette barrels; one in this many photon packets survive, with their weight scaled up to match.
+    pub roulette_barrels: f64,
+}
@@ -1,5 +1,17 @@
 //! Physics.
 
 pub mod crossing;
+pub mod formula;
+pub mod formula_builder;
+pub mod material;
+pub mod material_builder;
+pub mod mcrt;
+pub mod mueller;
+pub mod optics;
+pub mod phase;
+pub mod stokes;
 
-pub use self::crossing::*;
+pub use self::{
+    crossing::*, formula::*, formula_builder::*, material::*, material_builder::*, mueller::*,
+    optics::*, stokes::*,
+};
@@ -0,0 +1,80 @@
+//! Mueller matrix.
+
+use nalgebra::Matrix4;
+
+/// Linear transformation of Stokes parameters.
+#[derive(Clone, Copy)]
+pub struct Mueller(pub Matrix4<f64>);
+
+impl Mueller {
+    /// Construct a matrix which leaves the polarisation unchanged.
+    #[inline]
+    #[must_use]
+    pub fn identity() -> Self {
+        Self(Matrix4::identity())
+    }
+
+    /// Construct the matrix of an element which scales and retards the field components
+    /// along, and perpendicular to, the reference axis by the given amplitudes and phases.
+    #[inline]
+    #[must_use]
+    pub fn from_amplitudes(amps: [f64; 2], phases: [f64; 2]) -> Self {
+        let [amp_1, amp_2] = amps;
+        let sum = amp_1.mul_add(amp_1, amp_2 * amp_2) / 2.0;
+        let diff = amp_1.mul_add(amp_1, -amp_2 * amp_2) / 2.0;
+        let delta = phases[0] - phases[1];
+        let cross = amp_1 * amp_2;
+        let (x, y) = (cross * delta.cos(), cross * delta.sin());
+
+        #[rustfmt::skip]
+        let mat = Matrix4::new(
+            sum,  diff, 0.0, 0.0,
+            diff, sum,  0.0, 0.0,
+            0.0,  0.0,  x,   -y,
+            0.0,  0.0,  y,   x,
+        );
+
+        Self(mat)
+    }
+
+    /// Construct the matrix which rotates the reference axis by the given angle (rad),
+    /// towards the axis perpendicular to it and the direction of travel.
+    #[inline]
+    #[must_use]
+    pub fn rotation(angle: f64) -> Self {
+        let (sin, cos) = (2.0 * angle).sin_cos();
+
+        #[rustfmt::skip]
+        let mat = Matrix4::new(
+            1.0, 0.0,  0.0, 0.0,
+            0.0, cos,  sin, 0.0,
+            0.0, -sin, cos, 0.0,
+            0.0, 0.0,  0.0, 1.0,
+        );
+
+        Self(mat)
+    }
+
+    /// Construct the Rayleigh scattering matrix, normalised to unit intensity of unpolarised light.
+    /// Stokes parameters must be referenced to the axis perpendicular to the scattering plane.
+    #[inline]
+    #[must_use]
+    pub fn rayleigh(cos_theta: f64) -> Self {
+        debug_assert!(cos_theta.abs() <= 1.0);
+
+        let cos_sq = cos_theta * cos_theta;
+        let norm = 1.0 + cos_sq;
+        let pol = (1.0 - cos_sq) / norm;
+        let ret = 2.0 * cos_theta / norm;
+
+        #[rustfmt::skip]
+        let mat = Matrix4::new(
+            1.0, pol, 0.0, 0.0,
+            pol, 1.0, 0.0, 0.0,
+            0.0, 0.0, ret, 0.0,
+            0.0, 0.0, 0.0, ret,
+        );
+
+        Self(mat)
+    }
+}
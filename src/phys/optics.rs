@@ -0,0 +1,55 @@
+//! Local optical properties.
+
+use serde::Deserialize;
+
+/// Optical properties of a medium at a single wavelength.
+#[derive(Clone, Deserialize)]
+pub struct Optics {
+    /// Refractive index.
+    pub ref_index: f64,
+    /// Absorption coefficient (m^-1).
+    pub abs_coeff: f64,
+    /// Scattering coefficient (m^-1).
+    pub scat_coeff: f64,
+    /// Scattering anisotropy factor.
+    pub asym: f64,
+}
+
+impl Optics {
+    /// Construct a new instance.
+    #[inline]
+    #[must_use]
+    pub fn new(ref_index: f64, abs_coeff: f64, scat_coeff: f64, asym: f64) -> Self {
+        debug_assert!(ref_index >= 1.0);
+        debug_assert!(abs_coeff >= 0.0);
+        debug_assert!(scat_coeff >= 0.0);
+        debug_assert!(asym.abs() <= 1.0);
+
+        Self {
+            ref_index,
+            abs_coeff,
+            scat_coeff,
+            asym,
+        }
+    }
+
+    /// Calculate the interaction coefficient (m^-1).
+    #[inline]
+    #[must_use]
+    pub fn interaction_coeff(&self) -> f64 {
+        self.abs_coeff + self.scat_coeff
+    }
+
+    /// Calculate the single-scattering albedo.
+    /// Media which do not interact have an albedo of zero.
+    #[inline]
+    #[must_use]
+    pub fn albedo(&self) -> f64 {
+        let interaction_coeff = self.interaction_coeff();
+        if interaction_coeff <= 0.0 {
+            return 0.0;
+        }
+
+        self.scat_coeff / interaction_coeff
+    }
+}
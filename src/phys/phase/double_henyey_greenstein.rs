@@ -0,0 +1,50 @@
+//! Double Henyey-Greenstein phase function.
+
+use rand::Rng;
+
+use crate::phys::phase::{HenyeyGreenstein, PhaseFunction};
+
+/// Weighted combination of a forward and a backward peaked Henyey-Greenstein function.
+#[derive(Clone, Copy)]
+pub struct DoubleHenyeyGreenstein {
+    /// Forward peaked component.
+    pub forward: HenyeyGreenstein,
+    /// Backward peaked component.
+    pub backward: HenyeyGreenstein,
+    /// Fraction of scattering events following the forward component.
+    pub frac: f64,
+}
+
+impl DoubleHenyeyGreenstein {
+    /// Construct a new instance.
+    #[inline]
+    #[must_use]
+    pub fn new(forward_asym: f64, backward_asym: f64, frac: f64) -> Self {
+        debug_assert!((0.0..=1.0).contains(&frac));
+
+        Self {
+            forward: HenyeyGreenstein::new(forward_asym),
+            backward: HenyeyGreenstein::new(backward_asym),
+            frac,
+        }
+    }
+}
+
+impl PhaseFunction for DoubleHenyeyGreenstein {
+    #[inline]
+    fn sample<R: Rng>(&self, rng: &mut R) -> (f64, f64) {
+        if rng.gen::<f64>() < self.frac {
+            self.forward.sample(rng)
+        } else {
+            self.backward.sample(rng)
+        }
+    }
+
+    #[inline]
+    fn pdf(&self, cos_theta: f64) -> f64 {
+        self.frac.mul_add(
+            self.forward.pdf(cos_theta),
+            (1.0 - self.frac) * self.backward.pdf(cos_theta),
+        )
+    }
+}
@@ -0,0 +1,86 @@
+//! Henyey-Greenstein phase function.
+
+use core::f64::consts::{PI, TAU};
+use rand::Rng;
+
+use crate::phys::phase::PhaseFunction;
+
+/// Anisotropy below which scattering is treated as isotropic when sampling.
+const MIN_ASYM: f64 = 1.0e-6;
+
+/// Single-parameter forward or backward peaked scattering.
+#[derive(Clone, Copy)]
+pub struct HenyeyGreenstein {
+    /// Anisotropy factor; the mean cosine of the scattering angle.
+    pub asym: f64,
+}
+
+impl HenyeyGreenstein {
+    /// Construct a new instance.
+    #[inline]
+    #[must_use]
+    pub fn new(asym: f64) -> Self {
+        debug_assert!(asym.abs() <= 1.0);
+
+        Self { asym }
+    }
+
+    /// Sample the cosine of the scattering angle.
+    #[inline]
+    #[must_use]
+    pub fn sample_cos_theta<R: Rng>(&self, rng: &mut R) -> f64 {
+        let xi = rng.gen::<f64>();
+        if self.asym.abs() < MIN_ASYM {
+            return xi.mul_add(2.0, -1.0);
+        }
+
+        let asym_sq = self.asym * self.asym;
+        let frac = (1.0 - asym_sq) / (2.0 * self.asym).mul_add(xi, 1.0 - self.asym);
+        ((1.0 + asym_sq - (frac * frac)) / (2.0 * self.asym)).clamp(-1.0, 1.0)
+    }
+}
+
+impl PhaseFunction for HenyeyGreenstein {
+    #[inline]
+    fn sample<R: Rng>(&self, rng: &mut R) -> (f64, f64) {
+        (self.sample_cos_theta(rng).acos(), rng.gen_range(0.0..TAU))
+    }
+
+    #[inline]
+    fn pdf(&self, cos_theta: f64) -> f64 {
+        debug_assert!(cos_theta.abs() <= 1.0);
+
+        let asym_sq = self.asym * self.asym;
+        let denom = (-2.0 * self.asym).mul_add(cos_theta, 1.0 + asym_sq);
+        (1.0 - asym_sq) / (4.0 * PI * denom * denom.sqrt())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phys::phase::assert_sampling_matches_pdf;
+
+    #[test]
+    fn sampling_matches_pdf() {
+        for asym in [0.0, 0.5, -0.7, 0.9] {
+            assert_sampling_matches_pdf(&HenyeyGreenstein::new(asym));
+        }
+    }
+
+    #[test]
+    fn mean_cosine_is_the_anisotropy() {
+        let num_steps = 100_000;
+        for asym in [0.3, -0.6] {
+            let phase = HenyeyGreenstein::new(asym);
+            let step = 2.0 / f64::from(num_steps);
+            let mean: f64 = (0..num_steps)
+                .map(|n| {
+                    let mu = (f64::from(n) + 0.5).mul_add(step, -1.0);
+                    TAU * mu * phase.pdf(mu) * step
+                })
+                .sum();
+            assert!((mean - asym).abs() < 1.0e-6);
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! Isotropic phase function.
+
+use core::f64::consts::{PI, TAU};
+use rand::Rng;
+
+use crate::phys::phase::PhaseFunction;
+
+/// Uniform scattering in all directions.
+#[derive(Clone, Copy, Default)]
+pub struct Isotropic;
+
+impl PhaseFunction for Isotropic {
+    #[inline]
+    fn sample<R: Rng>(&self, rng: &mut R) -> (f64, f64) {
+        let cos_theta = rng.gen::<f64>().mul_add(2.0, -1.0);
+        (cos_theta.acos(), rng.gen_range(0.0..TAU))
+    }
+
+    #[inline]
+    fn pdf(&self, cos_theta: f64) -> f64 {
+        debug_assert!(cos_theta.abs() <= 1.0);
+
+        1.0 / (4.0 * PI)
+    }
+}
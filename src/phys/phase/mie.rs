@@ -0,0 +1,125 @@
+//! Tabulated phase function.
+
+use core::f64::consts::TAU;
+use rand::Rng;
+
+use crate::phys::phase::PhaseFunction;
+
+/// Phase function tabulated over the scattering angle, such as one calculated from Mie theory.
+/// The density is linearly interpolated in the cosine of the scattering angle.
+#[derive(Clone)]
+pub struct Mie {
+    /// Cosines of the tabulated scattering angles, in increasing order.
+    cos_thetas: Vec<f64>,
+    /// Probability densities per unit cosine.
+    densities: Vec<f64>,
+    /// Cumulative probabilities.
+    cdf: Vec<f64>,
+}
+
+impl Mie {
+    /// Construct a new instance from relative intensities at increasing scattering angles (radians).
+    /// The angles must run from zero to pi.
+    #[inline]
+    #[must_use]
+    pub fn new(angles: &[f64], intensities: &[f64]) -> Self {
+        debug_assert!(angles.len() >= 2);
+        debug_assert!(angles.len() == intensities.len());
+        debug_assert!(angles.windows(2).all(|pair| pair[0] < pair[1]));
+        debug_assert!(intensities.iter().all(|&intensity| intensity >= 0.0));
+
+        let cos_thetas: Vec<_> = angles.iter().rev().map(|angle| angle.cos()).collect();
+        let mut densities: Vec<_> = intensities.iter().rev().copied().collect();
+
+        let mut cdf = Vec::with_capacity(cos_thetas.len());
+        cdf.push(0.0);
+        for i in 1..cos_thetas.len() {
+            let width = cos_thetas[i] - cos_thetas[i - 1];
+            cdf.push(0.5f64.mul_add(width * (densities[i - 1] + densities[i]), cdf[i - 1]));
+        }
+
+        let total = cdf[cdf.len() - 1];
+        debug_assert!(total > 0.0);
+        for (density, prob) in densities.iter_mut().zip(cdf.iter_mut()) {
+            *density /= total;
+            *prob /= total;
+        }
+
+        Self {
+            cos_thetas,
+            densities,
+            cdf,
+        }
+    }
+
+    /// Sample the cosine of the scattering angle.
+    #[inline]
+    #[must_use]
+    pub fn sample_cos_theta<R: Rng>(&self, rng: &mut R) -> f64 {
+        let xi = rng.gen::<f64>();
+        let index = self
+            .cdf
+            .partition_point(|&prob| prob <= xi)
+            .clamp(1, self.cdf.len() - 1);
+
+        // Invert the quadratic cumulative distribution within the segment.
+        let (mu_0, mu_1) = (self.cos_thetas[index - 1], self.cos_thetas[index]);
+        let (p_0, p_1) = (self.densities[index - 1], self.densities[index]);
+        let slope = (p_1 - p_0) / (mu_1 - mu_0);
+        let remainder = xi - self.cdf[index - 1];
+        let disc = (2.0 * slope).mul_add(remainder, p_0 * p_0).max(0.0);
+        let denom = p_0 + disc.sqrt();
+        if denom <= 0.0 {
+            return mu_0;
+        }
+
+        (mu_0 + (2.0 * remainder / denom)).clamp(mu_0, mu_1)
+    }
+}
+
+impl PhaseFunction for Mie {
+    #[inline]
+    fn sample<R: Rng>(&self, rng: &mut R) -> (f64, f64) {
+        (self.sample_cos_theta(rng).acos(), rng.gen_range(0.0..TAU))
+    }
+
+    #[inline]
+    fn pdf(&self, cos_theta: f64) -> f64 {
+        debug_assert!(cos_theta.abs() <= 1.0);
+
+        let index = self
+            .cos_thetas
+            .partition_point(|&mu| mu <= cos_theta)
+            .clamp(1, self.cos_thetas.len() - 1);
+        let (mu_0, mu_1) = (self.cos_thetas[index - 1], self.cos_thetas[index]);
+        let (p_0, p_1) = (self.densities[index - 1], self.densities[index]);
+        let frac = ((cos_theta - mu_0) / (mu_1 - mu_0)).clamp(0.0, 1.0);
+
+        (p_1 - p_0).mul_add(frac, p_0) / TAU
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phys::phase::assert_sampling_matches_pdf;
+    use core::f64::consts::{FRAC_PI_2, PI};
+
+    #[test]
+    fn sampling_matches_pdf() {
+        let angles = [0.0, 10.0, 30.0, 60.0, 90.0, 150.0, 180.0].map(f64::to_radians);
+        let intensities = [50.0, 20.0, 5.0, 1.0, 0.5, 0.0, 2.0];
+
+        assert_sampling_matches_pdf(&Mie::new(&angles, &intensities));
+    }
+
+    #[test]
+    fn pdf_interpolates_the_table() {
+        let mie = Mie::new(&[0.0, FRAC_PI_2, PI], &[2.0, 1.0, 0.0]);
+
+        // Densities are normalised over the cosine, over which the table integrates to two.
+        assert!((mie.pdf(1.0) * TAU - 1.0).abs() < 1.0e-12);
+        assert!((mie.pdf(0.0) * TAU - 0.5).abs() < 1.0e-12);
+        assert!((mie.pdf(-0.5) * TAU - 0.25).abs() < 1.0e-12);
+    }
+}
@@ -0,0 +1,15 @@
+//! Scattering phase functions.
+
+pub mod double_henyey_greenstein;
+pub mod henyey_greenstein;
+pub mod isotropic;
+pub mod mie;
+pub mod phase;
+pub mod phase_builder;
+pub mod phase_function;
+pub mod rayleigh;
+
+pub use self::{
+    double_henyey_greenstein::*, henyey_greenstein::*, isotropic::*, mie::*, phase::*,
+    phase_builder::*, phase_function::*, rayleigh::*,
+};
@@ -0,0 +1,59 @@
+//! Phase function enumeration.
+
+use rand::Rng;
+
+use crate::phys::{
+    phase::{DoubleHenyeyGreenstein, HenyeyGreenstein, Isotropic, Mie, PhaseFunction, Rayleigh},
+    Mueller,
+};
+
+/// Phase function enumeration.
+#[allow(clippy::exhaustive_enums)]
+#[derive(Clone)]
+pub enum Phase {
+    /// Isotropic.
+    Isotropic(Isotropic),
+    /// Henyey-Greenstein.
+    HenyeyGreenstein(HenyeyGreenstein),
+    /// Double Henyey-Greenstein.
+    DoubleHenyeyGreenstein(DoubleHenyeyGreenstein),
+    /// Rayleigh.
+    Rayleigh(Rayleigh),
+    /// Tabulated Mie.
+    Mie(Mie),
+}
+
+impl PhaseFunction for Phase {
+    #[inline]
+    fn sample<R: Rng>(&self, rng: &mut R) -> (f64, f64) {
+        match *self {
+            Self::Isotropic(ref phase) => phase.sample(rng),
+            Self::HenyeyGreenstein(ref phase) => phase.sample(rng),
+            Self::DoubleHenyeyGreenstein(ref phase) => phase.sample(rng),
+            Self::Rayleigh(ref phase) => phase.sample(rng),
+            Self::Mie(ref phase) => phase.sample(rng),
+        }
+    }
+
+    #[inline]
+    fn pdf(&self, cos_theta: f64) -> f64 {
+        match *self {
+            Self::Isotropic(ref phase) => phase.pdf(cos_theta),
+            Self::HenyeyGreenstein(ref phase) => phase.pdf(cos_theta),
+            Self::DoubleHenyeyGreenstein(ref phase) => phase.pdf(cos_theta),
+            Self::Rayleigh(ref phase) => phase.pdf(cos_theta),
+            Self::Mie(ref phase) => phase.pdf(cos_theta),
+        }
+    }
+
+    #[inline]
+    fn mueller(&self, cos_theta: f64) -> Mueller {
+        match *self {
+            Self::Isotropic(ref phase) => phase.mueller(cos_theta),
+            Self::HenyeyGreenstein(ref phase) => phase.mueller(cos_theta),
+            Self::DoubleHenyeyGreenstein(ref phase) => phase.mueller(cos_theta),
+            Self::Rayleigh(ref phase) => phase.mueller(cos_theta),
+            Self::Mie(ref phase) => phase.mueller(cos_theta),
+        }
+    }
+}
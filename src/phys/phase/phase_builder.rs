@@ -0,0 +1,100 @@
+//! Phase function settings.
+
+use serde::Deserialize;
+
+use crate::{
+    phys::phase::{DoubleHenyeyGreenstein, HenyeyGreenstein, Isotropic, Mie, Phase, Rayleigh},
+    Error,
+};
+
+/// Phase function settings.
+#[derive(Clone, Deserialize)]
+pub enum PhaseBuilder {
+    /// Isotropic.
+    Isotropic,
+    /// Henyey-Greenstein, anisotropy factor.
+    HenyeyGreenstein(f64),
+    /// Double Henyey-Greenstein.
+    DoubleHenyeyGreenstein {
+        /// Anisotropy factor of the forward component.
+        forward: f64,
+        /// Anisotropy factor of the backward component.
+        backward: f64,
+        /// Fraction of scattering following the forward component.
+        frac: f64,
+    },
+    /// Rayleigh.
+    Rayleigh,
+    /// Tabulated Mie, scattering angles (deg) and relative intensities.
+    Mie(Vec<[f64; 2]>),
+}
+
+impl PhaseBuilder {
+    /// Build the Phase.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an anisotropy factor is not within (-1, 1), if a fraction is not within [0, 1],
+    /// or if a table does not run in increasing order from 0 to 180 degrees with non-negative intensities.
+    #[inline]
+    pub fn build(&self) -> Result<Phase, Error> {
+        match *self {
+            Self::Isotropic => Ok(Phase::Isotropic(Isotropic)),
+            Self::HenyeyGreenstein(asym) => {
+                check_asym(asym)?;
+                Ok(Phase::HenyeyGreenstein(HenyeyGreenstein::new(asym)))
+            }
+            Self::DoubleHenyeyGreenstein {
+                forward,
+                backward,
+                frac,
+            } => {
+                check_asym(forward)?;
+                check_asym(backward)?;
+                if !(0.0..=1.0).contains(&frac) {
+                    return Err(Error::InvalidParameter(format!(
+                        "Phase function fraction {frac} must be within [0, 1]."
+                    )));
+                }
+                Ok(Phase::DoubleHenyeyGreenstein(DoubleHenyeyGreenstein::new(
+                    forward, backward, frac,
+                )))
+            }
+            Self::Rayleigh => Ok(Phase::Rayleigh(Rayleigh)),
+            Self::Mie(ref points) => {
+                if points.len() < 2
+                    || points[0][0] != 0.0
+                    || points[points.len() - 1][0] != 180.0
+                    || points.windows(2).any(|pair| pair[0][0] >= pair[1][0])
+                {
+                    return Err(Error::InvalidParameter(
+                        "Mie table angles must increase from 0 to 180 degrees.".to_owned(),
+                    ));
+                }
+                if points.iter().any(|point| point[1] < 0.0)
+                    || points.iter().all(|point| point[1] <= 0.0)
+                {
+                    return Err(Error::InvalidParameter(
+                        "Mie table intensities must be non-negative, and not all zero.".to_owned(),
+                    ));
+                }
+
+                let angles: Vec<_> = points.iter().map(|point| point[0].to_radians()).collect();
+                let intensities: Vec<_> = points.iter().map(|point| point[1]).collect();
+                Ok(Phase::Mie(Mie::new(&angles, &intensities)))
+            }
+        }
+    }
+}
+
+/// Check that an anisotropy factor lies within (-1, 1).
+#[inline]
+fn check_asym(asym: f64) -> Result<(), Error> {
+    if asym.abs() < 1.0 {
+        Ok(())
+    } else {
+        Err(Error::InvalidParameter(format!(
+            "Anisotropy factor {asym} must be within (-1, 1)."
+        )))
+    }
+}
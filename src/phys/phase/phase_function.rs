@@ -0,0 +1,77 @@
+//! Phase function trait.
+
+use rand::Rng;
+
+use crate::{phys::Mueller, rt::Ray};
+
+/// Angular distribution of scattered light.
+pub trait PhaseFunction {
+    /// Sample a scattering pitch angle, away from the direction of travel, and a subsequent roll angle.
+    #[must_use]
+    fn sample<R: Rng>(&self, rng: &mut R) -> (f64, f64);
+
+    /// Calculate the probability density, per unit solid angle, of scattering through an angle with the given cosine.
+    #[must_use]
+    fn pdf(&self, cos_theta: f64) -> f64;
+
+    /// Determine the Mueller matrix, normalised to unit intensity, of scattering through an angle with the given cosine.
+    /// Stokes parameters must be referenced to the axis perpendicular to the scattering plane.
+    #[inline]
+    #[must_use]
+    fn mueller(&self, cos_theta: f64) -> Mueller {
+        debug_assert!(cos_theta.abs() <= 1.0);
+
+        Mueller::identity()
+    }
+
+    /// Scatter a Ray, in place, into a sampled direction.
+    #[inline]
+    fn scatter<R: Rng>(&self, rng: &mut R, ray: &mut Ray) {
+        let (pitch, roll) = self.sample(rng);
+        ray.rotate(pitch, roll);
+    }
+}
+
+/// Check that the sampled scattering angles of a phase function follow its probability density.
+/// Samples are binned uniformly in the cosine of the scattering angle,
+/// and each bin count must lie within five standard deviations of that expected from the density.
+#[cfg(test)]
+pub fn assert_sampling_matches_pdf<P: PhaseFunction>(phase: &P) {
+    use core::f64::consts::TAU;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    const NUM_BINS: usize = 40;
+    const NUM_SAMPLES: usize = 200_000;
+    const NUM_STEPS: usize = 64;
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut counts = [0_usize; NUM_BINS];
+    for _ in 0..NUM_SAMPLES {
+        let (pitch, _) = phase.sample(&mut rng);
+        let bin = ((pitch.cos() + 1.0) * 0.5 * NUM_BINS as f64) as usize;
+        counts[bin.min(NUM_BINS - 1)] += 1;
+    }
+
+    let width = 2.0 / NUM_BINS as f64;
+    let mut total = 0.0;
+    for (bin, &count) in counts.iter().enumerate() {
+        // Midpoint integration of the density over the bin.
+        let start = (bin as f64).mul_add(width, -1.0);
+        let step = width / NUM_STEPS as f64;
+        let prob = (0..NUM_STEPS)
+            .map(|n| TAU * phase.pdf((n as f64 + 0.5).mul_add(step, start)) * step)
+            .sum::<f64>();
+        total += prob;
+
+        let expected = prob * NUM_SAMPLES as f64;
+        let tolerance = 5.0f64.mul_add(expected.sqrt(), 1.0);
+        assert!(
+            (count as f64 - expected).abs() <= tolerance,
+            "Bin {bin}: counted {count}, expected {expected}."
+        );
+    }
+    assert!(
+        (total - 1.0).abs() < 1.0e-3,
+        "Density integrates to {total}."
+    );
+}
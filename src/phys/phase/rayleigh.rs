@@ -0,0 +1,58 @@
+//! Rayleigh phase function.
+
+use core::f64::consts::{PI, TAU};
+use rand::Rng;
+
+use crate::phys::{phase::PhaseFunction, Mueller};
+
+/// Scattering by particles much smaller than the wavelength.
+#[derive(Clone, Copy, Default)]
+pub struct Rayleigh;
+
+impl PhaseFunction for Rayleigh {
+    #[inline]
+    fn sample<R: Rng>(&self, rng: &mut R) -> (f64, f64) {
+        // Invert the cumulative distribution, a cubic in the cosine, using Cardano's method.
+        let u = rng.gen::<f64>().mul_add(4.0, -2.0);
+        let root = u.mul_add(u, 1.0).sqrt();
+        let cos_theta = ((u + root).cbrt() + (u - root).cbrt()).clamp(-1.0, 1.0);
+
+        (cos_theta.acos(), rng.gen_range(0.0..TAU))
+    }
+
+    #[inline]
+    fn pdf(&self, cos_theta: f64) -> f64 {
+        debug_assert!(cos_theta.abs() <= 1.0);
+
+        3.0 * cos_theta.mul_add(cos_theta, 1.0) / (16.0 * PI)
+    }
+
+    #[inline]
+    fn mueller(&self, cos_theta: f64) -> Mueller {
+        Mueller::rayleigh(cos_theta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phys::phase::assert_sampling_matches_pdf;
+    use nalgebra::Vector4;
+
+    #[test]
+    fn sampling_matches_pdf() {
+        assert_sampling_matches_pdf(&Rayleigh);
+    }
+
+    #[test]
+    fn unpolarised_light_is_polarised_perpendicular_to_the_scattering_plane() {
+        for cos_theta in [-1.0, -0.3, 0.0, 0.5, 1.0] {
+            let stokes = Rayleigh.mueller(cos_theta).0 * Vector4::new(1.0, 0.0, 0.0, 0.0);
+            let sin_sq = cos_theta.mul_add(-cos_theta, 1.0);
+
+            assert!((stokes[0] - 1.0).abs() < 1.0e-12);
+            assert!((stokes[1] - (sin_sq / (2.0 - sin_sq))).abs() < 1.0e-12);
+            assert_eq!([stokes[2], stokes[3]], [0.0, 0.0]);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Stokes polarisation state.
+
+use nalgebra::{Unit, Vector3, Vector4};
+
+use crate::phys::Mueller;
+
+/// Polarisation state of a beam, described by Stokes parameters relative to a reference axis.
+/// The reference axis is perpendicular to the direction of travel.
+/// Parameters are kept normalised to unit intensity, with the intensity carried by the statistical weight.
+#[derive(Clone)]
+pub struct Stokes {
+    /// Stokes parameters: intensity, and linear and circular polarisation.
+    pub params: Vector4<f64>,
+    /// Reference axis.
+    pub ref_axis: Unit<Vector3<f64>>,
+}
+
+impl Stokes {
+    /// Construct a new instance.
+    #[inline]
+    #[must_use]
+    pub fn new(params: Vector4<f64>, ref_axis: Unit<Vector3<f64>>) -> Self {
+        debug_assert!(params.x > 0.0);
+
+        Self {
+            params: params / params.x,
+            ref_axis,
+        }
+    }
+
+    /// Construct an unpolarised state for a beam travelling in the given direction.
+    #[inline]
+    #[must_use]
+    pub fn new_unpolarised(dir: &Unit<Vector3<f64>>) -> Self {
+        let arbitrary_axis = if dir.z.abs() < 0.9 {
+            Vector3::z_axis()
+        } else {
+            Vector3::y_axis()
+        };
+
+        Self {
+            params: Vector4::new(1.0, 0.0, 0.0, 0.0),
+            ref_axis: Unit::new_normalize(dir.cross(&arbitrary_axis)),
+        }
+    }
+
+    /// Calculate the degree of polarisation.
+    #[inline]
+    #[must_use]
+    pub fn degree_of_polarisation(&self) -> f64 {
+        self.params.fixed_rows::<3>(1).norm() / self.params.x
+    }
+
+    /// Re-reference the parameters to a new axis, perpendicular to the given direction of travel.
+    #[inline]
+    pub fn rotate_frame(&mut self, dir: &Unit<Vector3<f64>>, ref_axis: Unit<Vector3<f64>>) {
+        debug_assert!(ref_axis.dot(dir).abs() < 1.0e-6);
+
+        let perp_axis = dir.cross(&self.ref_axis);
+        let angle = ref_axis.dot(&perp_axis).atan2(ref_axis.dot(&self.ref_axis));
+        self.params = Mueller::rotation(angle).0 * self.params;
+        self.ref_axis = ref_axis;
+    }
+
+    /// Calculate the fraction of the intensity which would be passed by a Mueller matrix.
+    #[inline]
+    #[must_use]
+    pub fn ratio(&self, mueller: &Mueller) -> f64 {
+        (mueller.0.row(0) * self.params).x / self.params.x
+    }
+
+    /// Transform the state by a Mueller matrix, and renormalise it.
+    /// Returns the fraction of the intensity passed.
+    #[inline]
+    pub fn apply(&mut self, mueller: &Mueller) -> f64 {
+        let params = mueller.0 * self.params;
+        let ratio = params.x / self.params.x;
+        if params.x > 0.0 {
+            self.params = params / params.x;
+        }
+
+        ratio
+    }
+}
@@ -2,6 +2,8 @@
 
 use palette::{Gradient, LinSrgba};
 
+use crate::phys::Formula;
+
 /// Observable attributes.
 #[non_exhaustive]
 pub enum Attribute<'a> {
@@ -11,8 +13,8 @@ pub enum Attribute<'a> {
     Mirror(&'a Gradient<LinSrgba>, f64),
     /// Partially transparent, absorption fraction.
     Transparent(&'a Gradient<LinSrgba>, f64),
-    /// Refractive, absorption fraction, inside and outside refractive indices.
-    Refractive(&'a Gradient<LinSrgba>, f64, [f64; 2]),
+    /// Refractive, absorption fraction, inside and outside refractive indices as functions of wavelength (m).
+    Refractive(&'a Gradient<LinSrgba>, f64, [Formula; 2]),
     /// Luminous surface, brightness multiplier.
     Luminous(&'a Gradient<LinSrgba>, f64),
     /// Switchable condition, conditional value.
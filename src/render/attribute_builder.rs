@@ -4,7 +4,19 @@ use palette::{Gradient, LinSrgba};
 use serde::Deserialize;
 use std::collections::HashMap;
 
-use crate::render::Attribute;
+use crate::{
+    parse::{Material, PbrMaterial},
+    phys::{Formula, FormulaBuilder},
+    render::Attribute,
+    Error,
+};
+
+/// Specular exponent above which a material is treated as a mirror.
+const MIRROR_SPEC_EXP: f64 = 900.0;
+/// Metalness above which a physically based material may be treated as a mirror.
+const MIRROR_METALLIC: f64 = 0.5;
+/// Roughness below which a metallic physically based material is treated as a mirror.
+const MIRROR_ROUGHNESS: f64 = 0.25;
 
 /// Attribute builder.
 #[derive(Deserialize)]
@@ -18,6 +30,8 @@ pub enum AttributeBuilder {
     Transparent(String, f64),
     /// Refractive, absorption fraction, inside and outside refractive indices.
     Refractive(String, f64, [f64; 2]),
+    /// Dispersive refractive, absorption fraction, inside and outside refractive indices as functions of wavelength (m).
+    Dispersive(String, f64, [FormulaBuilder; 2]),
     /// Luminous surface, brightness multiplier.
     Luminous(String, f64),
     /// Switchable condition, conditional value.
@@ -25,6 +39,63 @@ pub enum AttributeBuilder {
 }
 
 impl AttributeBuilder {
+    /// Construct an attribute approximating a wavefront material, coloured by the named gradient.
+    /// Translucent materials become `Refractive` if they have a refractive index greater than unity,
+    /// or `Transparent` otherwise.
+    /// Reflective illumination models, or high specular exponents, become `Mirror`s.
+    #[inline]
+    #[must_use]
+    pub fn from_material(mat: &Material, grad: String) -> Self {
+        let opacity = mat.opacity.clamp(0.0, 1.0);
+        let specular = (mat.specular.iter().sum::<f64>() / 3.0).clamp(0.0, 1.0);
+
+        if opacity < 1.0 {
+            let refractive = mat.ref_index > 1.0 || matches!(mat.illum, Some(4 | 6 | 7 | 9));
+            if refractive && mat.ref_index >= 1.0 {
+                return Self::Refractive(grad, opacity, [mat.ref_index, 1.0]);
+            }
+            return Self::Transparent(grad, opacity);
+        }
+
+        let reflective = matches!(mat.illum, Some(3 | 5 | 8))
+            || (mat.spec_exp >= MIRROR_SPEC_EXP && specular > 0.0);
+        if reflective {
+            return Self::Mirror(grad, 1.0 - specular);
+        }
+
+        Self::Opaque(grad)
+    }
+
+    /// Construct an attribute approximating a physically based material, coloured by the named gradient.
+    /// Emissive materials become `Luminous`, transmissive materials become `Refractive`,
+    /// and alpha blended materials become `Transparent`.
+    /// Smooth metals become `Mirror`s.
+    #[inline]
+    #[must_use]
+    pub fn from_pbr(mat: &PbrMaterial, grad: String) -> Self {
+        let emission = mat.emissive.iter().copied().fold(0.0_f64, f64::max);
+        if emission > 0.0 {
+            return Self::Luminous(grad, 1.0 + emission);
+        }
+
+        let transmission = mat.transmission.clamp(0.0, 1.0);
+        if transmission > 0.0 {
+            return Self::Refractive(grad, 1.0 - transmission, [mat.ref_index.max(1.0), 1.0]);
+        }
+
+        let alpha = mat.base_colour[3].clamp(0.0, 1.0);
+        if mat.blend && alpha < 1.0 {
+            return Self::Transparent(grad, alpha);
+        }
+
+        let metallic = mat.metallic.clamp(0.0, 1.0);
+        if metallic >= MIRROR_METALLIC && mat.roughness <= MIRROR_ROUGHNESS {
+            return Self::Mirror(grad, 1.0 - metallic);
+        }
+
+        Self::Opaque(grad)
+    }
+
     /// Get the names of the `Gradient`s used.
     #[inline]
     #[must_use]
@@ -34,6 +105,7 @@ impl AttributeBuilder {
             | Self::Mirror(ref grad, ..)
             | Self::Transparent(ref grad, ..)
             | Self::Refractive(ref grad, ..)
+            | Self::Dispersive(ref grad, ..)
             | Self::Luminous(ref grad, ..) => vec![grad.clone()],
             Self::Switchable(ref grads, ..) => grads.clone().to_vec(),
         }
@@ -42,51 +114,110 @@ impl AttributeBuilder {
 
 impl<'a> AttributeBuilder {
     /// Build the `Attribute`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a required `Gradient` is not present in the given dictionary,
+    /// or if a refractive index formula is invalid.
     #[inline]
-    #[must_use]
-    pub fn build(self, grads: &'a HashMap<String, Gradient<LinSrgba>>) -> Attribute<'a> {
-        match self {
-            Self::Opaque(ref grad) => Attribute::Opaque(
-                grads
-                    .get(grad)
-                    .unwrap_or_else(|| panic!("Failed to link attribute-gradient key: {grad}")),
-            ),
-            Self::Mirror(ref grad, abs_frac) => Attribute::Mirror(
-                grads
-                    .get(grad)
-                    .unwrap_or_else(|| panic!("Failed to link attribute-gradient key: {grad}")),
-                abs_frac,
-            ),
-            Self::Transparent(ref grad, abs_frac) => Attribute::Transparent(
-                grads
-                    .get(grad)
-                    .unwrap_or_else(|| panic!("Failed to link attribute-gradient key: {grad}")),
-                abs_frac,
-            ),
-            Self::Refractive(ref grad, abs_frac, ref_indices) => Attribute::Refractive(
-                grads
-                    .get(grad)
-                    .unwrap_or_else(|| panic!("Failed to link attribute-gradient key: {grad}")),
+    pub fn build(
+        self,
+        grads: &'a HashMap<String, Gradient<LinSrgba>>,
+    ) -> Result<Attribute<'a>, Error> {
+        let link = |name: &String| {
+            grads
+                .get(name)
+                .ok_or_else(|| Error::MissingKey("gradient", name.clone()))
+        };
+
+        Ok(match self {
+            Self::Opaque(ref grad) => Attribute::Opaque(link(grad)?),
+            Self::Mirror(ref grad, abs_frac) => Attribute::Mirror(link(grad)?, abs_frac),
+            Self::Transparent(ref grad, abs_frac) => Attribute::Transparent(link(grad)?, abs_frac),
+            Self::Refractive(ref grad, abs_frac, [inside, outside]) => Attribute::Refractive(
+                link(grad)?,
                 abs_frac,
-                ref_indices,
-            ),
-            Self::Luminous(ref grad, bright_mult) => Attribute::Luminous(
-                grads
-                    .get(grad)
-                    .unwrap_or_else(|| panic!("Failed to link attribute-gradient key: {grad}")),
-                bright_mult,
+                [Formula::Constant(inside), Formula::Constant(outside)],
             ),
-            Self::Switchable([ref grad_a, ref grad_b], x) => Attribute::Switchable(
-                [
-                    grads.get(grad_a).unwrap_or_else(|| {
-                        panic!("Failed to link attribute-gradient key: {grad_a}")
-                    }),
-                    grads.get(grad_b).unwrap_or_else(|| {
-                        panic!("Failed to link attribute-gradient key: {grad_b}")
-                    }),
-                ],
-                x,
-            ),
-        }
+            Self::Dispersive(ref grad, abs_frac, [ref inside, ref outside]) => {
+                Attribute::Refractive(link(grad)?, abs_frac, [inside.build()?, outside.build()?])
+            }
+            Self::Luminous(ref grad, bright_mult) => Attribute::Luminous(link(grad)?, bright_mult),
+            Self::Switchable([ref grad_a, ref grad_b], x) => {
+                Attribute::Switchable([link(grad_a)?, link(grad_b)?], x)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Construct a material from its defaults with the given changes.
+    fn material(change: impl FnOnce(&mut Material)) -> Material {
+        let mut mat = Material::default();
+        change(&mut mat);
+        mat
+    }
+
+    /// Map a material to an attribute coloured by the gradient "grad".
+    fn map(mat: &Material) -> AttributeBuilder {
+        AttributeBuilder::from_material(mat, "grad".to_owned())
+    }
+
+    #[test]
+    fn materials_map_to_attributes() {
+        assert!(
+            matches!(map(&Material::default()), AttributeBuilder::Opaque(ref grad) if grad == "grad")
+        );
+
+        // Translucency.
+        assert!(matches!(
+            map(&material(|mat| mat.opacity = 0.25)),
+            AttributeBuilder::Transparent(_, abs_frac) if abs_frac == 0.25
+        ));
+        assert!(matches!(
+            map(&material(|mat| {
+                mat.opacity = 0.5;
+                mat.ref_index = 1.5;
+            })),
+            AttributeBuilder::Refractive(_, abs_frac, [1.5, 1.0]) if abs_frac == 0.5
+        ));
+        assert!(matches!(
+            map(&material(|mat| {
+                mat.opacity = 0.5;
+                mat.illum = Some(7);
+            })),
+            AttributeBuilder::Refractive(_, _, [1.0, 1.0])
+        ));
+        assert!(matches!(
+            map(&material(|mat| {
+                mat.opacity = 0.5;
+                mat.ref_index = 0.5;
+                mat.illum = Some(7);
+            })),
+            AttributeBuilder::Transparent(..)
+        ));
+
+        // Reflection.
+        assert!(matches!(
+            map(&material(|mat| mat.illum = Some(3))),
+            AttributeBuilder::Mirror(_, abs_frac) if abs_frac == 1.0
+        ));
+        assert!(matches!(
+            map(&material(|mat| {
+                mat.specular = [0.5, 0.75, 1.0];
+                mat.spec_exp = 1000.0;
+            })),
+            AttributeBuilder::Mirror(_, abs_frac) if abs_frac == 0.25
+        ));
+        assert!(matches!(
+            map(&material(|mat| {
+                mat.specular = [1.0; 3];
+                mat.spec_exp = 100.0;
+            })),
+            AttributeBuilder::Opaque(_)
+        ));
     }
 }
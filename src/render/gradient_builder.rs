@@ -3,6 +3,11 @@
 use palette::{Gradient, LinSrgba};
 use serde::Deserialize;
 
+use crate::{
+    parse::{Material, PbrMaterial},
+    Error,
+};
+
 /// Colour gradient.
 #[derive(Deserialize)]
 pub struct GradientBuilder(
@@ -11,24 +16,72 @@ pub struct GradientBuilder(
 );
 
 impl GradientBuilder {
-    /// Build the colour gradient instance.
+    /// Build a uniform gradient from the diffuse colour and opacity of a material.
+    #[inline]
+    #[must_use]
+    pub fn build_material(mat: &Material) -> Gradient<LinSrgba> {
+        let [r, g, b] = mat.diffuse;
+        uniform([r, g, b, mat.opacity])
+    }
+
+    /// Build a uniform gradient from the base colour of a physically based material.
     #[inline]
     #[must_use]
-    pub fn build(self) -> Gradient<LinSrgba> {
+    pub fn build_pbr(mat: &PbrMaterial) -> Gradient<LinSrgba> {
+        uniform(mat.base_colour)
+    }
+
+    /// Build the colour gradient instance.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the gradient contains no colours,
+    /// or if a colour is not a valid RGB or RGBA hexadecimal string.
+    #[inline]
+    pub fn build(self) -> Result<Gradient<LinSrgba>, Error> {
+        if self.0.is_empty() {
+            return Err(Error::InvalidParameter(
+                "Colour gradient requires at least one colour.".to_owned(),
+            ));
+        }
+
         let mut cols = Vec::with_capacity(self.0.len());
 
         for col in self.0 {
-            let col_arr = hex::decode(col.replace('#', ""))
-                .unwrap_or_else(|_| panic!("Failed to parse hexadecimal string: {col}."));
+            let col_arr = hex::decode(col.replace('#', "")).map_err(|err| {
+                Error::InvalidParameter(format!(
+                    "Failed to parse hexadecimal string: {col} ({err})."
+                ))
+            })?;
+
+            let alpha = match col_arr.len() {
+                3 => 255,
+                4 => col_arr[3],
+                _ => {
+                    return Err(Error::InvalidParameter(format!(
+                        "Hexadecimal colour must have three or four channels: {col}."
+                    )))
+                }
+            };
 
             let r = f32::from(col_arr[0]) / 255.0;
             let g = f32::from(col_arr[1]) / 255.0;
             let b = f32::from(col_arr[2]) / 255.0;
-            let a = f32::from(col_arr[3]) / 255.0;
+            let a = f32::from(alpha) / 255.0;
 
             cols.push(LinSrgba::new(r, g, b, a));
         }
 
-        Gradient::new(cols)
+        Ok(Gradient::new(cols))
     }
 }
+
+/// Construct a gradient of a single colour, clamping each channel to the unit range.
+#[inline]
+#[must_use]
+fn uniform(channels: [f64; 4]) -> Gradient<LinSrgba> {
+    let [r, g, b, a] = channels.map(|x| x.clamp(0.0, 1.0) as f32);
+    let col = LinSrgba::new(r, g, b, a);
+
+    Gradient::new(vec![col, col])
+}
@@ -1,7 +1,7 @@
 //! Program runtime.
 
 use crate::{
-    dom::Tree,
+    dom::Partition,
     render::{Attribute, Settings, Shader},
 };
 
@@ -12,14 +12,18 @@ pub struct Input<'a> {
     /// Aesthetic settings.
     pub shader: Shader<'a>,
     /// Scene hierarchy.
-    pub tree: Tree<'a, Attribute<'a>>,
+    pub tree: Partition<'a, Attribute<'a>>,
 }
 
 impl<'a> Input<'a> {
     /// Construct a new instance.
     #[inline]
     #[must_use]
-    pub const fn new(settings: Settings, shader: Shader<'a>, tree: Tree<'a, Attribute>) -> Self {
+    pub const fn new(
+        settings: Settings,
+        shader: Shader<'a>,
+        tree: Partition<'a, Attribute>,
+    ) -> Self {
         Self {
             settings,
             shader,
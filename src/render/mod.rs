@@ -7,11 +7,13 @@ pub mod input;
 pub mod output;
 pub mod parameters;
 pub mod run;
+pub mod sample;
 pub mod settings;
 pub mod shader;
 pub mod shader_builder;
+pub mod spectrum;
 
 pub use self::{
     attribute::*, attribute_builder::*, gradient_builder::*, input::*, output::*, parameters::*,
-    run::*, settings::*, shader::*, shader_builder::*,
+    run::*, sample::*, settings::*, shader::*, shader_builder::*, spectrum::*,
 };
@@ -1,16 +1,22 @@
 //! Output data.
 
-use ndarray::Array2;
+use ndarray::{s, Array2, Array3};
 use ndarray_stats::QuantileExt;
 use palette::LinSrgba;
 use std::path::Path;
 
-use crate::{parse::png, render::Shader};
+use crate::{
+    parse::png,
+    render::{Shader, Spectrum},
+    Error,
+};
 
 /// Saveable output data.
 pub struct Output {
     /// Colour data.
     pub colour: Array2<LinSrgba>,
+    /// Spectral data, per wavelength bin.
+    pub spectrum: Array3<f64>,
     /// Time data.
     pub time: Array2<f64>,
     // /// Thread data.
@@ -21,35 +27,83 @@ impl Output {
     /// Construct a new instance.
     #[inline]
     #[must_use]
-    pub fn new(res: [usize; 2]) -> Self {
+    pub fn new(res: [usize; 2], num_bins: usize) -> Self {
         let colour = Array2::from_elem(res, LinSrgba::new(0.0, 0.0, 0.0, 0.0));
+        let spectrum = Array3::zeros([res[0], res[1], num_bins]);
         let time = Array2::zeros(res);
         // let thread = Array2::zeros(res);
 
         Self {
             colour,
+            spectrum,
             time,
             // thread,
         }
     }
 
+    /// Get the resolution of the data.
+    #[inline]
+    #[must_use]
+    pub fn res(&self) -> [usize; 2] {
+        let shape = self.colour.shape();
+        [shape[0], shape[1]]
+    }
+
+    /// Write the data of a rendered sub-tile into place at the given pixel offset.
+    #[inline]
+    pub fn paste(&mut self, tile: &Self, offset: [usize; 2]) {
+        let [width, height] = tile.res();
+        debug_assert!(offset[0] + width <= self.res()[0]);
+        debug_assert!(offset[1] + height <= self.res()[1]);
+
+        let slice = s![offset[0]..offset[0] + width, offset[1]..offset[1] + height];
+        self.colour.slice_mut(slice).assign(&tile.colour);
+        self.spectrum
+            .slice_mut(s![
+                offset[0]..offset[0] + width,
+                offset[1]..offset[1] + height,
+                ..
+            ])
+            .assign(&tile.spectrum);
+        self.time.slice_mut(slice).assign(&tile.time);
+    }
+
+    /// Convert the spectral data to colours, keeping the opacity of the colour data.
+    #[inline]
+    #[must_use]
+    pub fn spectral_colour(&self, spectrum: &Spectrum) -> Array2<LinSrgba> {
+        Array2::from_shape_fn(self.colour.dim(), |(x, y)| {
+            spectrum.colour(self.spectrum.slice(s![x, y, ..]), self.colour[[x, y]].alpha)
+        })
+    }
+
     /// Save the output, in it's current state, to the given output directory.
+    /// Files are suffixed with the tag, if provided.
+    /// The colour image is converted from the spectral data if the shader is spectral.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an image file can not be written.
     #[inline]
-    pub fn save(&self, shader: &Shader, output_dir: &Path, tag: &str) {
-        png::save(
-            self.colour.view(),
-            &output_dir
-                .join(format!("colour_{tag}"))
-                .with_extension("png"),
+    pub fn save(&self, shader: &Shader, output_dir: &Path, tag: Option<&str>) -> Result<(), Error> {
+        let file_name = |name: &str| tag.map_or_else(|| name.to_owned(), |t| format!("{name}_{t}"));
+
+        let colour = shader.spectrum.as_ref().map_or_else(
+            || self.colour.clone(),
+            |spectrum| self.spectral_colour(spectrum),
         );
+        png::save(
+            colour.view(),
+            &output_dir.join(file_name("colour")).with_extension("png"),
+        )?;
 
         let max_time = self.time.max().expect("Failed to resolve time data.");
         png::save(
             self.time
                 .map(|t| shader.data_grad.get((t / max_time) as f32))
                 .view(),
-            &output_dir.join(format!("time_{tag}")).with_extension("png"),
-        );
+            &output_dir.join(file_name("time")).with_extension("png"),
+        )?;
 
         // let max_thread_id = self.thread.max().expect("Failed to resolve thread data.") + 1;
         // png::save(
@@ -60,5 +114,7 @@ impl Output {
         //         .join(&format!("thread_{}", tag))
         //         .with_extension("png"),
         // );
+
+        Ok(())
     }
 }
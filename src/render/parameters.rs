@@ -2,14 +2,24 @@
 
 use palette::{Gradient, LinSrgba};
 use serde::Deserialize;
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+    slice,
+};
 
 use crate::{
-    dom::{Surface, SurfaceBuilder, Tree, TreeBuilder},
-    geom::Mesh,
-    parse::{json, wavefront},
+    dom::{
+        Bvh, BvhBuilder, Instance, InstanceBuilder, InstanceBvh, Partition, Surface,
+        SurfaceBuilder, Tree, TreeBuilder,
+    },
+    geom::{Mesh, ShapeBuilder},
+    parse::{gltf, json, mtl, ply, stl, wavefront, Material, Scene},
     render::{Attribute, AttributeBuilder, GradientBuilder, Settings, Shader, ShaderBuilder},
     rt::{Camera, CameraBuilder},
+    Error,
 };
 
 /// Input configuration.
@@ -19,39 +29,200 @@ pub struct Parameters {
     resources_dir: PathBuf,
     /// Oct-tree settings.
     tree: TreeBuilder,
+    /// Optional bounding volume hierarchy settings, used in place of the oct-tree if given.
+    bvh: Option<BvhBuilder>,
+    /// Optional directory in which built oct-trees are cached between runs.
+    tree_cache: Option<PathBuf>,
+    /// Optional path at which the statistics of built oct-trees are saved as JSON.
+    tree_stats: Option<PathBuf>,
     /// Technical settings.
     settings: Settings,
     /// Aesthetic settings.
     shader: ShaderBuilder,
     /// Main camera.
     camera: CameraBuilder,
-    /// Surfaces.
-    surfaces: Vec<SurfaceBuilder>,
+    /// Optional surfaces.
+    surfaces: Option<Vec<SurfaceBuilder>>,
+    /// Optional analytic shapes, each with an attribute name.
+    shapes: Option<Vec<(ShapeBuilder, String)>>,
+    /// Optional mesh instances, sharing the geometry of their meshes.
+    instances: Option<Vec<InstanceBuilder>>,
+    /// Optional bounding volume hierarchy settings used within each instanced mesh.
+    instance_bvh: Option<BvhBuilder>,
+    /// Optional STL vertex weld distance, and smoothing angle (deg).
+    /// STL meshes are loaded with face normals if not given.
+    stl_smoothing: Option<(f64, f64)>,
+    /// Optional wavefront models, with surfaces assigned from their material libraries.
+    models: Option<Vec<String>>,
+    /// Optional glTF scenes, with surfaces assigned from their materials.
+    /// The first scene camera found sets the view of the main camera.
+    scenes: Option<Vec<String>>,
+}
+
+/// Wavefront model, read once and shared between the loading stages.
+pub struct Model {
+    /// Materials used by the model, in order of first use, keyed by resource key.
+    pub materials: Vec<(String, Material)>,
+    /// Mesh of each material, keyed by resource key.
+    pub meshes: HashMap<String, Mesh>,
 }
 
 impl Parameters {
-    /// Get the names of the `Gradient`s used.
+    /// Load the parameters from a JSON file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can not be read or parsed.
+    #[inline]
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        json::load(path)
+    }
+
+    /// Load the `AttributeBuilder` with the given name.
+    #[inline]
+    fn load_attribute_builder(&self, name: &str) -> Result<AttributeBuilder, Error> {
+        json::load(
+            &self
+                .resources_dir
+                .join("attributes")
+                .join(name)
+                .with_extension("json"),
+        )
+    }
+
+    /// Get the names of the wavefront models used.
     #[inline]
     #[must_use]
-    pub fn used_gradient_names(&self) -> Vec<String> {
+    pub fn used_model_names(&self) -> Vec<String> {
+        let mut names = self.models.clone().unwrap_or_default();
+
+        names.sort();
+        names.dedup();
+
+        names
+    }
+
+    /// Load the named wavefront model, with the materials of its material libraries.
+    /// Triangles are gathered into one mesh per material.
+    #[inline]
+    fn load_model(&self, name: &str) -> Result<Model, Error> {
+        let path = self
+            .resources_dir
+            .join("meshes")
+            .join(name)
+            .with_extension("obj");
+        let s = fs::read_to_string(&path).map_err(|err| Error::Io(path.clone(), err))?;
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+        let mut libs = HashMap::new();
+        for lib in wavefront::read_material_libs(&s) {
+            libs.extend(mtl::load(&dir.join(lib))?);
+        }
+
+        let materials = wavefront::read_material_names(&s)
+            .into_iter()
+            .map(|mat_name| {
+                let mat = libs
+                    .get(&mat_name)
+                    .ok_or_else(|| Error::MissingKey("material", mat_name.clone()))?;
+                Ok((model_key(name, &mat_name), mat.clone()))
+            })
+            .collect::<Result<_, Error>>()?;
+
+        let mut tris = HashMap::new();
+        for group in wavefront::read_groups(&s).map_err(|err| err.with_path(&path))? {
+            let mat_name = group
+                .material
+                .ok_or_else(|| Error::MissingKey("material", format!("{name}: {}", group.name)))?;
+            tris.entry(model_key(name, &mat_name))
+                .or_insert_with(Vec::new)
+                .extend(group.mesh.tris);
+        }
+        let meshes = tris
+            .into_iter()
+            .map(|(key, tris)| (key, Mesh::new(tris)))
+            .collect();
+
+        Ok(Model { materials, meshes })
+    }
+
+    /// Load the dictionary of wavefront `Model`s.
+    /// Each model, and its material libraries, is read once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a model or material library file can not be loaded,
+    /// or if the model uses a material which is not defined.
+    #[inline]
+    pub fn load_models(&self) -> Result<HashMap<String, Model>, Error> {
+        self.used_model_names()
+            .into_iter()
+            .map(|name| {
+                let model = self.load_model(&name)?;
+                Ok((name, model))
+            })
+            .collect()
+    }
+
+    /// Get the names of the glTF scenes used.
+    #[inline]
+    #[must_use]
+    pub fn used_scene_names(&self) -> Vec<String> {
+        let mut names = self.scenes.clone().unwrap_or_default();
+
+        names.sort();
+        names.dedup();
+
+        names
+    }
+
+    /// Load the named glTF scene.
+    /// Names without an extension are assumed to be `.gltf` files.
+    #[inline]
+    fn load_scene(&self, name: &str) -> Result<Scene, Error> {
+        let path = self.resources_dir.join("scenes").join(name);
+        if path.extension().is_none() {
+            gltf::load(&path.with_extension("gltf"))
+        } else {
+            gltf::load(&path)
+        }
+    }
+
+    /// Load the dictionary of glTF `Scene`s.
+    /// Each scene is parsed once, and shared between the loading stages.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a scene file can not be loaded.
+    #[inline]
+    pub fn load_scenes(&self) -> Result<HashMap<String, Scene>, Error> {
+        self.used_scene_names()
+            .into_iter()
+            .map(|name| {
+                let scene = self.load_scene(&name)?;
+                Ok((name, scene))
+            })
+            .collect()
+    }
+
+    /// Get the names of the `Gradient`s used.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an attribute file can not be loaded.
+    #[inline]
+    pub fn used_gradient_names(&self) -> Result<Vec<String>, Error> {
         let mut gradient_names = Vec::new();
 
         gradient_names.extend(self.shader.used_gradient_names());
-        gradient_names.extend(&mut self.used_attribute_names().iter().flat_map(|n| {
-            json::load::<AttributeBuilder>(
-                &self
-                    .resources_dir
-                    .join("attributes")
-                    .join(n)
-                    .with_extension("json"),
-            )
-            .used_gradient_names()
-        }));
+        for name in self.used_attribute_names() {
+            gradient_names.extend(self.load_attribute_builder(&name)?.used_gradient_names());
+        }
 
         gradient_names.sort();
         gradient_names.dedup();
 
-        gradient_names
+        Ok(gradient_names)
     }
 
     /// Get the names of the `Attribute`s used.
@@ -60,9 +231,15 @@ impl Parameters {
     pub fn used_attribute_names(&self) -> Vec<String> {
         let mut names = Vec::new();
 
-        for surf in &self.surfaces {
+        for surf in self.surfaces.iter().flatten() {
             names.push(surf.1.clone());
         }
+        for &(_, ref attr) in self.shapes.iter().flatten() {
+            names.push(attr.clone());
+        }
+        for inst in self.instances.iter().flatten() {
+            names.push(inst.attr.clone());
+        }
 
         names.sort();
         names.dedup();
@@ -76,9 +253,12 @@ impl Parameters {
     pub fn used_mesh_names(&self) -> Vec<String> {
         let mut names = Vec::new();
 
-        for surf in &self.surfaces {
+        for surf in self.surfaces.iter().flatten() {
             names.push(surf.0.clone());
         }
+        for inst in self.instances.iter().flatten() {
+            names.push(inst.mesh.clone());
+        }
 
         names.sort();
         names.dedup();
@@ -87,88 +267,250 @@ impl Parameters {
     }
 
     /// Load the dictionary of `Gradients`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a gradient file can not be loaded or built.
     #[inline]
-    #[must_use]
-    pub fn load_gradients(&self) -> HashMap<String, Gradient<LinSrgba>> {
+    pub fn load_gradients(
+        &self,
+        models: &HashMap<String, Model>,
+        scenes: &HashMap<String, Scene>,
+    ) -> Result<HashMap<String, Gradient<LinSrgba>>, Error> {
         let mut grads = HashMap::new();
 
-        for name in self.used_gradient_names() {
+        for name in self.used_gradient_names()? {
             let grad = json::load::<GradientBuilder>(
                 &self
                     .resources_dir
                     .join("gradients")
                     .join(name.clone())
                     .with_extension("json"),
-            )
-            .build();
+            )?
+            .build()?;
             grads.insert(name, grad);
         }
 
-        grads
+        for model in models.values() {
+            for &(ref key, ref mat) in &model.materials {
+                grads.insert(key.clone(), GradientBuilder::build_material(mat));
+            }
+        }
+
+        for (name, scene) in scenes {
+            for surf in &scene.surfaces {
+                let grad = GradientBuilder::build_pbr(&surf.material);
+                grads.insert(model_key(name, &surf.name), grad);
+            }
+        }
+
+        Ok(grads)
     }
 
     /// Load the dictionary of `Attributes`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an attribute file can not be loaded,
+    /// or if it references a missing `Gradient`.
     #[inline]
-    #[must_use]
     pub fn load_attributes<'a>(
         &self,
+        models: &HashMap<String, Model>,
+        scenes: &HashMap<String, Scene>,
         grads: &'a HashMap<String, Gradient<LinSrgba>>,
-    ) -> HashMap<String, Attribute<'a>> {
+    ) -> Result<HashMap<String, Attribute<'a>>, Error> {
         let mut attrs = HashMap::new();
 
         for name in self.used_attribute_names() {
-            let attr = json::load::<AttributeBuilder>(
-                &self
-                    .resources_dir
-                    .join("attributes")
-                    .join(name.clone())
-                    .with_extension("json"),
-            )
-            .build(grads);
+            let attr = self.load_attribute_builder(&name)?.build(grads)?;
             attrs.insert(name, attr);
         }
 
-        attrs
+        for model in models.values() {
+            for &(ref key, ref mat) in &model.materials {
+                let attr = AttributeBuilder::from_material(mat, key.clone()).build(grads)?;
+                attrs.insert(key.clone(), attr);
+            }
+        }
+
+        for (name, scene) in scenes {
+            for surf in &scene.surfaces {
+                let key = model_key(name, &surf.name);
+                let attr = AttributeBuilder::from_pbr(&surf.material, key.clone()).build(grads)?;
+                attrs.insert(key, attr);
+            }
+        }
+
+        Ok(attrs)
     }
 
     /// Load the dictionary of `Meshes`.
+    /// The loader is selected by the file extension of the mesh name, defaulting to wavefront.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a mesh file can not be loaded,
+    /// or if the STL weld distance or smoothing angle is negative.
     #[inline]
-    #[must_use]
-    pub fn load_meshes(&self) -> HashMap<String, Mesh> {
+    pub fn load_meshes(
+        &self,
+        models: &HashMap<String, Model>,
+        scenes: &HashMap<String, Scene>,
+    ) -> Result<HashMap<String, Mesh>, Error> {
+        let smoothing = match self.stl_smoothing {
+            Some((weld_dist, angle)) if weld_dist < 0.0 || angle < 0.0 => {
+                return Err(Error::InvalidParameter(format!(
+                    "STL weld distance and smoothing angle must be non-negative: {weld_dist}, {angle}"
+                )));
+            }
+            Some((weld_dist, angle)) => Some((weld_dist, angle.to_radians())),
+            None => None,
+        };
+
         let mut meshes = HashMap::new();
 
         for name in self.used_mesh_names() {
-            let mesh = wavefront::load(
-                &self
-                    .resources_dir
-                    .join("meshes")
-                    .join(name.clone())
-                    .with_extension("obj"),
-            );
+            let mesh = load_mesh(
+                &self.resources_dir.join("meshes").join(name.clone()),
+                smoothing,
+            )?;
             meshes.insert(name, mesh);
         }
 
-        meshes
+        for model in models.values() {
+            meshes.extend(model.meshes.clone());
+        }
+
+        for (name, scene) in scenes {
+            for surf in &scene.surfaces {
+                meshes.insert(model_key(name, &surf.name), surf.mesh.clone());
+            }
+        }
+
+        Ok(meshes)
     }
 
     /// Load the `Surface`s.
+    /// Model and scene surfaces are appended in name order, so the tree cache key is stable.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a surface references a missing `Mesh` or `Attribute`,
+    /// if a shape is invalid, or if a model file can not be read.
     #[inline]
-    #[must_use]
     pub fn load_surfaces<'a>(
         &self,
+        models: &HashMap<String, Model>,
+        scenes: &HashMap<String, Scene>,
         meshes: &HashMap<String, Mesh>,
         attributes: &'a HashMap<String, Attribute>,
-    ) -> Vec<Surface<Attribute<'a>>> {
-        self.surfaces
+    ) -> Result<Vec<Surface<'a, Attribute<'a>>>, Error> {
+        let mut surfs = self
+            .surfaces
             .iter()
+            .flatten()
             .map(|s| s.clone().build(meshes, attributes))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for &(ref builder, ref attr) in self.shapes.iter().flatten() {
+            let attr = attributes
+                .get(attr)
+                .ok_or_else(|| Error::MissingKey("attribute", attr.clone()))?;
+            surfs.push(Surface::from_shapes(vec![builder.build()?], attr));
+        }
+
+        for name in self.used_model_names() {
+            for &(ref key, _) in models.get(&name).iter().flat_map(|model| &model.materials) {
+                let key = key.clone();
+                if meshes.contains_key(&key) {
+                    surfs.push(SurfaceBuilder(key.clone(), key, None).build(meshes, attributes)?);
+                }
+            }
+        }
+
+        for name in self.used_scene_names() {
+            for surf in scenes.get(&name).iter().flat_map(|scene| &scene.surfaces) {
+                let key = model_key(&name, &surf.name);
+                surfs.push(SurfaceBuilder(key.clone(), key, None).build(meshes, attributes)?);
+            }
+        }
+
+        Ok(surfs)
+    }
+
+    /// Load the object-space `Surface`s of the instanced `Meshes`.
+    /// Each mesh is copied once, however many times it is instanced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an instance references a missing `Mesh`.
+    #[inline]
+    pub fn load_objects(
+        &self,
+        meshes: &HashMap<String, Mesh>,
+    ) -> Result<HashMap<String, Surface<'static, ()>>, Error> {
+        let mut objects = HashMap::new();
+        for inst in self.instances.iter().flatten() {
+            if objects.contains_key(&inst.mesh) {
+                continue;
+            }
+
+            let mesh = meshes
+                .get(&inst.mesh)
+                .ok_or_else(|| Error::MissingKey("mesh", inst.mesh.clone()))?;
+            objects.insert(inst.mesh.clone(), Surface::new(mesh.clone(), &()));
+        }
+
+        Ok(objects)
+    }
+
+    /// Build the object-space hierarchies of the instanced `Surface`s.
+    #[inline]
+    #[must_use]
+    pub fn build_objects<'a>(
+        &self,
+        objects: &'a HashMap<String, Surface<'a, ()>>,
+    ) -> HashMap<String, Bvh<'a, ()>> {
+        let default_sett = BvhBuilder::default();
+        let sett = self.instance_bvh.as_ref().unwrap_or(&default_sett);
+
+        objects
+            .iter()
+            .map(|(name, surf)| (name.clone(), Bvh::new(sett, slice::from_ref(surf))))
+            .collect()
+    }
+
+    /// Load the mesh `Instance`s.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an instance references a missing object or `Attribute`,
+    /// or if its transformation is invalid.
+    #[inline]
+    pub fn load_instances<'a>(
+        &self,
+        objects: &'a HashMap<String, Bvh<'a, ()>>,
+        attributes: &'a HashMap<String, Attribute>,
+    ) -> Result<Vec<Instance<'a, Attribute<'a>>>, Error> {
+        self.instances
+            .iter()
+            .flatten()
+            .map(|inst| inst.build(objects, attributes))
             .collect()
     }
 
     /// Build the `Shader`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the shader settings are invalid,
+    /// or if they reference a missing `Gradient`.
     #[inline]
-    #[must_use]
-    pub fn build_shader<'a>(&self, grads: &'a HashMap<String, Gradient<LinSrgba>>) -> Shader<'a> {
+    pub fn build_shader<'a>(
+        &self,
+        grads: &'a HashMap<String, Gradient<LinSrgba>>,
+    ) -> Result<Shader<'a>, Error> {
         self.shader.build(grads)
     }
 
@@ -179,17 +521,223 @@ impl Parameters {
         self.settings.clone()
     }
 
-    /// Build the `Camera`s.
+    /// Build the `Camera`.
+    /// The view is taken from the first scene, in name order, containing a camera, if any.
     #[inline]
     #[must_use]
-    pub fn build_camera(&self) -> Camera {
+    pub fn build_camera(&self, scenes: &HashMap<String, Scene>) -> Camera {
+        for name in self.used_scene_names() {
+            if let Some(cam) = scenes.get(&name).and_then(|scene| scene.camera.as_ref()) {
+                return self.camera.clone().with_scene_camera(cam).build();
+            }
+        }
+
         self.camera.clone().build()
     }
 
     /// Build the `Tree`.
+    /// The Tree is reloaded from, or saved to, the tree cache directory if one is given,
+    /// and its statistics are saved if a statistics path is given.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tree settings are invalid,
+    /// if the Tree can not be saved to the tree cache directory,
+    /// or if its statistics can not be saved.
     #[inline]
-    #[must_use]
-    pub fn build_tree<'a, T>(&self, surfs: &'a [Surface<T>]) -> Tree<'a, T> {
-        self.tree.build(surfs)
+    pub fn build_tree<'a, T: Sync>(&self, surfs: &'a [Surface<T>]) -> Result<Tree<'a, T>, Error> {
+        let tree = match self.tree_cache {
+            Some(ref cache_dir) => self.tree.build_cached(surfs, cache_dir)?,
+            None => self.tree.build(surfs)?,
+        };
+
+        if let Some(ref path) = self.tree_stats {
+            json::save(&tree.stats(), path)?;
+        }
+
+        Ok(tree)
+    }
+
+    /// Build the scene `Partition`.
+    /// A bounding volume hierarchy is used if its settings are given, otherwise an oct-tree.
+    /// Any instances are placed in a top-level hierarchy alongside the surfaces.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an oct-tree can not be built.
+    #[inline]
+    pub fn build_partition<'a, T: Sync>(
+        &self,
+        surfs: &'a [Surface<T>],
+        instances: Vec<Instance<'a, T>>,
+    ) -> Result<Partition<'a, T>, Error> {
+        if instances.is_empty() {
+            return self.build_surface_partition(surfs);
+        }
+
+        let base = if surfs.is_empty() {
+            None
+        } else {
+            Some(self.build_surface_partition(surfs)?)
+        };
+
+        Ok(Partition::new_instanced(base, InstanceBvh::new(instances)))
+    }
+
+    /// Build the `Partition` of the surfaces.
+    #[inline]
+    fn build_surface_partition<'a, T: Sync>(
+        &self,
+        surfs: &'a [Surface<T>],
+    ) -> Result<Partition<'a, T>, Error> {
+        if let Some(ref bvh) = self.bvh {
+            return Ok(Partition::Bvh(bvh.build(surfs)));
+        }
+
+        Ok(Partition::Tree(self.build_tree(surfs)?))
+    }
+}
+
+/// Form the resource key of a material within a wavefront model or glTF scene.
+#[inline]
+#[must_use]
+fn model_key(model: &str, material: &str) -> String {
+    format!("{model}:{material}")
+}
+
+/// Load a mesh file, selecting the loader by the file extension.
+/// Paths without an extension are assumed to be wavefront files.
+/// STL meshes are smoothed if a weld distance and smoothing angle (rad) are given.
+#[inline]
+fn load_mesh(path: &Path, stl_smoothing: Option<(f64, f64)>) -> Result<Mesh, Error> {
+    match path.extension().and_then(OsStr::to_str) {
+        None => wavefront::load(&path.with_extension("obj")),
+        Some("obj") => wavefront::load(path),
+        Some("stl") => stl_smoothing.map_or_else(
+            || stl::load(path),
+            |(weld_dist, angle)| stl::load_smoothed(path, weld_dist, angle),
+        ),
+        Some("ply") => Ok(ply::load(path)?.mesh),
+        Some(ext) => Err(Error::InvalidParameter(format!(
+            "Unsupported mesh file extension: {ext}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geom::Cube;
+    use nalgebra::Point3;
+    use palette::LinSrgba;
+
+    /// Construct parameters using the given resource directory and wavefront models.
+    fn parameters(resources_dir: &str, models: &str) -> Parameters {
+        json::read(&format!(
+            "{{
+                resources_dir: '{resources_dir}',
+                tree: {{ tar_tris: 2, max_depth: 4, padding: 0.01 }},
+                settings: {{ tiles: [1, 1], bump_dist: 1e-4, loop_limit: 10, min_weight: 0.01, max_distance: 10, save_tiles: false }},
+                shader: {{ sun_pos: [10, 10, 10], light: [0.3, 0.5, 0.2], shadow: [0.3, 0.7], spec_pow: 8, occ_dist: [10, 10], fall_off: 1, sky_grad: 'sky', data_grad: 'data' }},
+                camera: {{ pos: [-5, -5, 4], tar: [0, 0, 0], fov: 60, res: [4, 3] }},
+                models: {models},
+            }}"
+        ))
+        .expect("Failed to read parameters.")
+    }
+
+    /// Construct a single material model, with a unit cube mesh offset along x.
+    fn model(name: &str, offset: f64) -> Model {
+        let key = model_key(name, "mat");
+        let cube = Cube::new(
+            Point3::new(offset, 0.0, 0.0),
+            Point3::new(offset + 1.0, 1.0, 1.0),
+        );
+
+        let mut meshes = HashMap::new();
+        meshes.insert(key.clone(), Mesh::new(cube.tris()));
+
+        Model {
+            materials: vec![(key, Material::default())],
+            meshes,
+        }
+    }
+
+    #[test]
+    fn surface_order_does_not_depend_on_map_order() {
+        let grad = Gradient::new(vec![LinSrgba::new(1.0, 1.0, 1.0, 1.0)]);
+        let scenes = HashMap::new();
+
+        let keys: Vec<_> = ["['a', 'b']", "['b', 'a']"]
+            .iter()
+            .cycle()
+            .take(8)
+            .enumerate()
+            .map(|(i, names)| {
+                let params = parameters("res", names);
+
+                let mut models = HashMap::new();
+                if i % 2 == 0 {
+                    models.insert("a".to_owned(), model("a", 0.0));
+                    models.insert("b".to_owned(), model("b", 2.0));
+                } else {
+                    models.insert("b".to_owned(), model("b", 2.0));
+                    models.insert("a".to_owned(), model("a", 0.0));
+                }
+
+                let meshes = params
+                    .load_meshes(&models, &scenes)
+                    .expect("Failed to load meshes.");
+                let attrs: HashMap<_, _> = meshes
+                    .keys()
+                    .map(|key| (key.clone(), Attribute::Opaque(&grad)))
+                    .collect();
+                let surfs = params
+                    .load_surfaces(&models, &scenes, &meshes, &attrs)
+                    .expect("Failed to load surfaces.");
+
+                params.tree.cache_key(&surfs)
+            })
+            .collect();
+
+        assert!(keys.iter().all(|&key| key == keys[0]));
+    }
+
+    #[test]
+    fn model_groups_require_materials() {
+        let dir = std::env::temp_dir().join(format!("arctk_models_{}", std::process::id()));
+        let meshes_dir = dir.join("meshes");
+        fs::create_dir_all(&meshes_dir).expect("Failed to create scratch directory.");
+
+        let verts = "v 0 0 0\nv 1 0 0\nv 0 1 0\n";
+        fs::write(meshes_dir.join("lib.mtl"), "newmtl red\nKd 1 0 0\n")
+            .expect("Failed to write file.");
+        fs::write(
+            meshes_dir.join("good.obj"),
+            format!("mtllib lib.mtl\n{verts}usemtl red\nf 1 2 3\n"),
+        )
+        .expect("Failed to write file.");
+        fs::write(
+            meshes_dir.join("bare.obj"),
+            format!("mtllib lib.mtl\n{verts}o tri\nf 1 2 3\n"),
+        )
+        .expect("Failed to write file.");
+
+        let dir_str = dir.to_str().expect("Invalid scratch directory.");
+        let models = parameters(dir_str, "['good']")
+            .load_models()
+            .expect("Failed to load model.");
+        let model = &models["good"];
+        assert_eq!(model.materials.len(), 1);
+        assert_eq!(model.materials[0].0, "good:red");
+        assert_eq!(model.materials[0].1.diffuse, [1.0, 0.0, 0.0]);
+        assert_eq!(model.meshes["good:red"].tris.len(), 1);
+
+        assert!(matches!(
+            parameters(dir_str, "['bare']").load_models(),
+            Err(Error::MissingKey("material", ref name)) if name == "bare: tri"
+        ));
+
+        fs::remove_dir_all(dir).expect("Failed to remove scratch directory.");
     }
 }
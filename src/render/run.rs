@@ -9,12 +9,20 @@ use std::{
 };
 
 use crate::{
-    render::{Input, Output, Parameters},
+    render::{Input, Output, Parameters, Spectrum},
     rt::{Camera, Ray},
     util::ProgressBar,
+    Error,
 };
 
 /// Run the simulation with the given parameterisation.
+/// The stitched image is saved to the output directory, and returned.
+///
+/// # Errors
+///
+/// Returns an error if the input resources can not be loaded,
+/// if the settings are invalid,
+/// or if the output files can not be written.
 #[inline]
 pub fn run<
     T: Fn(&Input<'_>, &Camera, Ray, f64, [usize; 2], &mut Output, &mut ThreadRng) + Send + Sync + Copy,
@@ -22,45 +30,106 @@ pub fn run<
     parameters: &Parameters,
     output_dir: &Path,
     sample: T,
-) {
+) -> Result<Output, Error> {
     // Setup.
     let settings = parameters.build_settings();
-    let meshes = parameters.load_meshes();
-    let gradients = parameters.load_gradients();
-    let attributes = parameters.load_attributes(&gradients);
-    let surfaces = parameters.load_surfaces(&meshes, &attributes);
-    let tree = parameters.build_tree(&surfaces);
-    let shader = parameters.build_shader(&gradients);
-    let camera = parameters.build_camera();
+    let models = parameters.load_models()?;
+    let scenes = parameters.load_scenes()?;
+    let camera = parameters.build_camera(&scenes);
+    check_tiles(settings.tiles, camera.res)?;
+    let meshes = parameters.load_meshes(&models, &scenes)?;
+    let gradients = parameters.load_gradients(&models, &scenes)?;
+    let attributes = parameters.load_attributes(&models, &scenes, &gradients)?;
+    let surfaces = parameters.load_surfaces(&models, &scenes, &meshes, &attributes)?;
+    drop(models);
+    drop(scenes);
+    let object_surfaces = parameters.load_objects(&meshes)?;
+    let objects = parameters.build_objects(&object_surfaces);
+    let instances = parameters.load_instances(&objects, &attributes)?;
+    let tree = parameters.build_partition(&surfaces, instances)?;
+    let shader = parameters.build_shader(&gradients)?;
 
     // Create runtime object.
     let runtime = Input::new(settings, shader, tree);
 
     // Run
-    let tiles_output_dir = output_dir.join("tiles");
-    if tiles_output_dir.exists() {
-        fs::remove_dir_all(&tiles_output_dir).expect("Failed to initialise output directory.");
+    fs::create_dir_all(output_dir).map_err(|err| Error::Io(output_dir.to_path_buf(), err))?;
+    let tiles_output_dir = if runtime.settings.save_tiles.unwrap_or(false) {
+        let tiles_output_dir = output_dir.join("tiles");
+        if tiles_output_dir.exists() {
+            fs::remove_dir_all(&tiles_output_dir)
+                .map_err(|err| Error::Io(tiles_output_dir.clone(), err))?;
+        }
+        fs::create_dir_all(&tiles_output_dir)
+            .map_err(|err| Error::Io(tiles_output_dir.clone(), err))?;
+        Some(tiles_output_dir)
+    } else {
+        None
+    };
+    let data = render(tiles_output_dir.as_deref(), &runtime, &camera, sample)?;
+
+    // Save
+    data.save(&runtime.shader, output_dir, None)?;
+
+    Ok(data)
+}
+
+/// Check that the image can be divided into the given number of tiles.
+#[inline]
+fn check_tiles(tiles: [usize; 2], res: [usize; 2]) -> Result<(), Error> {
+    if tiles[0] == 0 || tiles[1] == 0 {
+        return Err(Error::InvalidParameter(format!(
+            "Tile count {tiles:?} must be greater than zero."
+        )));
+    }
+    if tiles[0] > res[0] || tiles[1] > res[1] {
+        return Err(Error::InvalidParameter(format!(
+            "Tile count {tiles:?} exceeds the image resolution {res:?}."
+        )));
     }
-    fs::create_dir_all(&tiles_output_dir).expect("Failed to create output directory.");
-    render(&tiles_output_dir, &runtime, &camera, sample);
+
+    Ok(())
 }
 
-/// Perform the rendering.
+/// Determine the pixel offset and resolution of a tile.
+/// Tiles at the far edges absorb any remaining pixels.
 #[allow(clippy::integer_division)]
 #[inline]
+#[must_use]
+fn tile_bounds(res: [usize; 2], tiles: [usize; 2], index: [usize; 2]) -> ([usize; 2], [usize; 2]) {
+    let mut offset = [0; 2];
+    let mut tile_res = [0; 2];
+
+    for dim in 0..2 {
+        debug_assert!(index[dim] < tiles[dim]);
+
+        let width = res[dim] / tiles[dim];
+        offset[dim] = width * index[dim];
+        tile_res[dim] = if index[dim] == (tiles[dim] - 1) {
+            res[dim] - offset[dim]
+        } else {
+            width
+        };
+    }
+
+    (offset, tile_res)
+}
+
+/// Perform the rendering.
+/// Individual tiles are saved if a tile output directory is given.
+#[inline]
 fn render<
     T: Fn(&Input<'_>, &Camera, Ray, f64, [usize; 2], &mut Output, &mut ThreadRng)
         + Send
         + Sync
         + Clone,
 >(
-    output_dir: &Path,
+    tiles_output_dir: Option<&Path>,
     input: &Input,
     camera: &Camera,
     sample: T,
-) {
+) -> Result<Output, Error> {
     let tiles = input.settings.tiles;
-    let tile_res = [camera.res[0] / tiles[0], camera.res[1] / tiles[1]];
 
     let mut tile_order = Vec::with_capacity(tiles[0] * tiles[1]);
     for iy in 0..tiles[1] {
@@ -70,29 +139,45 @@ fn render<
     }
     tile_order.shuffle(&mut thread_rng());
 
-    let pb = Arc::new(Mutex::new(ProgressBar::new(
-        "Rendering image",
-        tiles[0] * tiles[1],
-    )));
+    let data = Arc::new(Mutex::new(Output::new(camera.res, num_bins(input))));
+    let pb = ProgressBar::new("Rendering image", tiles[0] * tiles[1]);
     let print_width = ((tiles[0].max(tiles[1])) as f64).log10() as usize + 1;
-    tile_order.par_iter().for_each(|&(ix, iy)| {
-        let offset = [tile_res[0] * ix, tile_res[1] * iy];
-        let data = render_tile(input, camera, offset, tile_res, sample.clone());
-        data.save(
-            &input.shader,
-            output_dir,
-            &format!(
-                "{:0>width$}_{:0>width$}",
-                ix,
-                tiles[1] - iy - 1,
-                width = print_width
-            ),
-        );
-        pb.lock().expect("Could not lock progress bar.").tick();
-    });
-    pb.lock()
-        .expect("Could not lock progress bar.")
-        .finish_with_message("Rendering complete");
+    tile_order.par_iter().try_for_each(|&(ix, iy)| {
+        let (offset, tile_res) = tile_bounds(camera.res, tiles, [ix, iy]);
+        let tile = render_tile(input, camera, offset, tile_res, sample.clone());
+        if let Some(dir) = tiles_output_dir {
+            tile.save(
+                &input.shader,
+                dir,
+                Some(&format!(
+                    "{:0>width$}_{:0>width$}",
+                    ix,
+                    tiles[1] - iy - 1,
+                    width = print_width
+                )),
+            )?;
+        }
+        data.lock()
+            .expect("Could not lock output data.")
+            .paste(&tile, offset);
+        pb.tick();
+
+        Ok(())
+    })?;
+    pb.finish_with_message("Rendering complete");
+
+    Ok(Arc::try_unwrap(data)
+        .ok()
+        .expect("Failed to unwrap output data.")
+        .into_inner()
+        .expect("Could not unlock output data."))
+}
+
+/// Determine the number of wavelength bins of the output data.
+#[inline]
+#[must_use]
+fn num_bins(input: &Input) -> usize {
+    input.shader.spectrum.as_ref().map_or(0, Spectrum::num_bins)
 }
 
 /// Render a sub-tile.
@@ -105,7 +190,7 @@ fn render_tile<T: Fn(&Input<'_>, &Camera, Ray, f64, [usize; 2], &mut Output, &mu
     sub_res: [usize; 2],
     sample: T,
 ) -> Output {
-    let mut data = Output::new(sub_res);
+    let mut data = Output::new(sub_res, num_bins(input));
 
     let weight = 1.0 / (camera.ss_power * camera.ss_power) as f64;
     let mut rng = thread_rng();
@@ -126,3 +211,47 @@ fn render_tile<T: Fn(&Input<'_>, &Camera, Ray, f64, [usize; 2], &mut Output, &mu
 
     data
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiles_partition_the_image() {
+        for &(res, tiles) in &[
+            ([1920, 1080], [172, 72]),
+            ([7, 5], [3, 2]),
+            ([10, 10], [10, 1]),
+            ([8, 8], [4, 4]),
+        ] {
+            let mut covered = vec![0_usize; res[0] * res[1]];
+            for iy in 0..tiles[1] {
+                for ix in 0..tiles[0] {
+                    let (offset, tile_res) = tile_bounds(res, tiles, [ix, iy]);
+                    assert!(tile_res[0] > 0 && tile_res[1] > 0);
+                    for dim in 0..2 {
+                        let width = res[dim] / tiles[dim];
+                        if [ix, iy][dim] == tiles[dim] - 1 {
+                            assert_eq!(tile_res[dim], width + (res[dim] % tiles[dim]));
+                        } else {
+                            assert_eq!(tile_res[dim], width);
+                        }
+                    }
+                    for y in offset[1]..(offset[1] + tile_res[1]) {
+                        for x in offset[0]..(offset[0] + tile_res[0]) {
+                            covered[(y * res[0]) + x] += 1;
+                        }
+                    }
+                }
+            }
+            assert!(covered.iter().all(|&n| n == 1));
+        }
+    }
+
+    #[test]
+    fn invalid_tile_counts_are_rejected() {
+        assert!(check_tiles([0, 1], [4, 4]).is_err());
+        assert!(check_tiles([5, 1], [4, 4]).is_err());
+        assert!(check_tiles([4, 4], [4, 4]).is_ok());
+    }
+}
@@ -0,0 +1,339 @@
+//! Reference sampling engine.
+
+use core::f64::consts::{FRAC_PI_2, PI, TAU};
+use nalgebra::Unit;
+use palette::{Gradient, LinSrgba};
+use rand::{rngs::ThreadRng, Rng};
+use std::time::Instant;
+
+use crate::{
+    phys::Crossing,
+    render::{Attribute, Input, Output},
+    rt::{Camera, Hit, Ray},
+};
+
+/// Golden ratio.
+const GOLDEN_RATIO: f64 = 1.618_033_988_749_895;
+
+/// Wavelength at which refractive indices are evaluated when not rendering spectrally (m).
+/// This is the sodium D line, at which refractive indices are conventionally quoted.
+const REFERENCE_WAVELENGTH: f64 = 589.3e-9;
+
+/// Sample the scene along the given ray and accumulate the result into the output data.
+/// When rendering spectrally, the ray is traced at a wavelength drawn from a randomly chosen bin,
+/// and the spectral value of the observed colour is accumulated into that bin.
+/// Signature matches that required by `render::run`.
+#[inline]
+pub fn paint(
+    input: &Input<'_>,
+    camera: &Camera,
+    ray: Ray,
+    weight: f64,
+    pixel: [usize; 2],
+    data: &mut Output,
+    rng: &mut ThreadRng,
+) {
+    let start_time = Instant::now();
+
+    let col = if let Some(ref spectrum) = input.shader.spectrum {
+        let num_bins = spectrum.num_bins();
+        let bin = rng.gen_range(0..num_bins);
+        let wavelength = spectrum.sample_wavelength(rng, bin);
+        let col = colour(input, camera, ray, wavelength, weight, rng);
+        data.spectrum[[pixel[0], pixel[1], bin]] += spectrum.uplift(bin, &col) * num_bins as f64;
+        col
+    } else {
+        colour(input, camera, ray, REFERENCE_WAVELENGTH, weight, rng)
+    };
+    data.colour[pixel] += col;
+
+    data.time[pixel] += start_time.elapsed().as_secs_f64();
+}
+
+/// Determine the colour observed along the given ray, scaled by the statistical weight.
+/// Refractive indices are evaluated at the given wavelength (m).
+#[inline]
+#[must_use]
+pub fn colour(
+    input: &Input<'_>,
+    camera: &Camera,
+    ray: Ray,
+    wavelength: f64,
+    weight: f64,
+    rng: &mut ThreadRng,
+) -> LinSrgba {
+    trace(
+        input,
+        camera,
+        ray,
+        wavelength,
+        weight,
+        input.settings.loop_limit,
+        rng,
+    )
+}
+
+/// Determine the colour observed along the given ray, within the remaining loop budget.
+/// Rays spawned at refractive surfaces share the budget left to their parent.
+#[inline]
+#[must_use]
+fn trace(
+    input: &Input<'_>,
+    camera: &Camera,
+    mut ray: Ray,
+    wavelength: f64,
+    mut weight: f64,
+    loop_limit: u64,
+    rng: &mut ThreadRng,
+) -> LinSrgba {
+    debug_assert!(weight > 0.0);
+
+    let bump_dist = input.settings.bump_dist;
+    let min_weight = input.settings.min_weight;
+    let mut max_dist = input.settings.max_distance;
+
+    let mut col = LinSrgba::new(0.0, 0.0, 0.0, 0.0);
+
+    let mut num_loops = 0;
+    while let Some(hit) = input.tree.scan(ray.clone(), bump_dist, max_dist) {
+        if num_loops >= loop_limit || weight < min_weight || hit.dist >= max_dist {
+            return col;
+        }
+        num_loops += 1;
+        max_dist -= hit.dist;
+
+        match *hit.tag {
+            Attribute::Opaque(grad) => {
+                ray.travel(hit.dist);
+                col += surface_colour(input, camera, &ray, &hit, grad, rng) * weight as f32;
+                return col;
+            }
+            Attribute::Mirror(grad, abs_frac) => {
+                ray.travel(hit.dist);
+                col += surface_colour(input, camera, &ray, &hit, grad, rng)
+                    * (weight * abs_frac) as f32;
+                weight *= 1.0 - abs_frac;
+                ray.dir = Crossing::calc_ref_dir(&ray.dir, hit.side.norm());
+                ray.travel(bump_dist);
+            }
+            Attribute::Transparent(grad, abs_frac) => {
+                ray.travel(hit.dist);
+                col += surface_colour(input, camera, &ray, &hit, grad, rng)
+                    * (weight * abs_frac) as f32;
+                weight *= 1.0 - abs_frac;
+                ray.travel(bump_dist);
+            }
+            Attribute::Refractive(grad, abs_frac, [ref inside, ref outside]) => {
+                ray.travel(hit.dist);
+                col += surface_colour(input, camera, &ray, &hit, grad, rng)
+                    * (weight * abs_frac) as f32;
+                weight *= 1.0 - abs_frac;
+
+                let (n_curr, n_next) = if hit.side.is_inside() {
+                    (inside, outside)
+                } else {
+                    (outside, inside)
+                };
+                let crossing = Crossing::new(
+                    &ray.dir,
+                    hit.side.norm(),
+                    n_curr.eval(wavelength),
+                    n_next.eval(wavelength),
+                );
+
+                // Transmission ray.
+                if let Some(trans_dir) = crossing.trans_dir {
+                    let trans_weight = weight * crossing.trans_prob();
+                    if trans_weight >= min_weight {
+                        let mut trans = Ray::new(ray.pos, trans_dir);
+                        trans.travel(bump_dist);
+                        col += trace(
+                            input,
+                            camera,
+                            trans,
+                            wavelength,
+                            trans_weight,
+                            loop_limit - num_loops,
+                            rng,
+                        );
+                    }
+                }
+
+                // Reflection ray.
+                weight *= crossing.ref_prob;
+                ray.dir = crossing.ref_dir;
+                ray.travel(bump_dist);
+            }
+            Attribute::Luminous(grad, bright_mult) => {
+                ray.travel(hit.dist);
+                col += surface_colour(input, camera, &ray, &hit, grad, rng)
+                    * (weight * bright_mult) as f32;
+                return col;
+            }
+            Attribute::Switchable([grad_a, grad_b], x) => {
+                ray.travel(hit.dist);
+                let grad = if x <= 0.0 { grad_a } else { grad_b };
+                col += surface_colour(input, camera, &ray, &hit, grad, rng) * weight as f32;
+                return col;
+            }
+        }
+    }
+
+    col + sky_colour(input, &ray) * weight as f32
+}
+
+/// Determine the colour of the sky in the direction of the given ray.
+#[inline]
+#[must_use]
+fn sky_colour(input: &Input<'_>, ray: &Ray) -> LinSrgba {
+    input.shader.sky_grad.get((ray.dir.z.abs()) as f32)
+}
+
+/// Determine the shaded colour of a surface at the ray's current position.
+#[inline]
+#[must_use]
+fn surface_colour(
+    input: &Input<'_>,
+    camera: &Camera,
+    ray: &Ray,
+    hit: &Hit<Attribute>,
+    grad: &Gradient<LinSrgba>,
+    rng: &mut ThreadRng,
+) -> LinSrgba {
+    let light = light(input, camera, ray, hit);
+    let shadow = shadow(input, ray, hit, rng);
+
+    let x = hit.side.norm().dot(&camera.orient.forward).abs();
+    let base_col = grad.get(x as f32);
+
+    let illumination = (light * (1.0 - shadow)).clamp(0.0, 1.0);
+    Gradient::new(vec![LinSrgba::new(0.0, 0.0, 0.0, base_col.alpha), base_col])
+        .get(illumination as f32)
+}
+
+/// Calculate the ambient, diffuse and specular lighting fraction at the ray's current position.
+#[inline]
+#[must_use]
+pub fn light(input: &Input<'_>, camera: &Camera, ray: &Ray, hit: &Hit<Attribute>) -> f64 {
+    let light_dir = Unit::new_normalize(input.shader.sun_pos - ray.pos);
+    let view_dir = Unit::new_normalize(camera.orient.pos - ray.pos);
+    let ref_dir = Crossing::calc_ref_dir(&ray.dir, hit.side.norm());
+
+    let [ambient, mut diffuse, mut specular] = input.shader.light;
+    diffuse *= hit.side.norm().dot(&light_dir).max(0.0);
+    specular *= view_dir.dot(&ref_dir).max(0.0).powi(input.shader.spec_pow);
+
+    ambient + diffuse + specular
+}
+
+/// Calculate the shadowing fraction at the ray's current position.
+/// Combines ambient occlusion and (optionally soft) direct shadowing.
+#[inline]
+#[must_use]
+pub fn shadow(input: &Input<'_>, ray: &Ray, hit: &Hit<Attribute>, rng: &mut ThreadRng) -> f64 {
+    let bump_dist = input.settings.bump_dist;
+    let [ambient_frac, direct_frac] = input.shader.shadow;
+    let [ambient_dist, direct_dist] = input.shader.occ_dist;
+
+    let mut norm_ray = Ray::new(ray.pos, *hit.side.norm());
+    norm_ray.travel(bump_dist);
+
+    let ambient = if let Some((samples, power)) = input.shader.ambient_shadow_samples {
+        let offset = rng.gen_range(0.0..TAU);
+        let mut total = 0.0;
+        for n in 0..samples {
+            let (r, theta) = golden_circle(n, samples);
+            let mut sample_ray = norm_ray.clone();
+            sample_ray.rotate(r * FRAC_PI_2, theta + offset);
+            total += input
+                .tree
+                .scan(sample_ray, bump_dist, ambient_dist)
+                .map_or(1.0, |occ| occ.dist.min(ambient_dist) / ambient_dist);
+        }
+        1.0 - (total / f64::from(samples)).powi(power)
+    } else {
+        0.0
+    };
+
+    let sun_ray = Ray::new(
+        norm_ray.pos,
+        Unit::new_normalize(input.shader.sun_pos - norm_ray.pos),
+    );
+    let direct = if let Some((samples, angle)) = input.shader.soft_shadow_samples {
+        let offset = rng.gen_range(0.0..TAU);
+        let mut total = 0.0;
+        for n in 0..samples {
+            let (r, theta) = golden_circle(n, samples);
+            let mut sample_ray = sun_ray.clone();
+            sample_ray.rotate(r * angle, theta + offset);
+            total += occlusion(input, sample_ray, direct_dist);
+        }
+        1.0 - (total / f64::from(samples))
+    } else {
+        1.0 - occlusion(input, sun_ray, direct_dist)
+    };
+
+    ambient.mul_add(ambient_frac, direct * direct_frac)
+}
+
+/// Calculate the visibility of a given ray over a maximum distance.
+/// Returns unity for an unobstructed path, and zero for a fully occluded one.
+#[inline]
+#[must_use]
+pub fn occlusion(input: &Input<'_>, mut ray: Ray, mut max_dist: f64) -> f64 {
+    debug_assert!(max_dist > 0.0);
+
+    let bump_dist = input.settings.bump_dist;
+    let fall_off = input.shader.fall_off;
+    let loop_limit = input.settings.loop_limit;
+
+    let mut vis = 1.0;
+    let mut num_loops = 0;
+    while let Some(hit) = input.tree.scan(ray.clone(), bump_dist, max_dist) {
+        if num_loops >= loop_limit || hit.dist >= max_dist {
+            return vis;
+        }
+        num_loops += 1;
+        max_dist -= hit.dist;
+
+        if vis <= 0.0 {
+            return 0.0;
+        }
+
+        let dimming = 1.0 / hit.dist.mul_add(fall_off, 1.0);
+        match *hit.tag {
+            Attribute::Opaque(..) | Attribute::Switchable(..) => {
+                return vis * (1.0 - dimming);
+            }
+            Attribute::Luminous(..) => {
+                return vis;
+            }
+            Attribute::Mirror(_, abs_frac) => {
+                ray.travel(hit.dist);
+                vis *= abs_frac.mul_add(-dimming, 1.0);
+                ray.dir = Crossing::calc_ref_dir(&ray.dir, hit.side.norm());
+                ray.travel(bump_dist);
+            }
+            Attribute::Transparent(_, abs_frac) | Attribute::Refractive(_, abs_frac, ..) => {
+                ray.travel(hit.dist + bump_dist);
+                vis *= abs_frac.mul_add(-dimming, 1.0);
+            }
+        }
+    }
+
+    vis
+}
+
+/// Sample points within a unit circle using the golden-ratio spiral.
+/// Returns the radius and polar angle of the sample point.
+#[inline]
+#[must_use]
+fn golden_circle(n: i32, max: i32) -> (f64, f64) {
+    debug_assert!(n >= 0);
+    debug_assert!(n < max);
+
+    let r = (f64::from(n) / f64::from(max)).sqrt();
+    let theta = 2.0 * PI * f64::from(n) / GOLDEN_RATIO;
+
+    (r, theta)
+}
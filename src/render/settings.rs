@@ -15,4 +15,6 @@ pub struct Settings {
     pub min_weight: f64,
     /// Maximum ray travel distance.
     pub max_distance: f64,
+    /// Optionally save each rendered tile as a separate image.
+    pub save_tiles: Option<bool>,
 }
@@ -3,6 +3,8 @@
 use nalgebra::Point3;
 use palette::{Gradient, LinSrgba};
 
+use crate::render::Spectrum;
+
 /// Shader settings.
 pub struct Shader<'a> {
     /// Sun position (m).
@@ -25,6 +27,8 @@ pub struct Shader<'a> {
     pub sky_grad: &'a Gradient<LinSrgba>,
     /// Data colouring gradient.
     pub data_grad: &'a Gradient<LinSrgba>,
+    /// Optional wavelength binning, enabling spectral rendering.
+    pub spectrum: Option<Spectrum>,
 }
 
 impl<'a> Shader<'a> {
@@ -43,6 +47,7 @@ impl<'a> Shader<'a> {
         ambient_shadow_samples: Option<(i32, i32)>,
         sky_grad: &'a Gradient<LinSrgba>,
         data_grad: &'a Gradient<LinSrgba>,
+        spectrum: Option<Spectrum>,
     ) -> Self {
         debug_assert!(light[0] > 0.0);
         debug_assert!(light[1] > 0.0);
@@ -100,6 +105,7 @@ impl<'a> Shader<'a> {
             ambient_shadow_samples,
             sky_grad,
             data_grad,
+            spectrum,
         }
     }
 }
@@ -5,7 +5,10 @@ use palette::{Gradient, LinSrgba};
 use serde::Deserialize;
 use std::collections::HashMap;
 
-use crate::render::Shader;
+use crate::{
+    render::{Shader, Spectrum},
+    Error,
+};
 
 /// Aesthetic settings.
 #[derive(Deserialize)]
@@ -30,6 +33,8 @@ pub struct ShaderBuilder {
     sky_grad: String,
     /// Data colouring gradient.
     data_grad: String,
+    /// Optional number of wavelength bins, and wavelength range (m), enabling spectral rendering.
+    spectral: Option<(usize, [f64; 2])>,
 }
 
 impl ShaderBuilder {
@@ -43,16 +48,38 @@ impl ShaderBuilder {
 
 impl<'a> ShaderBuilder {
     /// Build the `Shader`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a shading parameter is out of range,
+    /// if a required `Gradient` is not present in the given dictionary,
+    /// or if the spectral bins can not resolve each colour channel.
     #[inline]
-    #[must_use]
-    pub fn build(&self, grads: &'a HashMap<String, Gradient<LinSrgba>>) -> Shader<'a> {
+    pub fn build(
+        &self,
+        grads: &'a HashMap<String, Gradient<LinSrgba>>,
+    ) -> Result<Shader<'a>, Error> {
+        self.check()?;
+
         let soft_shadow_samples = if let Some((n, alpha)) = self.soft_shadow_samples {
             Some((n, alpha.to_radians()))
         } else {
             None
         };
 
-        Shader::new(
+        let spectrum = if let Some((num_bins, range)) = self.spectral {
+            Some(Spectrum::new(num_bins, range)?)
+        } else {
+            None
+        };
+
+        let link = |name: &String| {
+            grads
+                .get(name)
+                .ok_or_else(|| Error::MissingKey("gradient", name.clone()))
+        };
+
+        Ok(Shader::new(
             self.sun_pos,
             self.light,
             self.shadow,
@@ -61,12 +88,62 @@ impl<'a> ShaderBuilder {
             self.fall_off,
             soft_shadow_samples,
             self.ambient_shadow_samples,
-            grads.get(&self.sky_grad).unwrap_or_else(|| {
-                panic!("Failed to link shader-gradient key: {}", &self.sky_grad)
-            }),
-            grads.get(&self.data_grad).unwrap_or_else(|| {
-                panic!("Failed to link shader-gradient key: {}", &self.data_grad)
-            }),
-        )
+            link(&self.sky_grad)?,
+            link(&self.data_grad)?,
+            spectrum,
+        ))
+    }
+
+    /// Check the shading parameters are within their valid ranges.
+    #[inline]
+    fn check(&self) -> Result<(), Error> {
+        let invalid = |msg: &str| Err(Error::InvalidParameter(msg.to_owned()));
+
+        if self.light.iter().any(|x| *x <= 0.0) {
+            return invalid("Shader lighting fractions must be positive.");
+        }
+        if self.shadow.iter().any(|x| *x <= 0.0) {
+            return invalid("Shader shadowing fractions must be positive.");
+        }
+        if self.spec_pow <= 0 {
+            return invalid("Shader specular power must be positive.");
+        }
+        if self.occ_dist.iter().any(|x| *x <= 0.0) {
+            return invalid("Shader occlusion distances must be positive.");
+        }
+        if self.fall_off <= 0.0 {
+            return invalid("Shader fall-off rate must be positive.");
+        }
+        if let Some((n, alpha)) = self.soft_shadow_samples {
+            if n <= 1 {
+                return invalid("Shader soft shadowing requires more than one sample.");
+            }
+            if alpha <= 0.0 {
+                return invalid("Shader soft shadowing angular radius must be positive.");
+            }
+        }
+        if let Some((n, power)) = self.ambient_shadow_samples {
+            if n <= 1 {
+                return invalid("Shader ambient shadowing requires more than one sample.");
+            }
+            if power <= 0 {
+                return invalid("Shader ambient shadowing scaling power must be positive.");
+            }
+        }
+
+        if let Some((num_bins, range)) = self.spectral {
+            if num_bins < 3 {
+                return invalid(
+                    "Shader spectral rendering requires at least three wavelength bins.",
+                );
+            }
+            if range[0] <= 0.0 || range[0] >= range[1] {
+                return invalid(
+                    "Shader spectral wavelength range must be positive and increasing.",
+                );
+            }
+        }
+
+        Ok(())
     }
 }
@@ -0,0 +1,214 @@
+//! Spectral colour conversion.
+
+use nalgebra::{Matrix3, Vector3};
+use ndarray::ArrayView1;
+use palette::LinSrgba;
+use rand::Rng;
+
+use crate::Error;
+
+/// Wavelength binning, and conversion between spectral values and linear sRGB colours.
+#[derive(Clone)]
+pub struct Spectrum {
+    /// Wavelength range (m).
+    range: [f64; 2],
+    /// Linear sRGB response to a unit spectral value in each bin.
+    responses: Vec<Vector3<f64>>,
+    /// Spectral value in each bin per unit of each linear sRGB channel.
+    uplifts: Vec<Vector3<f64>>,
+}
+
+impl Spectrum {
+    /// Construct a new instance with the given number of bins spanning the wavelength range (m).
+    /// Each bin is assigned to the colour channel it contributes most to,
+    /// and colours are lifted to spectra which are constant over the bins of each channel.
+    /// A spectrum lifted from a colour therefore converts back to the same colour.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bins do not resolve each of the red, green and blue channels.
+    #[inline]
+    pub fn new(num_bins: usize, range: [f64; 2]) -> Result<Self, Error> {
+        debug_assert!(num_bins > 0);
+        debug_assert!(range[0] > 0.0);
+        debug_assert!(range[0] < range[1]);
+
+        let width = (range[1] - range[0]) / num_bins as f64;
+        let xyz_to_rgb = xyz_to_linear_srgb();
+        let responses: Vec<_> = (0..num_bins)
+            .map(|bin| {
+                let wavelength = (bin as f64 + 0.5).mul_add(width, range[0]);
+                xyz_to_rgb * colour_matching(wavelength) * (width * 1.0e9)
+            })
+            .collect();
+        let channels: Vec<_> = responses.iter().map(|res| res.imax()).collect();
+
+        let mut totals = Matrix3::zeros();
+        for (res, &channel) in responses.iter().zip(&channels) {
+            let total = totals.row(channel) + res.transpose();
+            totals.set_row(channel, &total);
+        }
+        let inv = totals.transpose().try_inverse().ok_or_else(|| {
+            Error::InvalidParameter(format!(
+                "Spectral bins over {range:?} must resolve the red, green and blue channels."
+            ))
+        })?;
+        let uplifts = channels
+            .iter()
+            .map(|&channel| inv.row(channel).transpose())
+            .collect();
+
+        Ok(Self {
+            range,
+            responses,
+            uplifts,
+        })
+    }
+
+    /// Get the number of wavelength bins.
+    #[inline]
+    #[must_use]
+    pub fn num_bins(&self) -> usize {
+        self.responses.len()
+    }
+
+    /// Sample a wavelength (m) uniformly from within a bin.
+    #[inline]
+    #[must_use]
+    pub fn sample_wavelength<R: Rng>(&self, rng: &mut R, bin: usize) -> f64 {
+        debug_assert!(bin < self.num_bins());
+
+        let width = (self.range[1] - self.range[0]) / self.num_bins() as f64;
+        (bin as f64 + rng.gen::<f64>()).mul_add(width, self.range[0])
+    }
+
+    /// Determine the spectral value within a bin of the spectrum lifted from a colour.
+    /// Values may be negative for colours which are more saturated than the bins can represent.
+    #[inline]
+    #[must_use]
+    pub fn uplift(&self, bin: usize, col: &LinSrgba) -> f64 {
+        let rgb = Vector3::new(
+            f64::from(col.red),
+            f64::from(col.green),
+            f64::from(col.blue),
+        );
+        self.uplifts[bin].dot(&rgb)
+    }
+
+    /// Convert a binned spectrum to a colour with the given opacity.
+    #[inline]
+    #[must_use]
+    pub fn colour(&self, spectrum: ArrayView1<f64>, alpha: f32) -> LinSrgba {
+        debug_assert!(spectrum.len() == self.num_bins());
+
+        let rgb = self
+            .responses
+            .iter()
+            .zip(spectrum)
+            .fold(Vector3::zeros(), |acc, (res, &value)| acc + (res * value));
+
+        LinSrgba::new(rgb.x as f32, rgb.y as f32, rgb.z as f32, alpha)
+    }
+}
+
+/// Evaluate the CIE 1931 colour matching functions at the given wavelength (m).
+/// Uses the multi-lobe Gaussian fit of Wyman, Sloan and Shirley (2013).
+#[inline]
+#[must_use]
+pub fn colour_matching(wavelength: f64) -> Vector3<f64> {
+    let nm = wavelength * 1.0e9;
+    let lobe = |mean: f64, lower_width: f64, upper_width: f64| {
+        let t = (nm - mean) / if nm < mean { lower_width } else { upper_width };
+        (-0.5 * t * t).exp()
+    };
+
+    Vector3::new(
+        1.056f64.mul_add(
+            lobe(599.8, 37.9, 31.0),
+            0.362f64.mul_add(lobe(442.0, 16.0, 26.7), -0.065 * lobe(501.1, 20.4, 26.2)),
+        ),
+        0.821f64.mul_add(lobe(568.8, 46.9, 40.5), 0.286 * lobe(530.9, 16.3, 31.1)),
+        1.217f64.mul_add(lobe(437.0, 11.8, 36.0), 0.681 * lobe(459.0, 26.0, 13.8)),
+    )
+}
+
+/// Construct the transformation from CIE 1931 XYZ to linear sRGB, under the D65 white point.
+#[inline]
+#[must_use]
+fn xyz_to_linear_srgb() -> Matrix3<f64> {
+    #[rustfmt::skip]
+    let mat = Matrix3::new(
+        3.240_454_2,  -1.537_138_5, -0.498_531_4,
+        -0.969_266_0, 1.876_010_8,  0.041_556_0,
+        0.055_643_4,  -0.204_025_9, 1.057_225_2,
+    );
+
+    mat
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::Array1;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// Visible wavelength range (m).
+    const VISIBLE: [f64; 2] = [380.0e-9, 780.0e-9];
+
+    #[test]
+    fn uplift_colour_round_trip() {
+        let spectrum = Spectrum::new(30, VISIBLE).expect("Failed to construct spectrum.");
+
+        for col in [
+            LinSrgba::new(1.0, 1.0, 1.0, 1.0),
+            LinSrgba::new(0.8, 0.2, 0.1, 1.0),
+            LinSrgba::new(0.05, 0.6, 0.3, 0.5),
+            LinSrgba::new(0.0, 0.0, 1.0, 1.0),
+        ] {
+            let values =
+                Array1::from_iter((0..spectrum.num_bins()).map(|bin| spectrum.uplift(bin, &col)));
+            let round_trip = spectrum.colour(values.view(), col.alpha);
+
+            assert!((round_trip.red - col.red).abs() < 1.0e-5);
+            assert!((round_trip.green - col.green).abs() < 1.0e-5);
+            assert!((round_trip.blue - col.blue).abs() < 1.0e-5);
+            assert_eq!(round_trip.alpha, col.alpha);
+        }
+    }
+
+    #[test]
+    fn sampled_wavelengths_lie_within_their_bins() {
+        let spectrum = Spectrum::new(10, VISIBLE).expect("Failed to construct spectrum.");
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for bin in 0..spectrum.num_bins() {
+            let lower = VISIBLE[0] + (bin as f64 * 40.0e-9);
+            for _ in 0..100 {
+                let wavelength = spectrum.sample_wavelength(&mut rng, bin);
+                assert!(wavelength >= lower - 1.0e-18 && wavelength <= lower + 40.0e-9 + 1.0e-18);
+            }
+        }
+    }
+
+    #[test]
+    fn colour_matching_peaks() {
+        let peak = |channel: usize| {
+            (380..=780)
+                .max_by(|&a, &b| {
+                    colour_matching(f64::from(a) * 1.0e-9)[channel]
+                        .total_cmp(&colour_matching(f64::from(b) * 1.0e-9)[channel])
+                })
+                .expect("Empty range.")
+        };
+
+        assert!((595..=605).contains(&peak(0)));
+        assert!((550..=560).contains(&peak(1)));
+        assert!((440..=450).contains(&peak(2)));
+    }
+
+    #[test]
+    fn unresolved_channels_are_rejected() {
+        assert!(Spectrum::new(1, VISIBLE).is_err());
+        assert!(Spectrum::new(4, [600.0e-9, 700.0e-9]).is_err());
+    }
+}
@@ -3,7 +3,10 @@
 use nalgebra::Point3;
 use serde::Deserialize;
 
-use crate::rt::{Camera, Orientation};
+use crate::{
+    parse::SceneCamera,
+    rt::{Camera, Orientation},
+};
 
 /// Camera settings.
 #[derive(Clone, Deserialize)]
@@ -21,6 +24,19 @@ pub struct CameraBuilder {
 }
 
 impl CameraBuilder {
+    /// Replace the position, target and field-of-view with those of a scene camera.
+    /// The resolution and super-sampling power are kept.
+    #[inline]
+    #[must_use]
+    pub fn with_scene_camera(self, cam: &SceneCamera) -> Self {
+        Self {
+            pos: cam.pos,
+            tar: cam.tar,
+            fov: cam.hfov(self.res).to_degrees(),
+            ..self
+        }
+    }
+
     /// Build the Camera.
     #[inline]
     #[must_use]
@@ -5,7 +5,6 @@ pub mod camera_builder;
 pub mod hit;
 pub mod orientation;
 pub mod ray;
-pub mod scan;
 pub mod side;
 
-pub use self::{camera::*, camera_builder::*, hit::*, orientation::*, ray::*, scan::*, side::*};
+pub use self::{camera::*, camera_builder::*, hit::*, orientation::*, ray::*, side::*};
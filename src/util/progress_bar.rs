@@ -1,13 +1,15 @@
 //! Progress-Bar implementation.
 
 use atty::Stream;
+use core::sync::atomic::{AtomicUsize, Ordering};
 
 /// Progress-bar structure.
+/// Progress may be reported concurrently from multiple threads.
 pub struct ProgressBar {
     /// Graphics.
     pb: indicatif::ProgressBar,
     /// Current value.
-    count: usize,
+    count: AtomicUsize,
     /// Total target value.
     total: usize,
 }
@@ -30,7 +32,7 @@ impl ProgressBar {
 
         Self {
             pb,
-            count: 0,
+            count: AtomicUsize::new(0),
             total,
         }
     }
@@ -38,12 +40,12 @@ impl ProgressBar {
     /// Tick the bar forward a single increment.
     #[allow(clippy::print_stdout)]
     #[inline]
-    pub fn tick(&mut self) {
-        self.count += 1;
+    pub fn tick(&self) {
+        let count = self.count.fetch_add(1, Ordering::Relaxed) + 1;
         self.pb.inc(1);
 
         if !atty::is(Stream::Stdout) {
-            println!("{:.2}", self.count as f64 / self.total as f64 * 100.0);
+            println!("{:.2}", count as f64 / self.total as f64 * 100.0);
         }
     }
 
@@ -53,39 +55,36 @@ impl ProgressBar {
     /// If there are none at all, return None.
     #[inline]
     #[allow(clippy::print_stdout)]
-    pub fn block(&mut self, size: usize) -> Option<(usize, usize)> {
+    pub fn block(&self, size: usize) -> Option<(usize, usize)> {
         debug_assert!(size > 0);
 
-        if self.count >= self.total {
-            None
-        } else {
-            let remaining = self.total - self.count;
-            let alloc = size.min(remaining);
+        let start = self
+            .count
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+                (count < self.total).then(|| (count + size).min(self.total))
+            })
+            .ok()?;
+        let end = (start + size).min(self.total);
 
-            let start = self.count;
-            let end = start + alloc;
+        self.pb.inc((end - start) as u64);
 
-            self.count += alloc;
-            self.pb.inc(alloc as u64);
-
-            if !atty::is(Stream::Stdout) {
-                println!("{:.2}", self.count as f64 / self.total as f64 * 100.0);
-            }
-
-            Some((start, end))
+        if !atty::is(Stream::Stdout) {
+            println!("{:.2}", end as f64 / self.total as f64 * 100.0);
         }
+
+        Some((start, end))
     }
 
     /// Check if the progress bar is complete.
     #[inline]
     #[must_use]
-    pub const fn is_done(&self) -> bool {
-        self.count >= self.total
+    pub fn is_done(&self) -> bool {
+        self.count.load(Ordering::Relaxed) >= self.total
     }
 
     /// Finish with a message.
     #[inline]
-    pub fn finish_with_message(&mut self, msg: &'static str) {
+    pub fn finish_with_message(&self, msg: &'static str) {
         self.pb.finish_with_message(msg);
     }
 }